@@ -0,0 +1,7 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS trg_cart_items_open_only ON cart_items;
+DROP FUNCTION IF EXISTS reject_closed_cart_item_change;
+DROP TABLE IF EXISTS order_items;
+DROP TABLE IF EXISTS orders;
+DROP FUNCTION IF EXISTS reject_order_change;
+DROP SEQUENCE IF EXISTS order_number_seq;
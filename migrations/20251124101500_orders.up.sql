@@ -0,0 +1,76 @@
+-- Add up migration script here
+-- cart_status values are matched lowercase by the application (CartStatus / 'open'::cart_status)
+DO $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM pg_enum e JOIN pg_type t ON t.oid = e.enumtypid
+               WHERE t.typname = 'cart_status' AND e.enumlabel = 'Open') THEN
+        ALTER TYPE cart_status RENAME VALUE 'Open' TO 'open';
+        ALTER TYPE cart_status RENAME VALUE 'Paid' TO 'paid';
+        ALTER TYPE cart_status RENAME VALUE 'Refund' TO 'refund';
+        ALTER TYPE cart_status RENAME VALUE 'FOC' TO 'foc';
+    END IF;
+END
+$$;
+
+ALTER TABLE carts ALTER COLUMN status SET DEFAULT 'open';
+
+CREATE SEQUENCE IF NOT EXISTS order_number_seq START WITH 1000;
+
+-- Immutable sale record written once at checkout.
+CREATE TABLE orders (
+    id           UUID PRIMARY KEY DEFAULT (uuid_generate_v4()),
+    order_number BIGINT NOT NULL UNIQUE DEFAULT nextval('order_number_seq'),
+    cart_id      UUID NOT NULL UNIQUE REFERENCES carts(id),
+    user_id      UUID NOT NULL REFERENCES users(id),
+    total_amount DOUBLE PRECISION NOT NULL,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+ALTER SEQUENCE order_number_seq OWNED BY orders.order_number;
+
+CREATE TABLE order_items (
+    id           BIGSERIAL PRIMARY KEY,
+    order_id     UUID NOT NULL REFERENCES orders(id),
+    cart_item_id BIGINT NOT NULL REFERENCES cart_items(id),
+    product_id   UUID NOT NULL REFERENCES products(id),
+    product_name VARCHAR(255) NOT NULL,
+    quantity     INTEGER NOT NULL CHECK (quantity > 0),
+    unit_amount  DOUBLE PRECISION NOT NULL,
+    line_total   DOUBLE PRECISION NOT NULL,
+    UNIQUE (order_id, cart_item_id)
+);
+
+CREATE INDEX ix_order_items_order ON order_items(order_id);
+
+CREATE OR REPLACE FUNCTION reject_order_change() RETURNS trigger AS $$
+BEGIN
+  RAISE EXCEPTION 'Orders are immutable once written'
+    USING ERRCODE = '55000';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_orders_immutable
+BEFORE UPDATE OR DELETE ON orders
+FOR EACH ROW EXECUTE FUNCTION reject_order_change();
+
+CREATE TRIGGER trg_order_items_immutable
+BEFORE UPDATE OR DELETE ON order_items
+FOR EACH ROW EXECUTE FUNCTION reject_order_change();
+
+-- Items of a cart that is no longer open are frozen.
+CREATE OR REPLACE FUNCTION reject_closed_cart_item_change() RETURNS trigger AS $$
+BEGIN
+  IF EXISTS (
+      SELECT 1 FROM carts
+      WHERE id = COALESCE(NEW.cart_id, OLD.cart_id) AND status <> 'open'
+  ) THEN
+    RAISE EXCEPTION 'Cart is no longer open'
+      USING ERRCODE = '55000';
+  END IF;
+  RETURN COALESCE(NEW, OLD);
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_cart_items_open_only
+BEFORE INSERT OR UPDATE OR DELETE ON cart_items
+FOR EACH ROW EXECUTE FUNCTION reject_closed_cart_item_change();
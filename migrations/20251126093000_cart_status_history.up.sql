@@ -0,0 +1,11 @@
+-- Add up migration script here
+CREATE TABLE cart_status_history (
+    id          BIGSERIAL PRIMARY KEY,
+    cart_id     UUID NOT NULL REFERENCES carts(id) ON DELETE CASCADE,
+    from_status cart_status NOT NULL,
+    to_status   cart_status NOT NULL,
+    changed_by  UUID REFERENCES users(id) ON DELETE SET NULL,
+    changed_at  TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX ix_cart_status_history_cart ON cart_status_history(cart_id, changed_at);
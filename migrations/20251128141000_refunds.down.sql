@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS refund_items;
+DROP TABLE IF EXISTS refunds;
+DROP FUNCTION IF EXISTS guard_refund_item_quantity;
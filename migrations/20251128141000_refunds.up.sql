@@ -0,0 +1,54 @@
+-- Add up migration script here
+CREATE TABLE refunds (
+    id           UUID PRIMARY KEY DEFAULT (uuid_generate_v4()),
+    cart_id      UUID NOT NULL REFERENCES carts(id),
+    order_id     UUID NOT NULL REFERENCES orders(id),
+    refunded_by  UUID REFERENCES users(id) ON DELETE SET NULL,
+    reason       TEXT,
+    total_amount DOUBLE PRECISION NOT NULL,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX ix_refunds_cart ON refunds(cart_id);
+
+-- Reversal lines point back at the sold cart_items row they reverse.
+CREATE TABLE refund_items (
+    id           BIGSERIAL PRIMARY KEY,
+    refund_id    UUID NOT NULL REFERENCES refunds(id),
+    cart_item_id BIGINT NOT NULL REFERENCES cart_items(id),
+    product_id   UUID NOT NULL REFERENCES products(id),
+    quantity     INTEGER NOT NULL CHECK (quantity > 0),
+    unit_amount  DOUBLE PRECISION NOT NULL,
+    line_total   DOUBLE PRECISION NOT NULL
+);
+
+CREATE INDEX ix_refund_items_cart_item ON refund_items(cart_item_id);
+
+-- A sold line can be refunded across several refunds, but never beyond what was sold.
+CREATE OR REPLACE FUNCTION guard_refund_item_quantity() RETURNS trigger AS $$
+DECLARE
+  v_sold INTEGER;
+  v_refunded INTEGER;
+BEGIN
+  SELECT quantity INTO v_sold FROM cart_items WHERE id = NEW.cart_item_id FOR UPDATE;
+  SELECT COALESCE(SUM(quantity), 0) INTO v_refunded
+    FROM refund_items WHERE cart_item_id = NEW.cart_item_id;
+  IF v_refunded + NEW.quantity > v_sold THEN
+    RAISE EXCEPTION 'Cart item % has already been refunded', NEW.cart_item_id
+      USING ERRCODE = '23514', COLUMN = 'cart_item_id';
+  END IF;
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_refund_items_guard
+BEFORE INSERT ON refund_items
+FOR EACH ROW EXECUTE FUNCTION guard_refund_item_quantity();
+
+CREATE TRIGGER trg_refunds_immutable
+BEFORE UPDATE OR DELETE ON refunds
+FOR EACH ROW EXECUTE FUNCTION reject_order_change();
+
+CREATE TRIGGER trg_refund_items_immutable
+BEFORE UPDATE OR DELETE ON refund_items
+FOR EACH ROW EXECUTE FUNCTION reject_order_change();
@@ -0,0 +1,16 @@
+-- Add down migration script here
+ALTER TABLE order_items
+    DROP COLUMN IF EXISTS price_override_reason,
+    DROP COLUMN IF EXISTS list_amount,
+    DROP COLUMN IF EXISTS sell_unit;
+
+ALTER TABLE cart_items
+    DROP CONSTRAINT IF EXISTS cart_items_cart_id_product_id_sell_unit_key,
+    DROP CONSTRAINT IF EXISTS cart_items_price_override_check,
+    DROP COLUMN IF EXISTS price_overridden_by,
+    DROP COLUMN IF EXISTS price_override_reason,
+    DROP COLUMN IF EXISTS list_amount,
+    DROP COLUMN IF EXISTS sell_unit,
+    ADD CONSTRAINT cart_items_cart_id_product_id_key UNIQUE (cart_id, product_id);
+
+DROP TYPE IF EXISTS sell_unit;
@@ -0,0 +1,32 @@
+-- Add up migration script here
+-- Cart lines are priced from the catalog per sell unit; any other price is a recorded override.
+CREATE TYPE sell_unit AS ENUM ('unit','pack');
+
+ALTER TABLE cart_items
+    ADD COLUMN sell_unit sell_unit NOT NULL DEFAULT 'unit',
+    ADD COLUMN list_amount DOUBLE PRECISION,
+    ADD COLUMN price_override_reason TEXT,
+    ADD COLUMN price_overridden_by UUID REFERENCES users(id) ON DELETE SET NULL;
+
+-- Backfill closed carts too, so bypass the open-only and recalculation triggers.
+ALTER TABLE cart_items DISABLE TRIGGER USER;
+UPDATE cart_items SET list_amount = unit_amount;
+ALTER TABLE cart_items ENABLE TRIGGER USER;
+
+ALTER TABLE cart_items
+    ALTER COLUMN list_amount SET NOT NULL,
+    ADD CONSTRAINT cart_items_price_override_check
+        CHECK (price_override_reason IS NOT NULL OR unit_amount = list_amount),
+    DROP CONSTRAINT cart_items_cart_id_product_id_key,
+    ADD CONSTRAINT cart_items_cart_id_product_id_sell_unit_key UNIQUE (cart_id, product_id, sell_unit);
+
+ALTER TABLE order_items
+    ADD COLUMN sell_unit sell_unit NOT NULL DEFAULT 'unit',
+    ADD COLUMN list_amount DOUBLE PRECISION,
+    ADD COLUMN price_override_reason TEXT;
+
+ALTER TABLE order_items DISABLE TRIGGER USER;
+UPDATE order_items SET list_amount = unit_amount;
+ALTER TABLE order_items ENABLE TRIGGER USER;
+
+ALTER TABLE order_items ALTER COLUMN list_amount SET NOT NULL;
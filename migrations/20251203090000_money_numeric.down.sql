@@ -0,0 +1,43 @@
+-- Add down migration script here
+CREATE OR REPLACE FUNCTION recalc_cart_total() RETURNS trigger AS $$
+DECLARE
+  v_cart UUID;
+BEGIN
+  v_cart := COALESCE(NEW.cart_id, OLD.cart_id);
+  UPDATE carts c
+    SET total_amount = (
+        SELECT COALESCE(SUM(line_total), 0)
+        FROM cart_items ci
+        WHERE ci.cart_id = v_cart
+    ),
+    updated_at = now()
+  WHERE c.id = v_cart;
+  RETURN COALESCE(NEW, OLD);
+END;
+$$ LANGUAGE plpgsql;
+
+ALTER TABLE refund_items
+    ALTER COLUMN unit_amount TYPE DOUBLE PRECISION,
+    ALTER COLUMN line_total TYPE DOUBLE PRECISION;
+ALTER TABLE refunds ALTER COLUMN total_amount TYPE DOUBLE PRECISION;
+
+ALTER TABLE order_items
+    ALTER COLUMN unit_amount TYPE DOUBLE PRECISION,
+    ALTER COLUMN list_amount TYPE DOUBLE PRECISION,
+    ALTER COLUMN line_total TYPE DOUBLE PRECISION;
+ALTER TABLE orders ALTER COLUMN total_amount TYPE DOUBLE PRECISION;
+
+ALTER TABLE carts ALTER COLUMN total_amount TYPE DOUBLE PRECISION;
+
+ALTER TABLE cart_items DROP COLUMN line_total;
+ALTER TABLE cart_items
+    ALTER COLUMN unit_amount TYPE DOUBLE PRECISION,
+    ALTER COLUMN list_amount TYPE DOUBLE PRECISION;
+ALTER TABLE cart_items
+    ADD COLUMN line_total DOUBLE PRECISION GENERATED ALWAYS AS (unit_amount * quantity) STORED;
+
+ALTER TABLE products
+    DROP CONSTRAINT IF EXISTS products_pack_price_check,
+    DROP CONSTRAINT IF EXISTS products_price_check,
+    ALTER COLUMN price TYPE DOUBLE PRECISION,
+    ALTER COLUMN pack_price TYPE DOUBLE PRECISION;
@@ -0,0 +1,62 @@
+-- Add up migration script here
+-- Money moves from DOUBLE PRECISION to exact NUMERIC(12,2).
+-- Rounding rule: every amount is rounded half away from zero to 2 places;
+-- a line total is round(unit_amount * quantity, 2) and a cart total is the sum
+-- of its rounded line totals, so totals never drift by fractions of a cent.
+ALTER TABLE products
+    ALTER COLUMN price TYPE NUMERIC(12,2) USING round(price::numeric, 2),
+    ALTER COLUMN pack_price TYPE NUMERIC(12,2) USING round(pack_price::numeric, 2),
+    ADD CONSTRAINT products_price_check CHECK (price >= 0),
+    ADD CONSTRAINT products_pack_price_check CHECK (pack_price IS NULL OR pack_price >= 0);
+
+-- Column rewrites do not fire row triggers, so closed carts and the
+-- immutable order/refund tables are converted in place.
+ALTER TABLE cart_items DROP COLUMN line_total;
+ALTER TABLE cart_items
+    ALTER COLUMN unit_amount TYPE NUMERIC(12,2) USING round(unit_amount::numeric, 2),
+    ALTER COLUMN list_amount TYPE NUMERIC(12,2) USING round(list_amount::numeric, 2);
+ALTER TABLE cart_items
+    ADD COLUMN line_total NUMERIC(14,2) GENERATED ALWAYS AS (round(unit_amount * quantity, 2)) STORED;
+
+ALTER TABLE carts
+    ALTER COLUMN total_amount TYPE NUMERIC(14,2) USING round(total_amount::numeric, 2);
+
+ALTER TABLE orders
+    ALTER COLUMN total_amount TYPE NUMERIC(14,2) USING round(total_amount::numeric, 2);
+ALTER TABLE order_items
+    ALTER COLUMN unit_amount TYPE NUMERIC(12,2) USING round(unit_amount::numeric, 2),
+    ALTER COLUMN list_amount TYPE NUMERIC(12,2) USING round(list_amount::numeric, 2),
+    ALTER COLUMN line_total TYPE NUMERIC(14,2) USING round(line_total::numeric, 2);
+
+ALTER TABLE refunds
+    ALTER COLUMN total_amount TYPE NUMERIC(14,2) USING round(total_amount::numeric, 2);
+ALTER TABLE refund_items
+    ALTER COLUMN unit_amount TYPE NUMERIC(12,2) USING round(unit_amount::numeric, 2),
+    ALTER COLUMN line_total TYPE NUMERIC(14,2) USING round(line_total::numeric, 2);
+
+CREATE OR REPLACE FUNCTION recalc_cart_total() RETURNS trigger AS $$
+DECLARE
+  v_cart UUID;
+BEGIN
+  v_cart := COALESCE(NEW.cart_id, OLD.cart_id);
+  UPDATE carts c
+    SET total_amount = (
+        SELECT round(COALESCE(SUM(line_total), 0), 2)
+        FROM cart_items ci
+        WHERE ci.cart_id = v_cart
+    ),
+    updated_at = now()
+  WHERE c.id = v_cart;
+  RETURN COALESCE(NEW, OLD);
+END;
+$$ LANGUAGE plpgsql;
+
+-- Refresh stored totals under the new rule.
+ALTER TABLE carts DISABLE TRIGGER USER;
+UPDATE carts c
+    SET total_amount = (
+        SELECT round(COALESCE(SUM(line_total), 0), 2)
+        FROM cart_items ci
+        WHERE ci.cart_id = c.id
+    );
+ALTER TABLE carts ENABLE TRIGGER USER;
@@ -0,0 +1,8 @@
+-- Add down migration script here
+ALTER TABLE order_items DROP COLUMN IF EXISTS units_per_sell_unit;
+
+ALTER TABLE cart_items
+    DROP COLUMN IF EXISTS base_quantity,
+    DROP COLUMN IF EXISTS units_per_sell_unit;
+
+ALTER TABLE products DROP COLUMN IF EXISTS units_per_pack;
@@ -0,0 +1,15 @@
+-- Add up migration script here
+-- Stock is counted in base units; a pack holds units_per_pack of them.
+ALTER TABLE products
+    ADD COLUMN units_per_pack INTEGER NOT NULL DEFAULT 1 CHECK (units_per_pack > 0);
+
+-- Each line snapshots how many base units one of its sell units consumes, so a
+-- later pack-size change cannot release or restock the wrong amount.
+-- Existing lines were reserved one unit per pack, which DEFAULT 1 records.
+ALTER TABLE cart_items
+    ADD COLUMN units_per_sell_unit INTEGER NOT NULL DEFAULT 1 CHECK (units_per_sell_unit > 0);
+ALTER TABLE cart_items
+    ADD COLUMN base_quantity INTEGER GENERATED ALWAYS AS (quantity * units_per_sell_unit) STORED;
+
+ALTER TABLE order_items
+    ADD COLUMN units_per_sell_unit INTEGER NOT NULL DEFAULT 1;
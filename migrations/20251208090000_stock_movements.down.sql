@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS stock_movements;
+DROP FUNCTION IF EXISTS reject_stock_movement_change();
+DROP TYPE IF EXISTS stock_movement_reason;
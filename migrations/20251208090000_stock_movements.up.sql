@@ -0,0 +1,38 @@
+-- Add up migration script here
+-- Every change to products.quantity is recorded here, so SUM(quantity) per
+-- product always equals its stock. Stock is reserved when a line is added to a
+-- cart, so that is recorded as a sale and reversed by a cart_release.
+CREATE TYPE stock_movement_reason AS ENUM ('sale','cart_release','refund','adjustment','receipt');
+
+CREATE TABLE stock_movements (
+    id           BIGSERIAL PRIMARY KEY,
+    product_id   UUID NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    reason       stock_movement_reason NOT NULL,
+    quantity     INTEGER NOT NULL CHECK (quantity <> 0),  -- signed, in base units
+    created_by   UUID REFERENCES users(id) ON DELETE SET NULL,
+    reference_id UUID,                                    -- cart, refund, ... that caused it
+    note         TEXT,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX ix_stock_movements_product ON stock_movements(product_id, id);
+
+CREATE OR REPLACE FUNCTION reject_stock_movement_change() RETURNS trigger AS $$
+BEGIN
+  RAISE EXCEPTION 'Stock movements are append-only'
+    USING ERRCODE = '55000';
+END;
+$$ LANGUAGE plpgsql;
+
+-- Deleting a product cascades from a referential trigger (depth > 0) and is allowed.
+CREATE TRIGGER trg_stock_movements_immutable
+BEFORE UPDATE OR DELETE ON stock_movements
+FOR EACH ROW
+WHEN (pg_trigger_depth() = 0)
+EXECUTE FUNCTION reject_stock_movement_change();
+
+-- Open the ledger with current stock so it balances from day one.
+INSERT INTO stock_movements (product_id, reason, quantity, note)
+SELECT id, 'adjustment', quantity, 'Opening balance'
+FROM products
+WHERE quantity <> 0;
@@ -0,0 +1,6 @@
+-- Add down migration script here
+ALTER TABLE stock_movements
+    DROP CONSTRAINT IF EXISTS stock_movements_adjustment_reason_check,
+    DROP COLUMN IF EXISTS adjustment_reason;
+
+DROP TYPE IF EXISTS stock_adjustment_reason;
@@ -0,0 +1,8 @@
+-- Add up migration script here
+-- Manual adjustments carry a reason code; the free-text note holds the audit detail.
+CREATE TYPE stock_adjustment_reason AS ENUM ('damage','theft','expiry_write_off','found_stock','correction');
+
+ALTER TABLE stock_movements
+    ADD COLUMN adjustment_reason stock_adjustment_reason,
+    ADD CONSTRAINT stock_movements_adjustment_reason_check
+        CHECK (adjustment_reason IS NULL OR reason = 'adjustment');
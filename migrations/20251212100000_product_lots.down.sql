@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS cart_item_allocations;
+ALTER TABLE stock_movements DROP COLUMN IF EXISTS lot_id;
+DROP TABLE IF EXISTS product_lots;
@@ -0,0 +1,40 @@
+-- Add up migration script here
+-- Lots split a product's stock by batch. products.quantity stays the total on
+-- hand; whatever is not in a lot (products.quantity - SUM(lot quantity)) is
+-- unlotted stock that never expires. A lot is expired from its expiry date on.
+CREATE TABLE product_lots (
+    id          UUID PRIMARY KEY DEFAULT (uuid_generate_v4()),
+    product_id  UUID NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    lot_number  TEXT NOT NULL,
+    expiry_date DATE NOT NULL,
+    quantity    INTEGER NOT NULL DEFAULT 0 CHECK (quantity >= 0),  -- base units
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (product_id, lot_number)
+);
+
+CREATE INDEX ix_product_lots_fefo ON product_lots(product_id, expiry_date);
+
+ALTER TABLE stock_movements
+    ADD COLUMN lot_id UUID REFERENCES product_lots(id) ON DELETE CASCADE;
+
+-- Base units a cart line holds, per lot (NULL lot = unlotted stock), so the
+-- same lots are restocked when the line shrinks, is removed or is refunded.
+CREATE TABLE cart_item_allocations (
+    id           BIGSERIAL PRIMARY KEY,
+    cart_item_id BIGINT NOT NULL REFERENCES cart_items(id) ON DELETE CASCADE,
+    product_id   UUID NOT NULL REFERENCES products(id),
+    lot_id       UUID REFERENCES product_lots(id),
+    quantity     INTEGER NOT NULL CHECK (quantity > 0),
+    UNIQUE NULLS NOT DISTINCT (cart_item_id, lot_id)
+);
+
+-- Everything reserved so far came from unlotted stock.
+INSERT INTO cart_item_allocations (cart_item_id, product_id, lot_id, quantity)
+SELECT ci.id, ci.product_id, NULL, held.quantity
+FROM cart_items ci
+CROSS JOIN LATERAL (
+    SELECT ci.base_quantity - COALESCE((
+        SELECT SUM(ri.quantity) FROM refund_items ri WHERE ri.cart_item_id = ci.id
+    ), 0) * ci.units_per_sell_unit AS quantity
+) held
+WHERE held.quantity > 0;
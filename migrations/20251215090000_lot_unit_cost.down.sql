@@ -0,0 +1,2 @@
+-- Add down migration script here
+ALTER TABLE product_lots DROP COLUMN IF EXISTS unit_cost;
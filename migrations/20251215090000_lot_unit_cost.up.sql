@@ -0,0 +1,4 @@
+-- Add up migration script here
+-- Cost per base unit of a received lot; NULL where it was not recorded.
+ALTER TABLE product_lots
+    ADD COLUMN unit_cost NUMERIC(12,2) CHECK (unit_cost IS NULL OR unit_cost >= 0);
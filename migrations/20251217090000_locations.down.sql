@@ -0,0 +1,13 @@
+-- Add down migration script here
+ALTER TABLE carts DROP COLUMN IF EXISTS location_id;
+ALTER TABLE stock_movements DROP COLUMN IF EXISTS location_id;
+
+DROP INDEX IF EXISTS ix_product_lots_fefo;
+CREATE INDEX ix_product_lots_fefo ON product_lots(product_id, expiry_date);
+ALTER TABLE product_lots DROP CONSTRAINT IF EXISTS product_lots_product_location_lot_number_key;
+ALTER TABLE product_lots ADD CONSTRAINT product_lots_product_id_lot_number_key
+    UNIQUE (product_id, lot_number);
+ALTER TABLE product_lots DROP COLUMN IF EXISTS location_id;
+
+DROP TABLE IF EXISTS product_stock;
+DROP TABLE IF EXISTS locations;
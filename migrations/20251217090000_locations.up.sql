@@ -0,0 +1,60 @@
+-- Add up migration script here
+-- Stock is held per location. products.quantity stays the total across all
+-- locations; product_stock splits it by location, and lots live at one location.
+CREATE TABLE locations (
+    id         UUID PRIMARY KEY DEFAULT (uuid_generate_v4()),
+    name       TEXT NOT NULL UNIQUE,
+    is_default BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- At most one default location: it takes existing stock and carts, and any
+-- request that does not name a location.
+CREATE UNIQUE INDEX ux_locations_default ON locations(is_default) WHERE is_default;
+
+CREATE TRIGGER trg_locations_touch
+BEFORE UPDATE ON locations
+FOR EACH ROW EXECUTE FUNCTION touch_updated_at();
+
+INSERT INTO locations (name, is_default) VALUES ('Shop floor', TRUE);
+
+CREATE TABLE product_stock (
+    product_id  UUID NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    location_id UUID NOT NULL REFERENCES locations(id),
+    quantity    INTEGER NOT NULL DEFAULT 0 CHECK (quantity >= 0),  -- base units
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (product_id, location_id)
+);
+
+CREATE INDEX ix_product_stock_location ON product_stock(location_id);
+
+INSERT INTO product_stock (product_id, location_id, quantity)
+SELECT p.id, l.id, p.quantity
+FROM products p
+CROSS JOIN locations l
+WHERE l.is_default;
+
+-- Lots: the same lot number may sit at several locations once stock moves.
+ALTER TABLE product_lots ADD COLUMN location_id UUID REFERENCES locations(id);
+UPDATE product_lots SET location_id = (SELECT id FROM locations WHERE is_default);
+ALTER TABLE product_lots ALTER COLUMN location_id SET NOT NULL;
+ALTER TABLE product_lots DROP CONSTRAINT product_lots_product_id_lot_number_key;
+ALTER TABLE product_lots ADD CONSTRAINT product_lots_product_location_lot_number_key
+    UNIQUE (product_id, location_id, lot_number);
+DROP INDEX IF EXISTS ix_product_lots_fefo;
+CREATE INDEX ix_product_lots_fefo ON product_lots(product_id, location_id, expiry_date);
+
+-- The ledger is append-only, so the backfill runs with its triggers off.
+ALTER TABLE stock_movements DISABLE TRIGGER USER;
+ALTER TABLE stock_movements ADD COLUMN location_id UUID REFERENCES locations(id);
+UPDATE stock_movements SET location_id = (SELECT id FROM locations WHERE is_default);
+ALTER TABLE stock_movements ALTER COLUMN location_id SET NOT NULL;
+ALTER TABLE stock_movements ENABLE TRIGGER USER;
+
+-- A cart sells from one location; its allocations draw on that location only.
+ALTER TABLE carts DISABLE TRIGGER USER;
+ALTER TABLE carts ADD COLUMN location_id UUID REFERENCES locations(id);
+UPDATE carts SET location_id = (SELECT id FROM locations WHERE is_default);
+ALTER TABLE carts ALTER COLUMN location_id SET NOT NULL;
+ALTER TABLE carts ENABLE TRIGGER USER;
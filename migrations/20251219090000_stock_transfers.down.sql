@@ -0,0 +1,6 @@
+-- Add down migration script here
+-- stock_movement_reason keeps 'transfer_out' and 'transfer_in': Postgres cannot drop enum values.
+DROP TABLE IF EXISTS stock_transfer_allocations;
+DROP TABLE IF EXISTS stock_transfer_lines;
+DROP TABLE IF EXISTS stock_transfers;
+DROP TYPE IF EXISTS transfer_status;
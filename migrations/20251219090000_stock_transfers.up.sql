@@ -0,0 +1,55 @@
+-- Add up migration script here
+ALTER TYPE stock_movement_reason ADD VALUE IF NOT EXISTS 'transfer_out';
+ALTER TYPE stock_movement_reason ADD VALUE IF NOT EXISTS 'transfer_in';
+
+-- draft -> in_transit (dispatched) -> received; only a draft can be cancelled.
+CREATE TYPE transfer_status AS ENUM ('draft', 'in_transit', 'received', 'cancelled');
+
+CREATE SEQUENCE IF NOT EXISTS transfer_number_seq START WITH 1000;
+
+CREATE TABLE stock_transfers (
+    id               UUID PRIMARY KEY DEFAULT (uuid_generate_v4()),
+    transfer_number  BIGINT NOT NULL UNIQUE DEFAULT nextval('transfer_number_seq'),
+    from_location_id UUID NOT NULL REFERENCES locations(id),
+    to_location_id   UUID NOT NULL REFERENCES locations(id),
+    status           transfer_status NOT NULL DEFAULT 'draft',
+    note             TEXT,
+    created_by       UUID REFERENCES users(id) ON DELETE SET NULL,
+    dispatched_by    UUID REFERENCES users(id) ON DELETE SET NULL,
+    dispatched_at    TIMESTAMPTZ,
+    received_by      UUID REFERENCES users(id) ON DELETE SET NULL,
+    received_at      TIMESTAMPTZ,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at       TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CHECK (from_location_id <> to_location_id)
+);
+
+ALTER SEQUENCE transfer_number_seq OWNED BY stock_transfers.transfer_number;
+
+CREATE INDEX ix_stock_transfers_status ON stock_transfers(status, created_at DESC);
+
+CREATE TRIGGER trg_stock_transfers_touch
+BEFORE UPDATE ON stock_transfers
+FOR EACH ROW EXECUTE FUNCTION touch_updated_at();
+
+-- quantity is in base units and leaves the source in full on dispatch.
+-- received_quantity is set on receipt; anything short of quantity is the discrepancy.
+CREATE TABLE stock_transfer_lines (
+    id                BIGSERIAL PRIMARY KEY,
+    transfer_id       UUID NOT NULL REFERENCES stock_transfers(id) ON DELETE CASCADE,
+    product_id        UUID NOT NULL REFERENCES products(id),
+    quantity          INTEGER NOT NULL CHECK (quantity > 0),
+    received_quantity INTEGER CHECK (received_quantity BETWEEN 0 AND quantity),
+    discrepancy_note  TEXT,
+    UNIQUE (transfer_id, product_id)
+);
+
+-- Base units a dispatched line took from each source lot (NULL lot = unlotted),
+-- so the same lots are recreated at the destination on receipt.
+CREATE TABLE stock_transfer_allocations (
+    id       BIGSERIAL PRIMARY KEY,
+    line_id  BIGINT NOT NULL REFERENCES stock_transfer_lines(id) ON DELETE CASCADE,
+    lot_id   UUID REFERENCES product_lots(id),
+    quantity INTEGER NOT NULL CHECK (quantity > 0),
+    UNIQUE NULLS NOT DISTINCT (line_id, lot_id)
+);
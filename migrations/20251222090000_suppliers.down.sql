@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS product_suppliers;
+DROP TABLE IF EXISTS suppliers;
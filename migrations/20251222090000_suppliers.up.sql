@@ -0,0 +1,43 @@
+-- Add up migration script here
+CREATE TABLE suppliers (
+    id                 UUID PRIMARY KEY DEFAULT (uuid_generate_v4()),
+    name               TEXT NOT NULL UNIQUE,
+    contact_name       TEXT,
+    email              TEXT,
+    phone              TEXT,
+    address            TEXT,
+    -- Days after invoice that payment is due; 0 is payment on delivery.
+    payment_terms_days INTEGER NOT NULL DEFAULT 0 CHECK (payment_terms_days >= 0),
+    notes              TEXT,
+    is_active          BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at         TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at         TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TRIGGER trg_suppliers_touch
+BEFORE UPDATE ON suppliers
+FOR EACH ROW EXECUTE FUNCTION touch_updated_at();
+
+-- Where a product can be bought. Costs are per base unit.
+CREATE TABLE product_suppliers (
+    product_id     UUID NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    supplier_id    UUID NOT NULL REFERENCES suppliers(id) ON DELETE CASCADE,
+    supplier_sku   TEXT,
+    last_cost      NUMERIC(12,2) CHECK (last_cost IS NULL OR last_cost >= 0),
+    lead_time_days INTEGER CHECK (lead_time_days IS NULL OR lead_time_days >= 0),
+    is_preferred   BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at     TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at     TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (product_id, supplier_id),
+    UNIQUE (supplier_id, supplier_sku)
+);
+
+CREATE INDEX ix_product_suppliers_supplier ON product_suppliers(supplier_id);
+
+-- At most one preferred supplier per product.
+CREATE UNIQUE INDEX ux_product_suppliers_preferred
+    ON product_suppliers(product_id) WHERE is_preferred;
+
+CREATE TRIGGER trg_product_suppliers_touch
+BEFORE UPDATE ON product_suppliers
+FOR EACH ROW EXECUTE FUNCTION touch_updated_at();
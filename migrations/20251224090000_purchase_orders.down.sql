@@ -0,0 +1,6 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS purchase_receipt_lines;
+DROP TABLE IF EXISTS purchase_receipts;
+DROP TABLE IF EXISTS purchase_order_lines;
+DROP TABLE IF EXISTS purchase_orders;
+DROP TYPE IF EXISTS purchase_order_status;
@@ -0,0 +1,65 @@
+-- Add up migration script here
+-- draft -> sent -> partially_received -> closed. A PO closes by itself once every
+-- line is received in full, or can be closed short. Only a PO with nothing
+-- received can be cancelled.
+CREATE TYPE purchase_order_status AS ENUM ('draft', 'sent', 'partially_received', 'closed', 'cancelled');
+
+CREATE SEQUENCE IF NOT EXISTS purchase_order_number_seq START WITH 1000;
+
+CREATE TABLE purchase_orders (
+    id            UUID PRIMARY KEY DEFAULT (uuid_generate_v4()),
+    po_number     BIGINT NOT NULL UNIQUE DEFAULT nextval('purchase_order_number_seq'),
+    supplier_id   UUID NOT NULL REFERENCES suppliers(id),
+    -- Where the goods are delivered and received into stock.
+    location_id   UUID NOT NULL REFERENCES locations(id),
+    status        purchase_order_status NOT NULL DEFAULT 'draft',
+    expected_date DATE,
+    note          TEXT,
+    created_by    UUID REFERENCES users(id) ON DELETE SET NULL,
+    sent_by       UUID REFERENCES users(id) ON DELETE SET NULL,
+    sent_at       TIMESTAMPTZ,
+    closed_at     TIMESTAMPTZ,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at    TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+ALTER SEQUENCE purchase_order_number_seq OWNED BY purchase_orders.po_number;
+
+CREATE INDEX ix_purchase_orders_status ON purchase_orders(status, created_at DESC);
+CREATE INDEX ix_purchase_orders_supplier ON purchase_orders(supplier_id, created_at DESC);
+
+CREATE TRIGGER trg_purchase_orders_touch
+BEFORE UPDATE ON purchase_orders
+FOR EACH ROW EXECUTE FUNCTION touch_updated_at();
+
+-- Quantities are in base units; unit_cost is per base unit.
+CREATE TABLE purchase_order_lines (
+    id                BIGSERIAL PRIMARY KEY,
+    purchase_order_id UUID NOT NULL REFERENCES purchase_orders(id) ON DELETE CASCADE,
+    product_id        UUID NOT NULL REFERENCES products(id),
+    quantity          INTEGER NOT NULL CHECK (quantity > 0),
+    unit_cost         NUMERIC(12,2) NOT NULL CHECK (unit_cost >= 0),
+    received_quantity INTEGER NOT NULL DEFAULT 0 CHECK (received_quantity BETWEEN 0 AND quantity),
+    UNIQUE (purchase_order_id, product_id)
+);
+
+-- One delivery against a PO. Its lines are what actually arrived.
+CREATE TABLE purchase_receipts (
+    id                UUID PRIMARY KEY DEFAULT (uuid_generate_v4()),
+    purchase_order_id UUID NOT NULL REFERENCES purchase_orders(id) ON DELETE CASCADE,
+    note              TEXT,
+    received_by       UUID REFERENCES users(id) ON DELETE SET NULL,
+    received_at       TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX ix_purchase_receipts_po ON purchase_receipts(purchase_order_id, received_at);
+
+CREATE TABLE purchase_receipt_lines (
+    id         BIGSERIAL PRIMARY KEY,
+    receipt_id UUID NOT NULL REFERENCES purchase_receipts(id) ON DELETE CASCADE,
+    line_id    BIGINT NOT NULL REFERENCES purchase_order_lines(id) ON DELETE CASCADE,
+    lot_id     UUID REFERENCES product_lots(id),
+    quantity   INTEGER NOT NULL CHECK (quantity > 0),
+    unit_cost  NUMERIC(12,2) NOT NULL CHECK (unit_cost >= 0),
+    UNIQUE (receipt_id, line_id)
+);
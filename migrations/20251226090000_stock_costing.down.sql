@@ -0,0 +1,12 @@
+-- Add down migration script here
+ALTER TABLE order_items DROP COLUMN IF EXISTS cost_of_goods;
+ALTER TABLE cart_items DROP COLUMN IF EXISTS cost_of_goods;
+ALTER TABLE stock_transfer_allocations DROP COLUMN IF EXISTS cost_value;
+ALTER TABLE cart_item_allocations DROP COLUMN IF EXISTS cost_value;
+DROP TABLE IF EXISTS cost_layers;
+ALTER TABLE stock_movements DISABLE TRIGGER USER;
+ALTER TABLE stock_movements DROP COLUMN IF EXISTS unit_cost;
+ALTER TABLE stock_movements ENABLE TRIGGER USER;
+ALTER TABLE products DROP COLUMN IF EXISTS average_cost;
+DROP TABLE IF EXISTS inventory_settings;
+DROP TYPE IF EXISTS costing_method;
@@ -0,0 +1,88 @@
+-- Add up migration script here
+-- Which cost outgoing stock is booked at. Both are always kept up to date, so
+-- the method can be switched at any time; it only affects movements after the switch.
+CREATE TYPE costing_method AS ENUM ('fifo', 'weighted_average');
+
+-- Single-row settings table.
+CREATE TABLE inventory_settings (
+    id             BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    costing_method costing_method NOT NULL DEFAULT 'weighted_average',
+    updated_at     TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+INSERT INTO inventory_settings DEFAULT VALUES;
+
+CREATE TRIGGER trg_inventory_settings_touch
+BEFORE UPDATE ON inventory_settings
+FOR EACH ROW EXECUTE FUNCTION touch_updated_at();
+
+-- Costs per base unit carry four decimals so pack costs split into units
+-- without losing cents; money totals are still rounded to two.
+ALTER TABLE products
+    ADD COLUMN average_cost NUMERIC(14,4) NOT NULL DEFAULT 0 CHECK (average_cost >= 0);
+
+-- Opening average: what the lots on hand were bought at, where recorded.
+UPDATE products p
+SET average_cost = c.cost
+FROM (
+    SELECT product_id,
+           round(COALESCE(
+               SUM(quantity * unit_cost) / NULLIF(SUM(quantity), 0),
+               AVG(unit_cost)
+           ), 4) AS cost
+    FROM product_lots
+    WHERE unit_cost IS NOT NULL
+    GROUP BY product_id
+) c
+WHERE c.product_id = p.id AND c.cost IS NOT NULL;
+
+-- Cost per base unit each movement was booked at. Stock value as of any moment
+-- is the sum of quantity * unit_cost up to it.
+ALTER TABLE stock_movements DISABLE TRIGGER USER;
+ALTER TABLE stock_movements
+    ADD COLUMN unit_cost NUMERIC(14,4) CHECK (unit_cost >= 0);
+UPDATE stock_movements m SET unit_cost = p.average_cost FROM products p WHERE p.id = m.product_id;
+ALTER TABLE stock_movements ALTER COLUMN unit_cost SET NOT NULL;
+ALTER TABLE stock_movements ENABLE TRIGGER USER;
+
+-- Stock on hand by when and at what cost it came in, consumed oldest first.
+-- Kept across all locations of a product.
+CREATE TABLE cost_layers (
+    id          BIGSERIAL PRIMARY KEY,
+    product_id  UUID NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    movement_id BIGINT REFERENCES stock_movements(id),
+    unit_cost   NUMERIC(14,4) NOT NULL CHECK (unit_cost >= 0),
+    quantity    INTEGER NOT NULL CHECK (quantity > 0),
+    remaining   INTEGER NOT NULL CHECK (remaining BETWEEN 0 AND quantity),
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX ix_cost_layers_open ON cost_layers(product_id, id) WHERE remaining > 0;
+
+-- Stock already on hand opens as one layer at the opening average.
+INSERT INTO cost_layers (product_id, unit_cost, quantity, remaining)
+SELECT id, average_cost, quantity, quantity FROM products WHERE quantity > 0;
+
+-- Cost of what each allocation holds, so stock goes back at the cost it left at.
+ALTER TABLE cart_item_allocations
+    ADD COLUMN cost_value NUMERIC(14,4) NOT NULL DEFAULT 0;
+UPDATE cart_item_allocations a SET cost_value = a.quantity * p.average_cost
+FROM products p WHERE p.id = a.product_id;
+
+ALTER TABLE stock_transfer_allocations
+    ADD COLUMN cost_value NUMERIC(14,4) NOT NULL DEFAULT 0;
+UPDATE stock_transfer_allocations a SET cost_value = a.quantity * p.average_cost
+FROM stock_transfer_lines tl JOIN products p ON p.id = tl.product_id
+WHERE tl.id = a.line_id;
+
+-- Cost of goods sold, fixed at checkout.
+ALTER TABLE cart_items ADD COLUMN cost_of_goods NUMERIC(12,2);
+ALTER TABLE order_items ADD COLUMN cost_of_goods NUMERIC(12,2);
+
+ALTER TABLE cart_items DISABLE TRIGGER USER;
+UPDATE cart_items ci
+SET cost_of_goods = round(ci.base_quantity * p.average_cost, 2)
+FROM products p
+WHERE p.id = ci.product_id
+  AND EXISTS (SELECT 1 FROM orders o WHERE o.cart_id = ci.cart_id);
+ALTER TABLE cart_items ENABLE TRIGGER USER;
@@ -0,0 +1,4 @@
+-- Add down migration script here
+ALTER TABLE products
+    DROP COLUMN IF EXISTS reorder_quantity,
+    DROP COLUMN IF EXISTS reorder_level;
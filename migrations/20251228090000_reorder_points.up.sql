@@ -0,0 +1,6 @@
+-- Add up migration script here
+-- A product is due for reordering once its stock falls to its reorder level.
+-- A level of 0 turns reordering off for the product.
+ALTER TABLE products
+    ADD COLUMN reorder_level    INTEGER NOT NULL DEFAULT 0 CHECK (reorder_level >= 0),
+    ADD COLUMN reorder_quantity INTEGER NOT NULL DEFAULT 0 CHECK (reorder_quantity >= 0);
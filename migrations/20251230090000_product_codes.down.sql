@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS product_barcodes;
+DROP INDEX IF EXISTS ux_products_sku;
+ALTER TABLE products DROP COLUMN IF EXISTS sku;
+DROP TYPE IF EXISTS barcode_type;
@@ -0,0 +1,19 @@
+-- Add up migration script here
+CREATE TYPE barcode_type AS ENUM ('ean13', 'upc_a', 'internal');
+
+-- Stock keeping unit: the shop's own code for a product, unique regardless of case.
+ALTER TABLE products ADD COLUMN sku TEXT CHECK (sku IS NULL OR btrim(sku) <> '');
+CREATE UNIQUE INDEX ux_products_sku ON products (lower(sku));
+
+-- Codes printed on a product. Each scans to one sell unit, so a pack
+-- barcode sells a pack at the pack price.
+CREATE TABLE product_barcodes (
+    id           BIGSERIAL PRIMARY KEY,
+    product_id   UUID NOT NULL REFERENCES products(id) ON DELETE CASCADE,
+    code         TEXT NOT NULL UNIQUE,
+    barcode_type barcode_type NOT NULL,
+    sell_unit    sell_unit NOT NULL DEFAULT 'unit',
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX ix_product_barcodes_product ON product_barcodes(product_id);
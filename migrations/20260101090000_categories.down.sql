@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS ix_products_category;
+ALTER TABLE products DROP COLUMN IF EXISTS category_id;
+DROP TABLE IF EXISTS categories;
@@ -0,0 +1,24 @@
+-- Add up migration script here
+-- Catalog categories. A category without a parent is a top-level one.
+CREATE TABLE categories (
+    id          UUID PRIMARY KEY DEFAULT (uuid_generate_v4()),
+    parent_id   UUID REFERENCES categories(id) ON DELETE RESTRICT,
+    name        TEXT NOT NULL CHECK (btrim(name) <> ''),
+    description TEXT,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CHECK (parent_id IS NULL OR parent_id <> id)
+);
+
+-- Sibling names are unique regardless of case; top-level names share one parent.
+CREATE UNIQUE INDEX ux_categories_sibling_name
+    ON categories (COALESCE(parent_id, '00000000-0000-0000-0000-000000000000'::UUID), lower(name));
+CREATE INDEX ix_categories_parent ON categories(parent_id);
+
+CREATE TRIGGER trg_categories_touch
+BEFORE UPDATE ON categories
+FOR EACH ROW EXECUTE FUNCTION touch_updated_at();
+
+ALTER TABLE products
+    ADD COLUMN category_id UUID REFERENCES categories(id) ON DELETE SET NULL;
+CREATE INDEX ix_products_category ON products(category_id);
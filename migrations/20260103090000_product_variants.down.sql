@@ -0,0 +1,11 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS trg_stock_movements_parent ON stock_movements;
+DROP FUNCTION IF EXISTS reject_parent_stock_movement();
+DROP TRIGGER IF EXISTS trg_products_variant_category ON products;
+DROP FUNCTION IF EXISTS sync_variant_category();
+DROP TRIGGER IF EXISTS trg_products_variant ON products;
+DROP FUNCTION IF EXISTS check_product_variant();
+
+ALTER TABLE products
+    DROP COLUMN IF EXISTS variant_attributes,
+    DROP COLUMN IF EXISTS parent_id;
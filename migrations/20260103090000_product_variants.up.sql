@@ -0,0 +1,85 @@
+-- Add up migration script here
+-- A variant is a product of its own, with its own price, pack price, stock and
+-- barcodes, grouped under a parent product and told apart by its attributes,
+-- e.g. {"strength": "500mg"}. A parent with variants holds no stock itself.
+ALTER TABLE products
+    ADD COLUMN parent_id UUID REFERENCES products(id) ON DELETE RESTRICT,
+    ADD COLUMN variant_attributes JSONB,
+    ADD CONSTRAINT products_variant_check CHECK (
+        (parent_id IS NULL AND variant_attributes IS NULL)
+        OR (parent_id IS NOT NULL
+            AND parent_id <> id
+            AND jsonb_typeof(variant_attributes) = 'object'
+            AND variant_attributes <> '{}'::JSONB)
+    );
+
+CREATE INDEX ix_products_parent ON products(parent_id);
+-- Two variants of one parent cannot share the same attributes.
+CREATE UNIQUE INDEX ux_products_variant_attributes
+    ON products (parent_id, variant_attributes) WHERE parent_id IS NOT NULL;
+
+-- Variants are one level deep, sit in their parent's category, and can only be
+-- added to a parent that has no stock.
+CREATE OR REPLACE FUNCTION check_product_variant() RETURNS trigger AS $$
+DECLARE
+  parent RECORD;
+BEGIN
+  IF NEW.parent_id IS NULL THEN
+    RETURN NEW;
+  END IF;
+  SELECT parent_id, quantity, category_id INTO parent
+  FROM products WHERE id = NEW.parent_id
+  FOR UPDATE;
+  IF NOT FOUND THEN
+    RETURN NEW;
+  END IF;
+  IF parent.parent_id IS NOT NULL THEN
+    RAISE EXCEPTION 'A variant cannot have variants of its own'
+      USING ERRCODE = '55000';
+  END IF;
+  IF parent.quantity <> 0 THEN
+    RAISE EXCEPTION 'A product holding stock cannot take variants'
+      USING ERRCODE = '55000';
+  END IF;
+  IF EXISTS (SELECT 1 FROM products WHERE parent_id = NEW.id) THEN
+    RAISE EXCEPTION 'A product with variants cannot become a variant'
+      USING ERRCODE = '55000';
+  END IF;
+  NEW.category_id := parent.category_id;
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_products_variant
+BEFORE INSERT OR UPDATE OF parent_id ON products
+FOR EACH ROW EXECUTE FUNCTION check_product_variant();
+
+-- Moving a parent to another category moves its variants with it.
+CREATE OR REPLACE FUNCTION sync_variant_category() RETURNS trigger AS $$
+BEGIN
+  UPDATE products SET category_id = NEW.category_id
+  WHERE parent_id = NEW.id AND category_id IS DISTINCT FROM NEW.category_id;
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_products_variant_category
+AFTER UPDATE OF category_id ON products
+FOR EACH ROW
+WHEN (NEW.parent_id IS NULL AND OLD.category_id IS DISTINCT FROM NEW.category_id)
+EXECUTE FUNCTION sync_variant_category();
+
+-- Stock of a parent is the stock of its variants; none is booked to it directly.
+CREATE OR REPLACE FUNCTION reject_parent_stock_movement() RETURNS trigger AS $$
+BEGIN
+  IF EXISTS (SELECT 1 FROM products WHERE parent_id = NEW.product_id) THEN
+    RAISE EXCEPTION 'Stock is held by the product''s variants; choose a variant'
+      USING ERRCODE = '55000';
+  END IF;
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_stock_movements_parent
+BEFORE INSERT ON stock_movements
+FOR EACH ROW EXECUTE FUNCTION reject_parent_stock_movement();
@@ -0,0 +1,22 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS trg_stock_movements_bundle ON stock_movements;
+DROP FUNCTION IF EXISTS reject_bundle_stock_movement();
+DROP TRIGGER IF EXISTS trg_products_bundle_price ON products;
+DROP FUNCTION IF EXISTS refresh_bundle_prices_on_price_change();
+
+-- Bundle lines cannot go back to one allocation per lot.
+DELETE FROM cart_item_allocations a
+USING cart_items ci
+WHERE ci.id = a.cart_item_id AND a.product_id <> ci.product_id;
+ALTER TABLE cart_item_allocations
+    DROP CONSTRAINT IF EXISTS cart_item_allocations_cart_item_id_product_id_lot_id_key,
+    ADD CONSTRAINT cart_item_allocations_cart_item_id_lot_id_key
+        UNIQUE NULLS NOT DISTINCT (cart_item_id, lot_id),
+    DROP COLUMN IF EXISTS units_per_item;
+
+DROP TABLE IF EXISTS bundle_components;
+DROP TABLE IF EXISTS product_bundles;
+DROP FUNCTION IF EXISTS refresh_bundle_price_on_pricing_change();
+DROP FUNCTION IF EXISTS refresh_bundle_price_on_component_change();
+DROP FUNCTION IF EXISTS refresh_bundle_price(UUID);
+DROP TYPE IF EXISTS bundle_pricing;
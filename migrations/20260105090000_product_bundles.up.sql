@@ -0,0 +1,106 @@
+-- Add up migration script here
+CREATE TYPE bundle_pricing AS ENUM ('fixed', 'derived');
+
+-- A bundle is a product sold as a set of other products. It holds no stock of
+-- its own: selling one takes each component from stock and a refund puts the
+-- components back. A derived bundle's price is the sum of its components'.
+CREATE TABLE product_bundles (
+    product_id UUID PRIMARY KEY REFERENCES products(id) ON DELETE CASCADE,
+    pricing    bundle_pricing NOT NULL DEFAULT 'fixed',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TRIGGER trg_product_bundles_touch
+BEFORE UPDATE ON product_bundles
+FOR EACH ROW EXECUTE FUNCTION touch_updated_at();
+
+-- Base units of each component in one bundle.
+CREATE TABLE bundle_components (
+    bundle_id    UUID NOT NULL REFERENCES product_bundles(product_id) ON DELETE CASCADE,
+    component_id UUID NOT NULL REFERENCES products(id) ON DELETE RESTRICT,
+    quantity     INTEGER NOT NULL CHECK (quantity > 0),
+    PRIMARY KEY (bundle_id, component_id),
+    CHECK (component_id <> bundle_id)
+);
+
+CREATE INDEX ix_bundle_components_component ON bundle_components(component_id);
+
+-- A cart line for a bundle holds stock of several products, so allocations are
+-- kept per product, with the base units of that product per base unit of the line.
+ALTER TABLE cart_item_allocations
+    ADD COLUMN units_per_item INTEGER NOT NULL DEFAULT 1 CHECK (units_per_item > 0),
+    DROP CONSTRAINT cart_item_allocations_cart_item_id_lot_id_key,
+    ADD CONSTRAINT cart_item_allocations_cart_item_id_product_id_lot_id_key
+        UNIQUE NULLS NOT DISTINCT (cart_item_id, product_id, lot_id);
+
+CREATE OR REPLACE FUNCTION refresh_bundle_price(bundle UUID) RETURNS void AS $$
+  UPDATE products p
+  SET price = derived.price, updated_at = now()
+  FROM (
+    SELECT COALESCE(round(SUM(c.price * bc.quantity), 2), 0) AS price
+    FROM bundle_components bc
+    JOIN products c ON c.id = bc.component_id
+    WHERE bc.bundle_id = bundle
+  ) derived
+  WHERE p.id = bundle
+    AND p.price IS DISTINCT FROM derived.price
+    AND EXISTS (
+      SELECT 1 FROM product_bundles b WHERE b.product_id = bundle AND b.pricing = 'derived'
+    );
+$$ LANGUAGE sql;
+
+CREATE OR REPLACE FUNCTION refresh_bundle_price_on_component_change() RETURNS trigger AS $$
+BEGIN
+  IF TG_OP = 'DELETE' THEN
+    PERFORM refresh_bundle_price(OLD.bundle_id);
+  ELSE
+    PERFORM refresh_bundle_price(NEW.bundle_id);
+  END IF;
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_bundle_components_price
+AFTER INSERT OR UPDATE OR DELETE ON bundle_components
+FOR EACH ROW EXECUTE FUNCTION refresh_bundle_price_on_component_change();
+
+CREATE OR REPLACE FUNCTION refresh_bundle_price_on_pricing_change() RETURNS trigger AS $$
+BEGIN
+  PERFORM refresh_bundle_price(NEW.product_id);
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_product_bundles_price
+AFTER INSERT OR UPDATE OF pricing ON product_bundles
+FOR EACH ROW EXECUTE FUNCTION refresh_bundle_price_on_pricing_change();
+
+CREATE OR REPLACE FUNCTION refresh_bundle_prices_on_price_change() RETURNS trigger AS $$
+BEGIN
+  PERFORM refresh_bundle_price(bundle_id)
+  FROM bundle_components WHERE component_id = NEW.id;
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_products_bundle_price
+AFTER UPDATE OF price ON products
+FOR EACH ROW
+WHEN (OLD.price IS DISTINCT FROM NEW.price)
+EXECUTE FUNCTION refresh_bundle_prices_on_price_change();
+
+-- Stock of a bundle is the stock of its components; none is booked to it directly.
+CREATE OR REPLACE FUNCTION reject_bundle_stock_movement() RETURNS trigger AS $$
+BEGIN
+  IF EXISTS (SELECT 1 FROM product_bundles WHERE product_id = NEW.product_id) THEN
+    RAISE EXCEPTION 'A bundle holds no stock; its components do'
+      USING ERRCODE = '55000';
+  END IF;
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_stock_movements_bundle
+BEFORE INSERT ON stock_movements
+FOR EACH ROW EXECUTE FUNCTION reject_bundle_stock_movement();
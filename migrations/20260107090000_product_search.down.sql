@@ -0,0 +1,11 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS trg_categories_search ON categories;
+DROP FUNCTION IF EXISTS refresh_product_search_for_category();
+DROP TRIGGER IF EXISTS trg_product_barcodes_search ON product_barcodes;
+DROP FUNCTION IF EXISTS refresh_product_search_for_barcode();
+DROP TRIGGER IF EXISTS trg_products_search ON products;
+DROP FUNCTION IF EXISTS refresh_product_search_for_product();
+DROP FUNCTION IF EXISTS product_search_query(TEXT);
+DROP FUNCTION IF EXISTS refresh_product_search(UUID[]);
+DROP TABLE IF EXISTS product_search;
+DROP EXTENSION IF EXISTS pg_trgm;
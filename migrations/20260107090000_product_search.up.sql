@@ -0,0 +1,101 @@
+-- Add up migration script here
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- What product search matches against, kept beside the product so barcodes
+-- and the category path can be searched too: words and code prefixes through
+-- the document, misspelt names through trigrams of the name.
+CREATE TABLE product_search (
+    product_id  UUID PRIMARY KEY REFERENCES products(id) ON DELETE CASCADE,
+    document    TSVECTOR NOT NULL,
+    search_name TEXT NOT NULL
+);
+
+CREATE INDEX ix_product_search_document ON product_search USING gin(document);
+CREATE INDEX ix_product_search_name_trgm ON product_search USING gin(search_name gin_trgm_ops);
+
+CREATE OR REPLACE FUNCTION refresh_product_search(ids UUID[]) RETURNS void AS $$
+  INSERT INTO product_search (product_id, document, search_name)
+  SELECT p.id,
+         setweight(to_tsvector('simple', p.name), 'A')
+           || setweight(to_tsvector('simple', COALESCE(p.sku, '')), 'A')
+           || setweight(to_tsvector('simple', COALESCE(codes.codes, '')), 'B')
+           || setweight(to_tsvector('simple', COALESCE(path.names, '')), 'C'),
+         lower(p.name)
+  FROM products p
+  LEFT JOIN LATERAL (
+    SELECT string_agg(b.code, ' ') AS codes
+    FROM product_barcodes b
+    WHERE b.product_id = p.id
+  ) codes ON true
+  LEFT JOIN LATERAL (
+    WITH RECURSIVE ancestors AS (
+      SELECT c.parent_id, c.name FROM categories c WHERE c.id = p.category_id
+      UNION ALL
+      SELECT c.parent_id, c.name FROM categories c JOIN ancestors a ON c.id = a.parent_id
+    )
+    SELECT string_agg(name, ' ') AS names FROM ancestors
+  ) path ON true
+  WHERE p.id = ANY(ids)
+  ON CONFLICT (product_id) DO UPDATE
+    SET document = EXCLUDED.document, search_name = EXCLUDED.search_name;
+$$ LANGUAGE sql;
+
+-- Matches documents holding every word of `term` as a prefix. The words are
+-- split the way documents are, so codes like "SHELF-7" line up.
+CREATE OR REPLACE FUNCTION product_search_query(term TEXT) RETURNS tsquery AS $$
+  SELECT to_tsquery('simple', string_agg(
+           '''' || replace(replace(lexeme, '\', '\\'), '''', '''''') || ''':*', ' & '))
+  FROM unnest(tsvector_to_array(to_tsvector('simple', term))) AS lexeme;
+$$ LANGUAGE sql IMMUTABLE;
+
+CREATE OR REPLACE FUNCTION refresh_product_search_for_product() RETURNS trigger AS $$
+BEGIN
+  PERFORM refresh_product_search(ARRAY[NEW.id]);
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_products_search
+AFTER INSERT OR UPDATE OF name, sku, category_id ON products
+FOR EACH ROW EXECUTE FUNCTION refresh_product_search_for_product();
+
+CREATE OR REPLACE FUNCTION refresh_product_search_for_barcode() RETURNS trigger AS $$
+BEGIN
+  IF TG_OP IN ('UPDATE', 'DELETE') THEN
+    PERFORM refresh_product_search(ARRAY[OLD.product_id]);
+  END IF;
+  IF TG_OP IN ('INSERT', 'UPDATE') THEN
+    PERFORM refresh_product_search(ARRAY[NEW.product_id]);
+  END IF;
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_product_barcodes_search
+AFTER INSERT OR UPDATE OR DELETE ON product_barcodes
+FOR EACH ROW EXECUTE FUNCTION refresh_product_search_for_barcode();
+
+-- Renaming or moving a category changes the path of everything beneath it.
+CREATE OR REPLACE FUNCTION refresh_product_search_for_category() RETURNS trigger AS $$
+BEGIN
+  PERFORM refresh_product_search(ARRAY(
+    WITH RECURSIVE subtree AS (
+      SELECT NEW.id AS id
+      UNION
+      SELECT c.id FROM categories c JOIN subtree s ON c.parent_id = s.id
+    )
+    SELECT p.id FROM products p JOIN subtree s ON p.category_id = s.id
+  ));
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_categories_search
+AFTER UPDATE OF name, parent_id ON categories
+FOR EACH ROW EXECUTE FUNCTION refresh_product_search_for_category();
+
+DO $$
+BEGIN
+  PERFORM refresh_product_search(ARRAY(SELECT id FROM products));
+END;
+$$;
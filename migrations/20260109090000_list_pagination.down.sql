@@ -0,0 +1,9 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS trg_users_search ON users;
+DROP FUNCTION IF EXISTS refresh_user_search();
+DROP INDEX IF EXISTS ix_carts_list_total;
+DROP INDEX IF EXISTS ix_carts_list_created;
+DROP INDEX IF EXISTS ix_users_list_created;
+DROP INDEX IF EXISTS ix_products_list_name;
+DROP INDEX IF EXISTS ix_products_list_price;
+DROP INDEX IF EXISTS ix_products_list_created;
@@ -0,0 +1,32 @@
+-- Add up migration script here
+
+-- Lists resume after a cursor by seeking to (sort key, id), so each sort a
+-- large list allows gets an index in that order.
+CREATE INDEX ix_products_list_created ON products ((COALESCE(created_at, '-infinity'::timestamptz)), id)
+  WHERE parent_id IS NULL;
+CREATE INDEX ix_products_list_price ON products (price, id) WHERE parent_id IS NULL;
+CREATE INDEX ix_products_list_name ON products (lower(name), id) WHERE parent_id IS NULL;
+
+CREATE INDEX ix_users_list_created ON users ((COALESCE(created_at, '-infinity'::timestamptz)), id);
+
+CREATE INDEX ix_carts_list_created ON carts (user_id, created_at, id);
+CREATE INDEX ix_carts_list_total ON carts (user_id, total_amount, id);
+
+-- The user search document was only filled in once, so users added since
+-- could not be found.
+CREATE OR REPLACE FUNCTION refresh_user_search() RETURNS trigger AS $$
+BEGIN
+  NEW.search_tsv := to_tsvector('english',
+    coalesce(NEW.username, '') || ' ' || coalesce(NEW.first_name, '') || ' ' ||
+    coalesce(NEW.last_name, '') || ' ' || coalesce(NEW.email, ''));
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_users_search
+BEFORE INSERT OR UPDATE OF username, first_name, last_name, email ON users
+FOR EACH ROW EXECUTE FUNCTION refresh_user_search();
+
+UPDATE users SET search_tsv = to_tsvector('english',
+  coalesce(username, '') || ' ' || coalesce(first_name, '') || ' ' ||
+  coalesce(last_name, '') || ' ' || coalesce(email, ''));
@@ -0,0 +1,4 @@
+-- Add down migration script here
+ALTER TABLE cart_status_history DROP COLUMN IF EXISTS admin_override;
+ALTER TABLE orders DROP CONSTRAINT IF EXISTS ck_orders_foc_no_revenue;
+ALTER TABLE orders DROP COLUMN IF EXISTS foc_reason;
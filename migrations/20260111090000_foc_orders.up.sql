@@ -0,0 +1,10 @@
+-- Add up migration script here
+
+-- A cart given away free of charge still gets an order, at no revenue, so
+-- the goods that left can be audited; the reason says why.
+ALTER TABLE orders ADD COLUMN foc_reason TEXT;
+ALTER TABLE orders ADD CONSTRAINT ck_orders_foc_no_revenue
+    CHECK (foc_reason IS NULL OR total_amount = 0);
+
+-- Whether the change was made on another user's cart.
+ALTER TABLE cart_status_history ADD COLUMN admin_override BOOLEAN NOT NULL DEFAULT false;
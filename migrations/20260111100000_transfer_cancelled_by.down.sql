@@ -0,0 +1,4 @@
+-- Add down migration script here
+ALTER TABLE stock_transfers
+    DROP COLUMN IF EXISTS cancelled_at,
+    DROP COLUMN IF EXISTS cancelled_by;
@@ -0,0 +1,4 @@
+-- Add up migration script here
+ALTER TABLE stock_transfers
+    ADD COLUMN cancelled_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    ADD COLUMN cancelled_at TIMESTAMPTZ;
@@ -0,0 +1,2 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS ux_product_barcodes_scan_code;
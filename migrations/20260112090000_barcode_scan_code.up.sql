@@ -0,0 +1,6 @@
+-- Add up migration script here
+-- A UPC-A code scans the same as its EAN-13 form with a leading zero, so only
+-- one of the two may be stored.
+CREATE UNIQUE INDEX ux_product_barcodes_scan_code ON product_barcodes (
+    (CASE WHEN code ~ '^[0-9]{12}$' THEN '0' || code ELSE code END)
+);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS ix_cost_layers_open;
+ALTER TABLE cost_layers DROP COLUMN IF EXISTS location_id;
+CREATE INDEX ix_cost_layers_open ON cost_layers(product_id, id) WHERE remaining > 0;
@@ -0,0 +1,40 @@
+-- Add up migration script here
+-- Cost layers are kept per location, like the stock they cost, so a sale only
+-- uses up layers of stock that came in where it was sold.
+ALTER TABLE cost_layers ADD COLUMN location_id UUID REFERENCES locations(id);
+
+UPDATE cost_layers c
+SET location_id = m.location_id
+FROM stock_movements m
+WHERE m.id = c.movement_id;
+
+-- Opening layers came from no movement. What they have left is split across
+-- the locations holding stock that no later layer accounts for.
+WITH uncovered AS (
+    SELECT s.product_id, s.location_id,
+           s.quantity - COALESCE((
+               SELECT SUM(c.remaining)
+               FROM cost_layers c
+               WHERE c.product_id = s.product_id AND c.location_id = s.location_id
+           ), 0) AS quantity
+    FROM product_stock s
+), shares AS (
+    SELECT o.product_id, u.location_id, o.unit_cost, o.created_at, o.remaining, u.quantity,
+           SUM(u.quantity) OVER (PARTITION BY o.id ORDER BY u.location_id) - u.quantity AS taken
+    FROM cost_layers o
+    JOIN uncovered u ON u.product_id = o.product_id AND u.quantity > 0
+    WHERE o.location_id IS NULL AND o.remaining > 0
+)
+INSERT INTO cost_layers (product_id, location_id, unit_cost, quantity, remaining, created_at)
+SELECT product_id, location_id, unit_cost, share, share, created_at
+FROM shares
+CROSS JOIN LATERAL (SELECT LEAST(quantity, remaining - taken)::INTEGER AS share) s
+WHERE share > 0;
+
+DELETE FROM cost_layers WHERE location_id IS NULL;
+ALTER TABLE cost_layers ALTER COLUMN location_id SET NOT NULL;
+
+-- Split opening layers keep their age, so they are still used up first.
+DROP INDEX IF EXISTS ix_cost_layers_open;
+CREATE INDEX ix_cost_layers_open ON cost_layers(product_id, location_id, created_at, id)
+    WHERE remaining > 0;
@@ -10,7 +10,13 @@ use crate::{config::Config, shared_var::create_router};
 mod config;
 mod mauth;
 mod mcart;
+mod mcategory;
+mod mlocation;
 mod mproduct;
+mod mpurchase;
+mod mstock;
+mod msupplier;
+mod mtransfer;
 mod musers;
 mod shared_ops;
 mod shared_var;
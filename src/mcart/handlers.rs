@@ -1,28 +1,49 @@
 use crate::AppState;
 use crate::mauth::middlewares::JWTAuthMiddleware;
-use crate::mcart::models::{CartItemModel, CartModel, CartWithItemsModel};
+use crate::mcart::models::{
+    CartItemModel, CartLineForCheckout, CartModel, CartStatus, CartWithItemsModel, LinePrice,
+    OrderItemModel,
+    OrderModel, OrderWithItemsModel, RefundItemModel, RefundModel, RefundWithItemsModel,
+    RefundableCartLine,
+};
 use crate::mcart::schemas::{
-    AddCartItemSchema, UpdateCartItemSchema,
+    AddCartItemSchema, AdminOverrideQuery, CartFilterOptions, CheckoutCartSchema, ClearCartSchema, CreateCartQuery,
+    DeleteCartItemSchema, RefundCartSchema, RefundLineSchema, UpdateCartItemSchema, UpdateCartStatusSchema,
 };
 use crate::mcart::sql_string::CartSQLString;
+use crate::mlocation::handlers::resolve_location;
+use crate::mproduct::models::{ProductModel, SellUnit};
+use crate::mstock::handlers::{allocate_cart_stock, release_cart_stock};
+use crate::mstock::models::{StockMovementReason, StockMovementSource};
+use crate::musers::models::{MUserModel, UserRole};
 use crate::shared_var::MyBaseResponse;
+use crate::util::money::{Money, line_total, round_money};
+use crate::util::pagination::{Keyed, PageRequest, Sort, SortField, check_range};
+
+use std::collections::HashSet;
+use std::str::FromStr;
 
 use axum::body::Body;
 use axum::extract::Request;
-use sqlx::query_as;
+use sqlx::{Postgres, Transaction, query_as};
 
 #[utoipa::path(
     post,
     path = "/api/v1/cart/create", 
     tag = "Carts",
+    params(
+        CreateCartQuery
+    ),
     responses(
         (status = 200, description = "Carts created successfully", body = MyBaseResponse<Vec<CartModel>>),
-        (status = 409, description = "Database error", body = MyBaseResponse<CartModel>),
+        (status = 404, description = "Location not found", body = MyBaseResponse<CartModel>),
+        (status = 409, description = "Open cart with items already exists at another location", body = MyBaseResponse<CartModel>),
     ),
      security(("bearerAuth" = [])), 
 )]
 pub async fn create_cart_handler(
     request: Request<Body>,
+    query: CreateCartQuery,
     state: AppState,
 ) -> MyBaseResponse<CartModel> {
     let req_user = request.extensions().get::<JWTAuthMiddleware>();
@@ -30,29 +51,65 @@ pub async fn create_cart_handler(
         return MyBaseResponse::<CartModel>::error(400,"Unauthorised!");
     }
     let user = &req_user.unwrap().user;
+    let location = match resolve_location(&state.db, query.location_id).await {
+        Ok(l) => l,
+        Err(e) => return e.cast(),
+    };
     let res = query_as::<_, CartModel>(CartSQLString::CREATE_CART_ID)
-        .bind(&user.id)
+        .bind(user.id)
+        .bind(location.id)
         .fetch_one(&state.db)
         .await;
 
-    match res {
-        Ok(cart) => MyBaseResponse::ok(Some(cart), Some("Cart created successfully".into())),
+    let cart = match res {
+        Ok(cart) => cart,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+    if query.location_id.is_none() || cart.location_id == location.id {
+        return MyBaseResponse::ok(Some(cart), Some("Cart created successfully".into()));
+    }
+
+    // The open cart is elsewhere; it can follow the caller only while it is empty.
+    match query_as::<_, CartModel>(CartSQLString::MOVE_EMPTY_CART_TO_LOCATION)
+        .bind(cart.id)
+        .bind(location.id)
+        .fetch_optional(&state.db)
+        .await
+    {
+        Ok(Some(moved)) => {
+            MyBaseResponse::ok(Some(moved), Some("Cart moved to the requested location".into()))
+        }
+        Ok(None) => MyBaseResponse::error(
+            409,
+            "You already have an open cart with items at another location; check it out or clear it first",
+        ),
         Err(e) => MyBaseResponse::db_err(e),
     }
 }
 
+/// Fields a user's carts may be sorted by.
+static CART_SORTS: [SortField; 2] = [
+    SortField { name: "createdAt", sql: "c.created_at", sql_type: "TIMESTAMPTZ" },
+    SortField { name: "totalAmount", sql: "c.total_amount", sql_type: "NUMERIC" },
+];
+
 #[utoipa::path(
     get,
     path = "/api/v1/cart/get-by-user", 
     tag = "Carts",
+    params(
+        CartFilterOptions
+    ),
     responses(
-        (status = 200, description = "Carts retrieved successfully", body = MyBaseResponse<Vec<CartWithItemsModel>>),
+        (status = 200, description = "One page of the user's carts, with its place in the list in `meta`", body = MyBaseResponse<Vec<CartWithItemsModel>>),
+        (status = 400, description = "Unknown sort field, invalid cursor or an empty range"),
         (status = 409, description = "Database error", body = MyBaseResponse<CartWithItemsModel>),
     )
      
 )]
 pub async fn get_cart_by_user_handler(
     request: Request<Body>,
+    opts: CartFilterOptions,
     state: AppState,
 ) -> MyBaseResponse<Vec<CartWithItemsModel>> {
       let req_user = request.extensions().get::<JWTAuthMiddleware>();
@@ -60,13 +117,61 @@ pub async fn get_cart_by_user_handler(
         return MyBaseResponse::<Vec<CartWithItemsModel>>::error(400,"Unauthorised!");
     }
     let user = &req_user.unwrap().user;
-    let res = query_as::<_, CartWithItemsModel>(CartSQLString::GET_CART_BY_USER_ID)
-        .bind(&user.id)
+
+    let page = match Sort::parse(opts.sort.as_deref(), &CART_SORTS, "-createdAt")
+        .and_then(|sort| PageRequest::new(sort, opts.page, opts.limit, opts.cursor.as_deref()))
+    {
+        Ok(p) => p,
+        Err(e) => return e.cast(),
+    };
+    let ranges = check_range("created", opts.created_from.as_ref(), opts.created_to.as_ref())
+        .and(check_range("total_amount", opts.total_min.as_ref(), opts.total_max.as_ref()));
+    if let Err(e) = ranges {
+        return e.cast();
+    }
+
+    let count_sql = format!("SELECT COUNT(*) {}", CartSQLString::CART_LIST_FROM);
+    let total = sqlx::query_scalar::<_, i64>(&count_sql)
+        .bind(user.id)
+        .bind(&opts.status)
+        .bind(opts.created_from)
+        .bind(opts.created_to)
+        .bind(opts.total_min)
+        .bind(opts.total_max)
+        .fetch_one(&state.db)
+        .await;
+    let total = match total {
+        Ok(t) => t,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let select_sql = format!(
+        "SELECT {}, {} {} AND {} ORDER BY {} LIMIT $7 OFFSET $8",
+        CartSQLString::CART_WITH_ITEMS_COLUMNS,
+        page.sort.key_columns("c.id"),
+        CartSQLString::CART_LIST_FROM,
+        page.sort.after("c.id", 9),
+        page.sort.order_by("c.id"),
+    );
+    let res = query_as::<_, Keyed<CartWithItemsModel>>(&select_sql)
+        .bind(user.id)
+        .bind(&opts.status)
+        .bind(opts.created_from)
+        .bind(opts.created_to)
+        .bind(opts.total_min)
+        .bind(opts.total_max)
+        .bind(page.fetch_limit())
+        .bind(page.offset())
+        .bind(page.cursor_key())
+        .bind(page.cursor_id())
         .fetch_all(&state.db)
         .await;
 
     match res {
-        Ok(cart) => MyBaseResponse::ok(Some(cart), Some("Cart retrieved successfully".into())),
+        Ok(rows) => {
+            let (carts, meta) = page.finish(rows, total);
+            MyBaseResponse::page(carts, meta, Some("Cart retrieved successfully".into()))
+        }
         Err(e) => MyBaseResponse::db_err(e),
     }
 }
@@ -93,7 +198,7 @@ pub async fn get_open_cart_by_user_handler(
     }
     let user = &req_user.unwrap().user;
     let res = query_as::<_, CartWithItemsModel>(CartSQLString::GET_OPEN_CART_BY_USER_ID)
-        .bind(&user.id)
+        .bind(user.id)
         .fetch_one(&state.db)
         .await;
 
@@ -103,92 +208,242 @@ pub async fn get_open_cart_by_user_handler(
     }
 }
 
+/// Locks the cart a mutation targets and checks the caller may change it.
+/// Without a `cart_id` the caller's own open cart is used. Another user's cart
+/// can only be changed by an Admin who sets `admin_override`.
+async fn resolve_open_cart(
+    tx: &mut Transaction<'_, Postgres>,
+    user: &MUserModel,
+    cart_id: Option<uuid::Uuid>,
+    admin_override: bool,
+) -> Result<CartModel, MyBaseResponse<()>> {
+    let cart = match cart_id {
+        Some(id) => query_as::<_, CartModel>(CartSQLString::LOCK_CART_BY_ID)
+            .bind(id)
+            .fetch_optional(&mut **tx)
+            .await
+            .map_err(MyBaseResponse::db_err)?
+            .ok_or_else(|| MyBaseResponse::error(404, "Cart not found"))?,
+        None => query_as::<_, CartModel>(CartSQLString::LOCK_OPEN_CART_BY_USER_ID)
+            .bind(user.id)
+            .fetch_optional(&mut **tx)
+            .await
+            .map_err(MyBaseResponse::db_err)?
+            .ok_or_else(|| MyBaseResponse::error(404, "No open cart; create one first"))?,
+    };
+
+    check_cart_access(user, &cart, admin_override)?;
+
+    if cart.status != CartStatus::Open {
+        return Err(MyBaseResponse::error(
+            409,
+            format!("Cart is {}; only open carts can be changed", cart.status),
+        ));
+    }
+
+    Ok(cart)
+}
+
+/// Checks the caller may act on `cart`: their own, or another user's when an
+/// Admin sets `admin_override`.
+fn check_cart_access(
+    user: &MUserModel,
+    cart: &CartModel,
+    admin_override: bool,
+) -> Result<(), MyBaseResponse<()>> {
+    if cart.user_id != user.id {
+        if user.role != UserRole::Admin {
+            return Err(MyBaseResponse::error(403, "Cart belongs to another user"));
+        }
+        if !admin_override {
+            return Err(MyBaseResponse::error(
+                403,
+                "Cart belongs to another user; set admin_override to act on it",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Prices a cart line from the catalog. A client price that differs from the
+/// list price is an override and needs both a reason and the Admin role.
+async fn price_cart_line(
+    tx: &mut Transaction<'_, Postgres>,
+    user: &MUserModel,
+    product_id: uuid::Uuid,
+    sell_unit: &SellUnit,
+    requested: Option<Money>,
+    reason: Option<&str>,
+) -> Result<LinePrice, MyBaseResponse<()>> {
+    let product = query_as!(
+        ProductModel,
+        r#"SELECT * FROM products WHERE id = $1"#,
+        product_id
+    )
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(MyBaseResponse::db_err)?
+    .ok_or_else(|| MyBaseResponse::error(404, "Product not found"))?;
+
+    let has_variants = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM products WHERE parent_id = $1) AS "exists!""#,
+        product_id
+    )
+    .fetch_one(&mut **tx)
+    .await
+    .map_err(MyBaseResponse::db_err)?;
+    if has_variants {
+        return Err(MyBaseResponse::error(
+            400,
+            format!("{} comes in variants; add the variant to the cart", product.name),
+        ));
+    }
+
+    let list_amount = product.list_price(sell_unit).ok_or_else(|| {
+        MyBaseResponse::error(400, format!("{} is not sold by the {}", product.name, sell_unit))
+    })?;
+
+    let units_per_sell_unit = product.units_per(sell_unit);
+
+    let unit_amount = match requested {
+        Some(amount) if round_money(amount) != list_amount => round_money(amount),
+        _ => {
+            return Ok(LinePrice {
+                unit_amount: list_amount,
+                list_amount,
+                override_reason: None,
+                units_per_sell_unit,
+            });
+        }
+    };
+
+    if unit_amount.is_sign_negative() {
+        return Err(MyBaseResponse::error(400, "Price cannot be negative"));
+    }
+    let reason = match reason.map(str::trim) {
+        Some(r) if !r.is_empty() => r.to_string(),
+        _ => {
+            return Err(MyBaseResponse::error(
+                400,
+                "A price_override_reason is required to sell below or above the list price",
+            ));
+        }
+    };
+    if user.role != UserRole::Admin {
+        return Err(MyBaseResponse::error(403, "Only admins can override prices"));
+    }
+
+    Ok(LinePrice {
+        unit_amount,
+        list_amount,
+        override_reason: Some(reason),
+        units_per_sell_unit,
+    })
+}
+
 #[utoipa::path(
     post,
-    path = "/api/v1/cart/add-item", 
+    path = "/api/v1/cart/add-item",
     tag = "Carts",
     request_body = AddCartItemSchema,
     responses(
         (status = 200, description = "Item added to cart successfully", body = MyBaseResponse<CartItemModel>),
+        (status = 403, description = "Price override without the Admin role", body = MyBaseResponse<CartItemModel>),
         (status = 409, description = "Database error", body = MyBaseResponse<CartItemModel>),
     )
-     
+
 )]
 pub async fn add_item_to_cart_handler(
-    
+    auth: JWTAuthMiddleware,
      payload: axum::extract::Json<AddCartItemSchema>,
     state: AppState,
-    
+
 ) -> MyBaseResponse<CartItemModel> {
-  
+
     if payload.quantity <= 0 {
         return MyBaseResponse::error(400, "Quantity must be > 0");
     }
 
-       match sqlx::query!(
-        r#"SELECT quantity FROM cart_items WHERE cart_id = $1 AND product_id = $2"#,
+    let mut tx = match state.db.begin().await {
+        Ok(t) => t,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let cart = match resolve_open_cart(
+        &mut tx,
+        &auth.user,
         payload.cart_id,
-        payload.product_id
+        payload.admin_override,
     )
-    .fetch_optional(&state.db)
     .await
     {
-        Ok(Some(row)) => {
+        Ok(c) => c,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return e.cast();
+        }
+    };
+
+       match sqlx::query_as::<_, (i64, i32, i32)>(CartSQLString::LOCK_CART_LINE_QUANTITY)
+        .bind(cart.id)
+        .bind(payload.product_id)
+        .bind(&payload.sell_unit)
+    .fetch_optional(&mut *tx)
+    .await
+    {
+        Ok(Some((_, quantity, _))) => {
+            let _ = tx.rollback().await;
             // Build new desired quantity (old + added) and call update handler directly.
-            let new_qty = row.quantity + payload.quantity;
+            let new_qty = quantity + payload.quantity;
             let update_payload = UpdateCartItemSchema {
-                cart_id: payload.cart_id,
+                cart_id: Some(cart.id),
                 product_id: payload.product_id,
                 quantity: new_qty,
+                sell_unit: payload.sell_unit.clone(),
                 unit_amount: payload.unit_amount,
+                price_override_reason: payload.price_override_reason.clone(),
+                admin_override: payload.admin_override,
             };
             return update_item_in_cart_handler(
+                auth,
                 axum::extract::Json(update_payload),
                 state,
             ).await;
         }
-        Ok(None) => { 
-            
-            let mut tx = match state.db.begin().await {
-                Ok(t) => t,
-                Err(e) => return MyBaseResponse::db_err(e),
-            };
-
-            
-            let dec = sqlx::query!(
-                r#"UPDATE products
-                   SET quantity = quantity - $2, updated_at = now()
-                   WHERE id = $1 AND quantity >= $2
-                   RETURNING id"#,
+        Ok(None) => {
+            let price = match price_cart_line(
+                &mut tx,
+                &auth.user,
                 payload.product_id,
-                payload.quantity
+                &payload.sell_unit,
+                payload.unit_amount,
+                payload.price_override_reason.as_deref(),
             )
-            .fetch_optional(&mut *tx)
-            .await;
-
-            match dec {
-                Ok(Some(_)) => {}
-                Ok(None) => {
-                    let _ = tx.rollback().await;
-                    return MyBaseResponse::error(400, "Insufficient stock");
-                }
+            .await
+            {
+                Ok(p) => p,
                 Err(e) => {
                     let _ = tx.rollback().await;
-                    return MyBaseResponse::db_err(e);
+                    return e.cast();
                 }
-            }
+            };
 
-            
-            let inserted = sqlx::query_as::<_, CartItemModel>(
-                r#"INSERT INTO cart_items (cart_id, product_id, quantity, unit_amount, created_at, updated_at)
-                   VALUES ($1, $2, $3, $4, now(), now())
-                   RETURNING id, cart_id, product_id, quantity, unit_amount, line_total, created_at, updated_at"#
-            )
-            .bind(&payload.cart_id)
-            .bind(&payload.product_id)
-            .bind(&payload.quantity)
-            .bind(&payload.unit_amount)
-            .bind(payload.quantity as f64 * payload.unit_amount)
+            let Some(base_quantity) = payload.quantity.checked_mul(price.units_per_sell_unit) else {
+                let _ = tx.rollback().await;
+                return MyBaseResponse::error(400, "Quantity is too large");
+            };
+
+            let overridden_by = price.override_reason.as_ref().map(|_| auth.user.id);
+            let inserted = sqlx::query_as::<_, CartItemModel>(CartSQLString::INSERT_CART_LINE)
+            .bind(cart.id)
+            .bind(payload.product_id)
+            .bind(&payload.sell_unit)
+            .bind(payload.quantity)
+            .bind(price.unit_amount)
+            .bind(price.list_amount)
+            .bind(&price.override_reason)
+            .bind(overridden_by)
+            .bind(price.units_per_sell_unit)
             .fetch_one(&mut *tx)
             .await;
 
@@ -200,29 +455,117 @@ pub async fn add_item_to_cart_handler(
                 }
             };
 
+            match reserve_stock(
+                &mut tx,
+                auth.user.id,
+                &cart,
+                inserted.id,
+                payload.product_id,
+                base_quantity,
+            )
+            .await
+            {
+                Ok(true) => {}
+                Ok(false) => {
+                    let _ = tx.rollback().await;
+                    return MyBaseResponse::error(400, "Insufficient stock");
+                }
+                Err(e) => {
+                    let _ = tx.rollback().await;
+                    return MyBaseResponse::db_err(e);
+                }
+            }
+
             if let Err(e) = tx.commit().await {
                 return MyBaseResponse::db_err(e);
             }
 
             return MyBaseResponse::ok(Some(inserted), Some("Item added to cart".into()));
          }
-        Err(e) => return MyBaseResponse::db_err(e),
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    }
+
+
+}
+/// Allocates base units at the cart's location to a cart line and records the
+/// sale; `false` when the location does not have enough sellable stock. A
+/// bundle takes each of its components instead, all or none.
+async fn reserve_stock(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: uuid::Uuid,
+    cart: &CartModel,
+    cart_item_id: i64,
+    product_id: uuid::Uuid,
+    quantity: i32,
+) -> Result<bool, sqlx::Error> {
+    let source = StockMovementSource {
+        reason: StockMovementReason::Sale,
+        created_by: Some(user_id),
+        reference_id: Some(cart.id),
+    };
+    let components = sqlx::query_as::<_, (uuid::Uuid, i32)>(CartSQLString::GET_BUNDLE_COMPONENTS)
+        .bind(product_id)
+        .fetch_all(&mut **tx)
+        .await?;
+    if components.is_empty() {
+        return allocate_cart_stock(tx, cart_item_id, product_id, cart.location_id, quantity, 1, &source)
+            .await;
+    }
+    for (component_id, per_bundle) in components {
+        let Some(needed) = quantity.checked_mul(per_bundle) else {
+            return Ok(false);
+        };
+        let taken = allocate_cart_stock(
+            tx,
+            cart_item_id,
+            component_id,
+            cart.location_id,
+            needed,
+            per_bundle,
+            &source,
+        )
+        .await?;
+        if !taken {
+            return Ok(false);
+        }
     }
+    Ok(true)
+}
 
-   
+/// Returns base units held by a cart line to stock; `None` releases the whole line.
+async fn release_stock(
+    tx: &mut Transaction<'_, Postgres>,
+    reason: StockMovementReason,
+    user_id: uuid::Uuid,
+    reference_id: uuid::Uuid,
+    cart_item_id: i64,
+    quantity: Option<i32>,
+) -> Result<(), sqlx::Error> {
+    let source = StockMovementSource {
+        reason,
+        created_by: Some(user_id),
+        reference_id: Some(reference_id),
+    };
+    release_cart_stock(tx, cart_item_id, quantity, &source).await
 }
+
 #[utoipa::path(
     put,
-    path = "/api/v1/cart/update-item", 
+    path = "/api/v1/cart/update-item",
     tag = "Carts",
     request_body = UpdateCartItemSchema,
     responses(
         (status = 200, description = "Item updated in cart successfully", body = MyBaseResponse<CartItemModel>),
+        (status = 403, description = "Price override without the Admin role", body = MyBaseResponse<CartItemModel>),
         (status = 409, description = "Database error", body = MyBaseResponse<CartItemModel>),
     )
-     
+
 )]
 pub async fn update_item_in_cart_handler(
+    auth: JWTAuthMiddleware,
      payload: axum::extract::Json<UpdateCartItemSchema>,
     state: AppState,
 ) -> MyBaseResponse<CartItemModel> {
@@ -236,19 +579,31 @@ pub async fn update_item_in_cart_handler(
         Err(e) => return MyBaseResponse::db_err(e),
     };
 
-    
-    let existing = sqlx::query!(
-        r#"SELECT quantity FROM cart_items
-           WHERE cart_id = $1 AND product_id = $2
-           FOR UPDATE"#,
+    let cart = match resolve_open_cart(
+        &mut tx,
+        &auth.user,
         payload.cart_id,
-        payload.product_id
+        payload.admin_override,
     )
+    .await
+    {
+        Ok(c) => c,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return e.cast();
+        }
+    };
+
+
+    let existing = sqlx::query_as::<_, (i64, i32, i32)>(CartSQLString::LOCK_CART_LINE_QUANTITY)
+    .bind(cart.id)
+    .bind(payload.product_id)
+    .bind(&payload.sell_unit)
     .fetch_optional(&mut *tx)
     .await;
 
-    let old_qty = match existing {
-        Ok(Some(r)) => r.quantity,
+    let (item_id, old_base_qty) = match existing {
+        Ok(Some((id, _, base))) => (id, base),
         Ok(None) => {
             let _ = tx.rollback().await;
             return MyBaseResponse::error(404, "Item not found");
@@ -260,38 +615,33 @@ pub async fn update_item_in_cart_handler(
     };
 
     let new_qty = payload.quantity;
-    let delta = new_qty - old_qty;
 
     if new_qty == 0 {
-        
-        let del = sqlx::query!(
-            r#"DELETE FROM cart_items
-               WHERE cart_id = $1 AND product_id = $2
-               RETURNING *"#,
-            payload.cart_id,
-            payload.product_id,
-            
-        )
-        .fetch_one(&mut *tx)
-        .await;
 
-        if del.is_err() {
+        let restore = release_stock(
+            &mut tx,
+            StockMovementReason::CartRelease,
+            auth.user.id,
+            cart.id,
+            item_id,
+            None,
+        ).await;
+
+        if let Err(e) = restore {
             let _ = tx.rollback().await;
-            return MyBaseResponse::db_err(del.err().unwrap());
+            return MyBaseResponse::db_err(e);
         }
 
-        let restore = sqlx::query!(
-            r#"UPDATE products SET quantity = quantity + $2, updated_at = now()
-               WHERE id = $1"#,
-            payload.product_id,
-            old_qty
-        )
-        .execute(&mut *tx)
+        let del = sqlx::query_as::<_, CartItemModel>(CartSQLString::DELETE_CART_LINE)
+        .bind(cart.id)
+        .bind(payload.product_id)
+        .bind(&payload.sell_unit)
+        .fetch_one(&mut *tx)
         .await;
 
-        if let Err(e) = restore {
+        if del.is_err() {
             let _ = tx.rollback().await;
-            return MyBaseResponse::db_err(e);
+            return MyBaseResponse::db_err(del.err().unwrap());
         }
 
         if let Err(e) = tx.commit().await {
@@ -301,23 +651,35 @@ pub async fn update_item_in_cart_handler(
         return MyBaseResponse::ok(None, Some("Item removed".into()));
     }
 
-    
+    let price = match price_cart_line(
+        &mut tx,
+        &auth.user,
+        payload.product_id,
+        &payload.sell_unit,
+        payload.unit_amount,
+        payload.price_override_reason.as_deref(),
+    )
+    .await
+    {
+        Ok(p) => p,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return e.cast();
+        }
+    };
+
+    // Compare in base units: the pack size may have changed since the line was priced.
+    let Some(new_base_qty) = new_qty.checked_mul(price.units_per_sell_unit) else {
+        let _ = tx.rollback().await;
+        return MyBaseResponse::error(400, "Quantity is too large");
+    };
+    let delta = new_base_qty - old_base_qty;
+
     if delta > 0 {
-        
-        let dec = sqlx::query!(
-            r#"UPDATE products
-               SET quantity = quantity - $2, updated_at = now()
-               WHERE id = $1 AND quantity >= $2
-               RETURNING id"#,
-            payload.product_id,
-            delta
-        )
-        .fetch_optional(&mut *tx)
-        .await;
 
-        match dec {
-            Ok(Some(_)) => {}
-            Ok(None) => {
+        match reserve_stock(&mut tx, auth.user.id, &cart, item_id, payload.product_id, delta).await {
+            Ok(true) => {}
+            Ok(false) => {
                 let _ = tx.rollback().await;
                 return MyBaseResponse::error(400, "Insufficient stock for increase");
             }
@@ -327,16 +689,15 @@ pub async fn update_item_in_cart_handler(
             }
         }
     } else if delta < 0 {
-        
-        let inc = sqlx::query!(
-            r#"UPDATE products
-               SET quantity = quantity + $2, updated_at = now()
-               WHERE id = $1"#,
-            payload.product_id,
-            (-delta)
-        )
-        .execute(&mut *tx)
-        .await;
+
+        let inc = release_stock(
+            &mut tx,
+            StockMovementReason::CartRelease,
+            auth.user.id,
+            cart.id,
+            item_id,
+            Some(-delta),
+        ).await;
 
         if let Err(e) = inc {
             let _ = tx.rollback().await;
@@ -344,19 +705,17 @@ pub async fn update_item_in_cart_handler(
         }
     }
 
-    
-    let updated = sqlx::query_as::<_, CartItemModel>(
-        r#"UPDATE cart_items
-           SET quantity = $3,
-               unit_amount = $4,
-               updated_at = now()
-           WHERE cart_id = $1 AND product_id = $2
-           RETURNING id, cart_id, product_id, quantity, unit_amount, line_total, created_at, updated_at"#
-    )
-    .bind(&payload.cart_id)
-    .bind(&payload.product_id)
+    let overridden_by = price.override_reason.as_ref().map(|_| auth.user.id);
+    let updated = sqlx::query_as::<_, CartItemModel>(CartSQLString::UPDATE_CART_LINE)
+    .bind(cart.id)
+    .bind(payload.product_id)
+    .bind(&payload.sell_unit)
     .bind(new_qty)
-    .bind(&payload.unit_amount)
+    .bind(price.unit_amount)
+    .bind(price.list_amount)
+    .bind(&price.override_reason)
+    .bind(overridden_by)
+    .bind(price.units_per_sell_unit)
     .fetch_one(&mut *tx)
     .await;
 
@@ -373,4 +732,681 @@ pub async fn update_item_in_cart_handler(
     }
 
     MyBaseResponse::ok(Some(updated), Some("Item updated".into()))
-}
\ No newline at end of file
+}
+
+/// Re-validates every line of a locked Open cart against live stock and price,
+/// then writes the immutable order and its items. With a `foc_reason` the
+/// goods are given away: the order records why, and no revenue.
+async fn finalize_sale(
+    tx: &mut Transaction<'_, Postgres>,
+    cart: &CartModel,
+    foc_reason: Option<&str>,
+) -> Result<OrderWithItemsModel, MyBaseResponse<()>> {
+    let lines = query_as::<_, CartLineForCheckout>(CartSQLString::LOCK_CART_LINES_FOR_CHECKOUT)
+        .bind(cart.id)
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(MyBaseResponse::db_err)?;
+
+    if lines.is_empty() {
+        return Err(MyBaseResponse::error(400, "Cart is empty"));
+    }
+
+    // Stock is reserved when a line is added, so a negative balance means the
+    // product was oversold (e.g. corrected by an admin) since the line was added.
+    // Overridden lines keep their agreed price; every other line must still match the catalog.
+    let mut problems = Vec::new();
+    for line in &lines {
+        if line.product_quantity < 0 {
+            problems.push(format!("{}: insufficient stock", line.product_name));
+        }
+        let current = match line.sell_unit {
+            SellUnit::Unit => Some(line.product_price),
+            SellUnit::Pack => line.product_pack_price,
+        };
+        match current {
+            None => problems.push(format!(
+                "{}: no longer sold by the {}",
+                line.product_name, line.sell_unit
+            )),
+            Some(price)
+                if line.price_override_reason.is_none()
+                    && price != line.list_amount =>
+            {
+                problems.push(format!(
+                    "{}: price changed from {} to {}",
+                    line.product_name, line.list_amount, price
+                ))
+            }
+            _ => {}
+        }
+        if line.has_expired_lot {
+            problems.push(format!("{}: allocated lot has expired", line.product_name));
+        }
+        if line.sell_unit == SellUnit::Pack && line.units_per_sell_unit != line.product_units_per_pack {
+            problems.push(format!(
+                "{}: pack size changed from {} to {}",
+                line.product_name, line.units_per_sell_unit, line.product_units_per_pack
+            ));
+        }
+    }
+    if !problems.is_empty() {
+        return Err(MyBaseResponse::error(
+            409,
+            format!("Cart needs review: {}", problems.join("; ")),
+        ));
+    }
+
+    sqlx::query(CartSQLString::SET_CART_ITEMS_COST_OF_GOODS)
+        .bind(cart.id)
+        .execute(&mut **tx)
+        .await
+        .map_err(MyBaseResponse::db_err)?;
+
+    let order = query_as::<_, OrderModel>(CartSQLString::INSERT_ORDER)
+        .bind(cart.id)
+        .bind(foc_reason)
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(MyBaseResponse::db_err)?;
+
+    let items = query_as::<_, OrderItemModel>(CartSQLString::INSERT_ORDER_ITEMS)
+        .bind(order.id)
+        .bind(cart.id)
+        .bind(foc_reason.is_some())
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(MyBaseResponse::db_err)?;
+
+    Ok(OrderWithItemsModel::new(order, items))
+}
+
+/// Moves a locked cart to `to` and records who made the change.
+async fn set_cart_status(
+    tx: &mut Transaction<'_, Postgres>,
+    cart: &CartModel,
+    to: CartStatus,
+    changed_by: uuid::Uuid,
+) -> Result<CartModel, sqlx::Error> {
+    let updated = query_as::<_, CartModel>(CartSQLString::SET_CART_STATUS)
+        .bind(cart.id)
+        .bind(&to)
+        .fetch_one(&mut **tx)
+        .await?;
+
+    sqlx::query(CartSQLString::INSERT_CART_STATUS_HISTORY)
+        .bind(cart.id)
+        .bind(&cart.status)
+        .bind(&to)
+        .bind(changed_by)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(updated)
+}
+
+/// Reverses sold quantities on a locked Paid cart: writes the refund and its lines
+/// and returns the refunded units to stock. `lines = None` refunds everything left.
+async fn apply_refund(
+    tx: &mut Transaction<'_, Postgres>,
+    cart: &CartModel,
+    lines: Option<&[RefundLineSchema]>,
+    reason: Option<String>,
+    refunded_by: uuid::Uuid,
+) -> Result<RefundWithItemsModel, MyBaseResponse<()>> {
+    let order = query_as::<_, OrderModel>(CartSQLString::GET_ORDER_BY_CART_ID)
+        .bind(cart.id)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(MyBaseResponse::db_err)?
+        .ok_or_else(|| MyBaseResponse::error(409, "No order was recorded for this cart"))?;
+
+    let sold = query_as::<_, RefundableCartLine>(CartSQLString::LOCK_REFUNDABLE_CART_LINES)
+        .bind(cart.id)
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(MyBaseResponse::db_err)?;
+
+    let mut to_refund: Vec<(&RefundableCartLine, i32)> = Vec::new();
+    match lines {
+        None => {
+            for line in &sold {
+                let remaining = line.quantity - line.refunded_quantity;
+                if remaining > 0 {
+                    to_refund.push((line, remaining));
+                }
+            }
+        }
+        Some(requested) => {
+            let mut seen = HashSet::new();
+            for req in requested {
+                if !seen.insert(req.cart_item_id) {
+                    return Err(MyBaseResponse::error(
+                        400,
+                        format!("Cart item {} is listed more than once", req.cart_item_id),
+                    ));
+                }
+                let line = sold.iter().find(|l| l.id == req.cart_item_id).ok_or_else(|| {
+                    MyBaseResponse::error(404, format!("Cart item {} not found", req.cart_item_id))
+                })?;
+                let remaining = line.quantity - line.refunded_quantity;
+                let qty = req.quantity.unwrap_or(remaining);
+                if qty <= 0 {
+                    return Err(MyBaseResponse::error(400, "Refund quantity must be > 0"));
+                }
+                if qty > remaining {
+                    return Err(MyBaseResponse::error(
+                        409,
+                        format!(
+                            "Cart item {} has only {} unit(s) left to refund",
+                            line.id, remaining
+                        ),
+                    ));
+                }
+                to_refund.push((line, qty));
+            }
+        }
+    }
+
+    if to_refund.is_empty() {
+        return Err(MyBaseResponse::error(409, "Cart has already been fully refunded"));
+    }
+
+    let total: Money = to_refund
+        .iter()
+        .map(|(line, qty)| line_total(line.unit_amount, *qty))
+        .sum();
+
+    let refund = query_as::<_, RefundModel>(CartSQLString::INSERT_REFUND)
+        .bind(cart.id)
+        .bind(order.id)
+        .bind(refunded_by)
+        .bind(&reason)
+        .bind(total)
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(MyBaseResponse::db_err)?;
+
+    let mut items = Vec::with_capacity(to_refund.len());
+    for (line, qty) in &to_refund {
+        let item = query_as::<_, RefundItemModel>(CartSQLString::INSERT_REFUND_ITEM)
+            .bind(refund.id)
+            .bind(line.id)
+            .bind(line.product_id)
+            .bind(qty)
+            .bind(line.unit_amount)
+            .bind(line_total(line.unit_amount, *qty))
+            .fetch_one(&mut **tx)
+            .await
+            .map_err(MyBaseResponse::db_err)?;
+        items.push(item);
+
+        release_stock(
+            tx,
+            StockMovementReason::Refund,
+            refunded_by,
+            refund.id,
+            line.id,
+            Some(*qty * line.units_per_sell_unit),
+        )
+            .await
+            .map_err(MyBaseResponse::db_err)?;
+    }
+
+    let fully_refunded = sold.iter().all(|line| {
+        let now = to_refund
+            .iter()
+            .find(|(l, _)| l.id == line.id)
+            .map_or(0, |(_, q)| *q);
+        line.refunded_quantity + now >= line.quantity
+    });
+
+    Ok(RefundWithItemsModel {
+        refund,
+        items,
+        fully_refunded,
+    })
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/cart/checkout",
+    tag = "Carts",
+    request_body = CheckoutCartSchema,
+    responses(
+        (status = 200, description = "Cart checked out successfully", body = MyBaseResponse<OrderWithItemsModel>),
+        (status = 400, description = "Cart is empty", body = MyBaseResponse<OrderWithItemsModel>),
+        (status = 409, description = "Cart is not open or a line no longer matches stock/price", body = MyBaseResponse<OrderWithItemsModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn checkout_cart_handler(
+    auth: JWTAuthMiddleware,
+    payload: axum::extract::Json<CheckoutCartSchema>,
+    state: AppState,
+) -> MyBaseResponse<OrderWithItemsModel> {
+    let mut tx = match state.db.begin().await {
+        Ok(t) => t,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let cart = match resolve_open_cart(
+        &mut tx,
+        &auth.user,
+        payload.cart_id,
+        payload.admin_override,
+    )
+    .await
+    {
+        Ok(c) => c,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return e.cast();
+        }
+    };
+
+    let order = match finalize_sale(&mut tx, &cart, None).await {
+        Ok(o) => o,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return e.cast();
+        }
+    };
+
+    if let Err(e) = set_cart_status(&mut tx, &cart, CartStatus::Paid, auth.user.id).await {
+        let _ = tx.rollback().await;
+        return MyBaseResponse::db_err(e);
+    }
+
+    if let Err(e) = tx.commit().await {
+        return MyBaseResponse::db_err(e);
+    }
+
+    MyBaseResponse::ok(Some(order), Some("Cart checked out".into()))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/cart/status",
+    tag = "Carts",
+    request_body = UpdateCartStatusSchema,
+    responses(
+        (status = 200, description = "Cart status updated successfully", body = MyBaseResponse<CartModel>),
+        (status = 400, description = "Unknown status, or no reason given for FOC", body = MyBaseResponse<CartModel>),
+        (status = 403, description = "Another user's cart without admin_override, or refund/FOC by a non-admin", body = MyBaseResponse<CartModel>),
+        (status = 409, description = "Transition not allowed; message lists the allowed next states", body = MyBaseResponse<CartModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn update_cart_status_handler(
+    auth: JWTAuthMiddleware,
+    payload: axum::extract::Json<UpdateCartStatusSchema>,
+    state: AppState,
+) -> MyBaseResponse<CartModel> {
+    let next = match CartStatus::from_str(&payload.status) {
+        Ok(s) => s,
+        Err(_) => {
+            return MyBaseResponse::error(400, format!("Unknown cart status: {}", payload.status));
+        }
+    };
+
+    let mut tx = match state.db.begin().await {
+        Ok(t) => t,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let cart = query_as::<_, CartModel>(CartSQLString::LOCK_CART_BY_ID)
+        .bind(payload.id)
+        .fetch_optional(&mut *tx)
+        .await;
+
+    let cart = match cart {
+        Ok(Some(c)) => c,
+        Ok(None) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::error(404, "Cart not found");
+        }
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+
+    if let Err(e) = check_cart_access(&auth.user, &cart, payload.admin_override) {
+        let _ = tx.rollback().await;
+        return e.cast();
+    }
+
+    if next == CartStatus::Refund && auth.user.role != UserRole::Admin {
+        let _ = tx.rollback().await;
+        return MyBaseResponse::error(403, "Only admins can refund a cart");
+    }
+
+    if next == CartStatus::FOC && auth.user.role != UserRole::Admin {
+        let _ = tx.rollback().await;
+        return MyBaseResponse::error(403, "Only admins can give a cart away free of charge");
+    }
+    let foc_reason = payload.reason.as_deref().map(str::trim).filter(|r| !r.is_empty());
+    if next == CartStatus::FOC && foc_reason.is_none() {
+        let _ = tx.rollback().await;
+        return MyBaseResponse::error(400, "A reason is required to give a cart away free of charge");
+    }
+
+    if !cart.status.can_transition_to(&next) {
+        let _ = tx.rollback().await;
+        let allowed = cart
+            .status
+            .allowed_next()
+            .iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>();
+        return MyBaseResponse::error(
+            409,
+            format!(
+                "Cannot move cart from {} to {}. Allowed next states: [{}]",
+                cart.status,
+                next,
+                allowed.join(", ")
+            ),
+        );
+    }
+
+    // A sale, paid or free of charge, always goes through the same validation and
+    // order record as checkout, and a refund always restocks whatever has not been
+    // refunded yet.
+    let applied = match next {
+        CartStatus::Paid => finalize_sale(&mut tx, &cart, None).await.map(|_| ()),
+        CartStatus::FOC => finalize_sale(&mut tx, &cart, foc_reason).await.map(|_| ()),
+        CartStatus::Refund => apply_refund(&mut tx, &cart, None, None, auth.user.id)
+            .await
+            .map(|_| ()),
+        _ => Ok(()),
+    };
+    if let Err(e) = applied {
+        let _ = tx.rollback().await;
+        return e.cast();
+    }
+
+    let updated = match set_cart_status(&mut tx, &cart, next, auth.user.id).await {
+        Ok(c) => c,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+
+    if let Err(e) = tx.commit().await {
+        return MyBaseResponse::db_err(e);
+    }
+
+    MyBaseResponse::ok(Some(updated), Some("Cart status updated".into()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/cart/refund",
+    tag = "Carts",
+    request_body = RefundCartSchema,
+    responses(
+        (status = 200, description = "Refund recorded and stock returned", body = MyBaseResponse<RefundWithItemsModel>),
+        (status = 404, description = "Cart or cart item not found", body = MyBaseResponse<RefundWithItemsModel>),
+        (status = 409, description = "Cart is not paid or the line has already been refunded", body = MyBaseResponse<RefundWithItemsModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn refund_cart_handler(
+    auth: JWTAuthMiddleware,
+    payload: axum::extract::Json<RefundCartSchema>,
+    state: AppState,
+) -> MyBaseResponse<RefundWithItemsModel> {
+    let mut tx = match state.db.begin().await {
+        Ok(t) => t,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let cart = query_as::<_, CartModel>(CartSQLString::LOCK_CART_BY_ID)
+        .bind(payload.cart_id)
+        .fetch_optional(&mut *tx)
+        .await;
+
+    let cart = match cart {
+        Ok(Some(c)) => c,
+        Ok(None) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::error(404, "Cart not found");
+        }
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+
+    if cart.status != CartStatus::Paid {
+        let _ = tx.rollback().await;
+        return MyBaseResponse::error(
+            409,
+            format!("Only paid carts can be refunded; cart is {}", cart.status),
+        );
+    }
+
+    let refund = match apply_refund(
+        &mut tx,
+        &cart,
+        payload.lines.as_deref(),
+        payload.reason.clone(),
+        auth.user.id,
+    )
+    .await
+    {
+        Ok(r) => r,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return e.cast();
+        }
+    };
+
+    if refund.fully_refunded
+        && let Err(e) = set_cart_status(&mut tx, &cart, CartStatus::Refund, auth.user.id).await
+    {
+        let _ = tx.rollback().await;
+        return MyBaseResponse::db_err(e);
+    }
+
+    if let Err(e) = tx.commit().await {
+        return MyBaseResponse::db_err(e);
+    }
+
+    MyBaseResponse::ok(Some(refund), Some("Refund recorded".into()))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/cart/items/{id}",
+    tag = "Carts",
+    params(
+        ("id" = i64, Path, description = "Cart item id"),
+        AdminOverrideQuery
+    ),
+    responses(
+        (status = 200, description = "Item removed and its stock released", body = MyBaseResponse<CartItemModel>),
+        (status = 404, description = "Item not found", body = MyBaseResponse<CartItemModel>),
+        (status = 409, description = "Cart is not open", body = MyBaseResponse<CartItemModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn delete_cart_item_handler(
+    auth: JWTAuthMiddleware,
+    params: DeleteCartItemSchema,
+    query: AdminOverrideQuery,
+    state: AppState,
+) -> MyBaseResponse<CartItemModel> {
+    let mut tx = match state.db.begin().await {
+        Ok(t) => t,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    // Resolve the cart first so the cart row is locked before its items, like checkout does.
+    let cart_id = sqlx::query_scalar::<_, uuid::Uuid>(CartSQLString::GET_CART_ID_BY_ITEM_ID)
+        .bind(params.id)
+        .fetch_optional(&mut *tx)
+        .await;
+
+    let cart_id = match cart_id {
+        Ok(Some(id)) => id,
+        Ok(None) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::error(404, "Item not found");
+        }
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+
+    let cart = match resolve_open_cart(
+        &mut tx,
+        &auth.user,
+        Some(cart_id),
+        query.admin_override.unwrap_or(false),
+    )
+    .await
+    {
+        Ok(c) => c,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return e.cast();
+        }
+    };
+
+    if let Err(e) = release_stock(
+        &mut tx,
+        StockMovementReason::CartRelease,
+        auth.user.id,
+        cart.id,
+        params.id,
+        None,
+    ).await {
+        let _ = tx.rollback().await;
+        return MyBaseResponse::db_err(e);
+    }
+
+    let deleted = query_as::<_, CartItemModel>(CartSQLString::DELETE_CART_ITEM_BY_ID)
+        .bind(params.id)
+        .bind(cart.id)
+        .fetch_one(&mut *tx)
+        .await;
+
+    let deleted = match deleted {
+        Ok(ci) => ci,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+
+    if let Err(e) = tx.commit().await {
+        return MyBaseResponse::db_err(e);
+    }
+
+    MyBaseResponse::ok(Some(deleted), Some("Item removed".into()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/cart/clear",
+    tag = "Carts",
+    request_body = ClearCartSchema,
+    responses(
+        (status = 200, description = "Cart emptied and stock released", body = MyBaseResponse<CartModel>),
+        (status = 404, description = "Cart not found", body = MyBaseResponse<CartModel>),
+        (status = 409, description = "Cart is not open", body = MyBaseResponse<CartModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn clear_cart_handler(
+    auth: JWTAuthMiddleware,
+    payload: axum::extract::Json<ClearCartSchema>,
+    state: AppState,
+) -> MyBaseResponse<CartModel> {
+    let mut tx = match state.db.begin().await {
+        Ok(t) => t,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let cart = match resolve_open_cart(
+        &mut tx,
+        &auth.user,
+        payload.cart_id,
+        payload.admin_override,
+    )
+    .await
+    {
+        Ok(c) => c,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return e.cast();
+        }
+    };
+
+    let item_ids = sqlx::query_scalar::<_, i64>(CartSQLString::LOCK_CART_ITEM_IDS)
+        .bind(cart.id)
+        .fetch_all(&mut *tx)
+        .await;
+
+    let item_ids = match item_ids {
+        Ok(ids) => ids,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+
+    for item_id in item_ids {
+        if let Err(e) = release_stock(
+            &mut tx,
+            StockMovementReason::CartRelease,
+            auth.user.id,
+            cart.id,
+            item_id,
+            None,
+        ).await {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    }
+
+    let removed = query_as::<_, CartItemModel>(CartSQLString::DELETE_CART_ITEMS_BY_CART_ID)
+        .bind(cart.id)
+        .fetch_all(&mut *tx)
+        .await;
+
+    let removed = match removed {
+        Ok(r) => r,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+
+    let cleared = query_as::<_, CartModel>(CartSQLString::LOCK_CART_BY_ID)
+        .bind(cart.id)
+        .fetch_one(&mut *tx)
+        .await;
+
+    let cleared = match cleared {
+        Ok(c) => c,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+
+    if let Err(e) = tx.commit().await {
+        return MyBaseResponse::db_err(e);
+    }
+
+    MyBaseResponse::ok(
+        Some(cleared),
+        Some(format!("Removed {} item(s) from cart", removed.len())),
+    )
+}
@@ -4,6 +4,9 @@ use chrono::{DateTime, Utc};
 use sqlx::prelude::FromRow;
 use utoipa::ToSchema;
 
+use crate::mproduct::models::SellUnit;
+use crate::util::money::Money;
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, sqlx::Type, PartialEq, ToSchema)]
 #[sqlx(type_name = "cart_status", rename_all = "lowercase")]
 pub enum CartStatus {
@@ -47,15 +50,34 @@ impl TryFrom<String> for CartStatus {
     }
 }
 
+impl CartStatus {
+    /// States a cart may legally move to from this one. Refund and FOC are terminal.
+    pub fn allowed_next(&self) -> Vec<CartStatus> {
+        match self {
+            CartStatus::Open => vec![CartStatus::Paid, CartStatus::FOC],
+            CartStatus::Paid => vec![CartStatus::Refund],
+            CartStatus::Refund | CartStatus::FOC => vec![],
+        }
+    }
+
+    pub fn can_transition_to(&self, next: &CartStatus) -> bool {
+        self.allowed_next().contains(next)
+    }
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, FromRow, ToSchema, PartialEq)]
 #[allow(non_snake_case)]
 pub struct CartModel {
     pub id: uuid::Uuid,
     #[serde(rename = "userId")]
     pub user_id: uuid::Uuid,
+    /// Location the cart sells from; its stock is only taken from there.
+    #[serde(rename = "locationId")]
+    pub location_id: uuid::Uuid,
     pub status: CartStatus,
     #[serde(rename = "totalAmount")]
-    pub total_amount: f64,
+    #[schema(value_type = String)]
+    pub total_amount: Money,
     #[serde(rename = "createdAt")]
     pub created_at: Option<DateTime<Utc>>,
     #[serde(rename = "updatedAt")]
@@ -69,7 +91,30 @@ pub struct CartItemModel {
     pub cart_id: uuid::Uuid,
     #[serde(rename = "productId")]
     pub product_id: uuid::Uuid,
+    #[serde(rename = "sellUnit")]
+    pub sell_unit: SellUnit,
     pub quantity: i32,
+    /// Base units one `sell_unit` took out of stock when the line was priced.
+    #[serde(rename = "unitsPerSellUnit")]
+    pub units_per_sell_unit: i32,
+    /// Base units reserved by the line: `quantity * units_per_sell_unit`.
+    #[serde(rename = "baseQuantity")]
+    pub base_quantity: i32,
+    #[serde(rename = "unitAmount")]
+    #[schema(value_type = String)]
+    pub unit_amount: Money,
+    #[serde(rename = "listAmount")]
+    #[schema(value_type = String)]
+    pub list_amount: Money,
+    #[serde(rename = "lineTotal")]
+    #[schema(value_type = String)]
+    pub line_total: Money,
+    #[serde(rename = "priceOverrideReason")]
+    pub price_override_reason: Option<String>,
+    /// Cost of the stock sold on this line; set at checkout.
+    #[serde(rename = "costOfGoods")]
+    #[schema(value_type = Option<String>)]
+    pub cost_of_goods: Option<Money>,
     #[serde(rename = "createdAt")]
     pub created_at: Option<DateTime<Utc>>,
     #[serde(rename = "updatedAt")]
@@ -83,10 +128,20 @@ pub struct CartItemWithProductModel {
     pub cart_id: uuid::Uuid,
     #[serde(rename = "product_id")]
     pub product_id: uuid::Uuid,
+    pub sell_unit: SellUnit,
     pub quantity: i32,
+    pub units_per_sell_unit: i32,
+    #[schema(value_type = String)]
+    pub unit_amount: Money,
+    #[schema(value_type = String)]
+    pub line_total: Money,
+    pub price_override_reason: Option<String>,
     pub product_name: String,
-    pub product_price: f64,
-    pub product_pack_price: f64,
+    #[schema(value_type = String)]
+    pub product_price: Money,
+    #[schema(value_type = Option<String>)]
+    pub product_pack_price: Option<Money>,
+    pub product_units_per_pack: i32,
     #[serde(rename = "created_at")]
     pub created_at: Option<DateTime<Utc>>,
     #[serde(rename = "updated_at")]
@@ -98,9 +153,13 @@ pub struct CartWithItemsModel {
     pub id: uuid::Uuid,
     #[serde(rename = "userId")]
     pub user_id: uuid::Uuid,
+    /// Location the cart sells from; its stock is only taken from there.
+    #[serde(rename = "locationId")]
+    pub location_id: uuid::Uuid,
     pub status: CartStatus,
     #[serde(rename = "totalAmount")]
-    pub total_amount: f64,
+    #[schema(value_type = String)]
+    pub total_amount: Money,
     #[schema(value_type = Vec<CartItemWithProductModel>)]
     pub items: Option<sqlx::types::Json<Vec<CartItemWithProductModel>>>,
     #[serde(rename = "createdAt")]
@@ -113,6 +172,7 @@ impl CartWithItemsModel {
         Self {
             id: cart.id,
             user_id: cart.user_id,
+            location_id: cart.location_id,
             status: cart.status,
             total_amount: cart.total_amount,
             items: Some(sqlx::types::Json(items)),
@@ -121,3 +181,155 @@ impl CartWithItemsModel {
         }
     }
 }
+
+/// Cart line joined with the live product row, used to re-validate a cart at checkout.
+#[derive(Clone, Debug, FromRow, PartialEq)]
+pub struct CartLineForCheckout {
+    pub id: i64,
+    pub product_id: uuid::Uuid,
+    pub sell_unit: SellUnit,
+    pub quantity: i32,
+    pub units_per_sell_unit: i32,
+    pub unit_amount: Money,
+    pub list_amount: Money,
+    pub price_override_reason: Option<String>,
+    pub product_name: String,
+    pub product_price: Money,
+    pub product_pack_price: Option<Money>,
+    pub product_units_per_pack: i32,
+    pub product_quantity: i32,
+    /// Some of the line's units come from a lot that has expired since it was added.
+    pub has_expired_lot: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, FromRow, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct OrderModel {
+    pub id: uuid::Uuid,
+    #[serde(rename = "orderNumber")]
+    pub order_number: i64,
+    #[serde(rename = "cartId")]
+    pub cart_id: uuid::Uuid,
+    #[serde(rename = "userId")]
+    pub user_id: uuid::Uuid,
+    #[serde(rename = "totalAmount")]
+    #[schema(value_type = String)]
+    pub total_amount: Money,
+    /// Why the goods were given away; set only on free-of-charge orders,
+    /// which carry no revenue.
+    #[serde(rename = "focReason")]
+    pub foc_reason: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: Option<DateTime<Utc>>,
+}
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, FromRow, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct OrderItemModel {
+    pub id: i64,
+    #[serde(rename = "orderId")]
+    pub order_id: uuid::Uuid,
+    #[serde(rename = "cartItemId")]
+    pub cart_item_id: i64,
+    #[serde(rename = "productId")]
+    pub product_id: uuid::Uuid,
+    #[serde(rename = "productName")]
+    pub product_name: String,
+    #[serde(rename = "sellUnit")]
+    pub sell_unit: SellUnit,
+    pub quantity: i32,
+    #[serde(rename = "unitsPerSellUnit")]
+    pub units_per_sell_unit: i32,
+    #[serde(rename = "unitAmount")]
+    #[schema(value_type = String)]
+    pub unit_amount: Money,
+    #[serde(rename = "listAmount")]
+    #[schema(value_type = String)]
+    pub list_amount: Money,
+    #[serde(rename = "priceOverrideReason")]
+    pub price_override_reason: Option<String>,
+    #[serde(rename = "lineTotal")]
+    #[schema(value_type = String)]
+    pub line_total: Money,
+    /// Cost of the stock sold on this line; empty for sales made before costing.
+    #[serde(rename = "costOfGoods")]
+    #[schema(value_type = Option<String>)]
+    pub cost_of_goods: Option<Money>,
+}
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, ToSchema)]
+#[allow(non_snake_case)]
+pub struct OrderWithItemsModel {
+    #[serde(flatten)]
+    pub order: OrderModel,
+    pub items: Vec<OrderItemModel>,
+}
+impl OrderWithItemsModel {
+    pub fn new(order: OrderModel, items: Vec<OrderItemModel>) -> Self {
+        Self { order, items }
+    }
+}
+
+/// Price resolved for a cart line; `override_reason` is set when it differs from the list price.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinePrice {
+    pub unit_amount: Money,
+    pub list_amount: Money,
+    pub override_reason: Option<String>,
+    pub units_per_sell_unit: i32,
+}
+
+/// Sold cart line with how much of it has already been refunded.
+#[derive(Clone, Debug, FromRow, PartialEq)]
+pub struct RefundableCartLine {
+    pub id: i64,
+    pub product_id: uuid::Uuid,
+    pub quantity: i32,
+    pub units_per_sell_unit: i32,
+    pub unit_amount: Money,
+    pub refunded_quantity: i32,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, FromRow, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct RefundModel {
+    pub id: uuid::Uuid,
+    #[serde(rename = "cartId")]
+    pub cart_id: uuid::Uuid,
+    #[serde(rename = "orderId")]
+    pub order_id: uuid::Uuid,
+    #[serde(rename = "refundedBy")]
+    pub refunded_by: Option<uuid::Uuid>,
+    pub reason: Option<String>,
+    #[serde(rename = "totalAmount")]
+    #[schema(value_type = String)]
+    pub total_amount: Money,
+    #[serde(rename = "createdAt")]
+    pub created_at: Option<DateTime<Utc>>,
+}
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, FromRow, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct RefundItemModel {
+    pub id: i64,
+    #[serde(rename = "refundId")]
+    pub refund_id: uuid::Uuid,
+    #[serde(rename = "cartItemId")]
+    pub cart_item_id: i64,
+    #[serde(rename = "productId")]
+    pub product_id: uuid::Uuid,
+    pub quantity: i32,
+    #[serde(rename = "unitAmount")]
+    #[schema(value_type = String)]
+    pub unit_amount: Money,
+    #[serde(rename = "lineTotal")]
+    #[schema(value_type = String)]
+    pub line_total: Money,
+}
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, ToSchema)]
+#[allow(non_snake_case)]
+pub struct RefundWithItemsModel {
+    #[serde(flatten)]
+    pub refund: RefundModel,
+    pub items: Vec<RefundItemModel>,
+    /// True once every sold unit on the cart has been refunded.
+    #[serde(rename = "fullyRefunded")]
+    pub fully_refunded: bool,
+}
@@ -1,15 +1,22 @@
 use crate::{
     AppState,
-    mauth::layers::{MyAuthLayer, MyAuthPermsLayer},
+    mauth::{
+        layers::{MyAuthLayer, MyAuthPermsLayer},
+        middlewares::JWTAuthMiddleware,
+    },
     mcart::{
         self,
-        schemas::{AddCartItemSchema, UpdateCartItemSchema},
+        schemas::{
+            AddCartItemSchema, AdminOverrideQuery, CartFilterOptions, CheckoutCartSchema, ClearCartSchema,
+            CreateCartQuery, DeleteCartItemSchema,
+            RefundCartSchema, UpdateCartItemSchema, UpdateCartStatusSchema,
+        },
     },
 };
 use axum::{
-    Json, Router,
+    Extension, Json, Router,
     body::Body,
-    extract::{Query, Request, State},
+    extract::{Path, Query, Request, State},
     routing::{delete, get, post, put},
 };
 
@@ -17,15 +24,22 @@ pub fn create_cart_router(app: AppState) -> Router {
     return Router::new()
         .route(
             "/create",
-            post(|pool: State<AppState>, request: Request<Body>| async move {
-                return mcart::handlers::create_cart_handler(request, pool.0.clone()).await;
-            }),
+            post(
+                |pool: State<AppState>,
+                 Query(query): Query<CreateCartQuery>,
+                 request: Request<Body>| async move {
+                    return mcart::handlers::create_cart_handler(request, query, pool.0.clone())
+                        .await;
+                },
+            ),
         )
         .route(
             "/add-item",
             post(
-                |pool: State<AppState>, payload: Json<AddCartItemSchema>| async move {
-                    return mcart::handlers::add_item_to_cart_handler(payload, pool.0.clone())
+                |pool: State<AppState>,
+                 Extension(auth): Extension<JWTAuthMiddleware>,
+                 payload: Json<AddCartItemSchema>| async move {
+                    return mcart::handlers::add_item_to_cart_handler(auth, payload, pool.0.clone())
                         .await;
                 },
             ),
@@ -33,17 +47,94 @@ pub fn create_cart_router(app: AppState) -> Router {
         .route(
             "/update-item",
             put(
-                |pool: State<AppState>, payload: Json<UpdateCartItemSchema>| async move {
-                    return mcart::handlers::update_item_in_cart_handler(payload, pool.0.clone())
+                |pool: State<AppState>,
+                 Extension(auth): Extension<JWTAuthMiddleware>,
+                 payload: Json<UpdateCartItemSchema>| async move {
+                    return mcart::handlers::update_item_in_cart_handler(
+                        auth,
+                        payload,
+                        pool.0.clone(),
+                    )
+                    .await;
+                },
+            ),
+        )
+        .route(
+            "/items/{id}",
+            delete(
+                |pool: State<AppState>,
+                 Extension(auth): Extension<JWTAuthMiddleware>,
+                 Path(params): Path<DeleteCartItemSchema>,
+                 Query(query): Query<AdminOverrideQuery>| async move {
+                    return mcart::handlers::delete_cart_item_handler(
+                        auth,
+                        params,
+                        query,
+                        pool.0.clone(),
+                    )
+                    .await;
+                },
+            ),
+        )
+        .route(
+            "/clear",
+            post(
+                |pool: State<AppState>,
+                 Extension(auth): Extension<JWTAuthMiddleware>,
+                 payload: Json<ClearCartSchema>| async move {
+                    return mcart::handlers::clear_cart_handler(auth, payload, pool.0.clone())
                         .await;
                 },
             ),
         )
+        .route(
+            "/checkout",
+            post(
+                |pool: State<AppState>,
+                 Extension(auth): Extension<JWTAuthMiddleware>,
+                 payload: Json<CheckoutCartSchema>| async move {
+                    return mcart::handlers::checkout_cart_handler(auth, payload, pool.0.clone())
+                        .await;
+                },
+            ),
+        )
+        .route(
+            "/status",
+            put(
+                |pool: State<AppState>,
+                 Extension(auth): Extension<JWTAuthMiddleware>,
+                 payload: Json<UpdateCartStatusSchema>| async move {
+                    return mcart::handlers::update_cart_status_handler(
+                        auth,
+                        payload,
+                        pool.0.clone(),
+                    )
+                    .await;
+                },
+            ),
+        )
+        .route(
+            "/refund",
+            post(
+                |pool: State<AppState>,
+                 Extension(auth): Extension<JWTAuthMiddleware>,
+                 payload: Json<RefundCartSchema>| async move {
+                    return mcart::handlers::refund_cart_handler(auth, payload, pool.0.clone())
+                        .await;
+                },
+            )
+            .layer(MyAuthPermsLayer {}),
+        )
         .route(
             "/get-by-user",
-            get(|pool: State<AppState>, request: Request<Body>| async move {
-                return mcart::handlers::get_cart_by_user_handler(request, pool.0.clone()).await;
-            }),
+            get(
+                |pool: State<AppState>,
+                 Query(query): Query<CartFilterOptions>,
+                 request: Request<Body>| async move {
+                    return mcart::handlers::get_cart_by_user_handler(request, query, pool.0.clone())
+                        .await;
+                },
+            ),
         )
         .route(
             "/get-open-by-user",
@@ -1,42 +1,124 @@
-use utoipa::ToSchema;
+use chrono::{DateTime, Utc};
+use utoipa::{IntoParams, ToSchema};
 use validator::Validate;
 
+use crate::mcart::models::CartStatus;
+use crate::mproduct::models::SellUnit;
+use crate::util::money::Money;
+
 #[derive(serde::Serialize, serde::Deserialize, Debug, Validate, ToSchema, PartialEq)]
 pub struct AddCartItemSchema {
-    pub cart_id: uuid::Uuid,
+    /// Defaults to the caller's open cart.
+    pub cart_id: Option<uuid::Uuid>,
     pub product_id: uuid::Uuid,
     pub quantity: i32,
-    pub unit_amount: f64,
+    #[serde(default)]
+    pub sell_unit: SellUnit,
+    /// Priced from the catalog when omitted. A different price is an override.
+    #[schema(value_type = Option<String>)]
+    pub unit_amount: Option<Money>,
+    /// Required, along with the Admin role, when `unit_amount` overrides the list price.
+    pub price_override_reason: Option<String>,
+    /// Lets an Admin act on another user's cart.
+    #[serde(default)]
+    pub admin_override: bool,
 }
 #[derive(serde::Serialize, serde::Deserialize, Debug, Validate, ToSchema, PartialEq)]
 pub struct UpdateCartItemSchema {
     pub quantity: i32,
     pub product_id: uuid::Uuid,
-    pub cart_id: uuid::Uuid,
-    pub unit_amount: f64,
+    /// Defaults to the caller's open cart.
+    pub cart_id: Option<uuid::Uuid>,
+    #[serde(default)]
+    pub sell_unit: SellUnit,
+    /// Priced from the catalog when omitted. A different price is an override.
+    #[schema(value_type = Option<String>)]
+    pub unit_amount: Option<Money>,
+    /// Required, along with the Admin role, when `unit_amount` overrides the list price.
+    pub price_override_reason: Option<String>,
+    /// Lets an Admin act on another user's cart.
+    #[serde(default)]
+    pub admin_override: bool,
 }
 #[derive(serde::Serialize, serde::Deserialize, Debug, Validate, ToSchema, PartialEq)]
 pub struct DeleteCartItemSchema {
-    pub id: uuid::Uuid,
+    pub id: i64,
+}
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, ToSchema, IntoParams, PartialEq)]
+pub struct AdminOverrideQuery {
+    /// Lets an Admin act on another user's cart.
+    pub admin_override: Option<bool>,
 }
 #[derive(serde::Serialize, serde::Deserialize, Debug, Validate, ToSchema, PartialEq)]
 pub struct CreateCartSchema {
     pub user_id: uuid::Uuid,
 }
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, ToSchema, IntoParams, PartialEq)]
+pub struct CreateCartQuery {
+    /// Location the cart sells from. Defaults to the default location.
+    pub location_id: Option<uuid::Uuid>,
+}
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, ToSchema, IntoParams, PartialEq)]
+pub struct CartFilterOptions {
+    pub page: Option<i64>,
+    /// 10 by default, at most 100.
+    pub limit: Option<i64>,
+    pub status: Option<CartStatus>,
+    /// `createdAt` or `totalAmount`; prefix with `-` for descending.
+    /// Defaults to `-createdAt`.
+    pub sort: Option<String>,
+    /// `nextCursor` of the previous page, to resume after it instead of
+    /// counting pages.
+    pub cursor: Option<String>,
+    #[schema(value_type = Option<String>)]
+    #[param(value_type = Option<String>)]
+    pub total_min: Option<Money>,
+    #[schema(value_type = Option<String>)]
+    #[param(value_type = Option<String>)]
+    pub total_max: Option<Money>,
+    pub created_from: Option<DateTime<Utc>>,
+    pub created_to: Option<DateTime<Utc>>,
+}
 #[derive(serde::Serialize, serde::Deserialize, Debug, Validate, ToSchema, PartialEq)]
 pub struct UpdateCartStatusSchema {
     pub id: uuid::Uuid,
     pub status: String,
+    /// Why the cart is given away; required when moving it to FOC.
+    pub reason: Option<String>,
+    /// Lets an Admin act on another user's cart.
+    #[serde(default)]
+    pub admin_override: bool,
 }
 #[derive(serde::Serialize, serde::Deserialize, Debug, Validate, ToSchema, PartialEq)]
 pub struct ClearCartSchema {
-    pub cart_id: uuid::Uuid,
+    /// Defaults to the caller's open cart.
+    pub cart_id: Option<uuid::Uuid>,
+    /// Lets an Admin act on another user's cart.
+    #[serde(default)]
+    pub admin_override: bool,
 }
 #[derive(serde::Serialize, serde::Deserialize, Debug, Validate, ToSchema, PartialEq)]
 pub struct CheckoutCartSchema {
-    pub cart_id: uuid::Uuid,
+    /// Defaults to the caller's open cart.
+    pub cart_id: Option<uuid::Uuid>,
+    /// Lets an Admin act on another user's cart.
+    #[serde(default)]
+    pub admin_override: bool,
 }
 #[derive(serde::Serialize, serde::Deserialize, Debug, Validate, ToSchema, PartialEq)]
 pub struct GetCartByUserSchema {
     pub user_id: uuid::Uuid,
 }
+#[derive(serde::Serialize, serde::Deserialize, Debug, Validate, ToSchema, PartialEq)]
+pub struct RefundLineSchema {
+    pub cart_item_id: i64,
+    /// Defaults to everything on the line that has not been refunded yet.
+    pub quantity: Option<i32>,
+}
+#[derive(serde::Serialize, serde::Deserialize, Debug, Validate, ToSchema, PartialEq)]
+pub struct RefundCartSchema {
+    pub cart_id: uuid::Uuid,
+    pub reason: Option<String>,
+    /// Omit for a full refund of whatever remains on the cart.
+    pub lines: Option<Vec<RefundLineSchema>>,
+}
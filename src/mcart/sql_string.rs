@@ -1,9 +1,11 @@
 pub struct CartSQLString;
 impl CartSQLString {
+    // Item amounts are emitted as JSON text so NUMERIC scale survives decoding into `Money`.
     pub const GET_OPEN_CART_BY_USER_ID: &'static str = r#"
         SELECT
           c.id,
           c.user_id,
+          c.location_id,
           c.status,
           c.total_amount,
           c.created_at,
@@ -14,12 +16,17 @@ impl CartSQLString {
                 'item_id', ci.id,
                 'cart_id', ci.cart_id,
                 'product_id', ci.product_id,
+                'sell_unit', ci.sell_unit,
                 'quantity', ci.quantity,
-                'unit_amount', ci.unit_amount,
-                'line_total', ci.line_total,
+                'unit_amount', ci.unit_amount::text,
+                'list_amount', ci.list_amount::text,
+                'price_override_reason', ci.price_override_reason,
+                'line_total', ci.line_total::text,
+                'units_per_sell_unit', ci.units_per_sell_unit,
                 'product_name', p.name,
-                'product_price', p.price,
-                'product_pack_price', p.pack_price,
+                'product_price', p.price::text,
+                'product_pack_price', p.pack_price::text,
+                'product_units_per_pack', p.units_per_pack,
                 'product_created_at', p.created_at,
                 'product_updated_at', p.updated_at,
                 'created_at', ci.created_at,
@@ -36,10 +43,11 @@ impl CartSQLString {
         LIMIT 1;
     "#;
 
-    pub const GET_CART_BY_USER_ID: &'static str = r#"
-        SELECT
+    /// Columns of a cart with its items, selected from `carts c`.
+    pub const CART_WITH_ITEMS_COLUMNS: &'static str = r#"
           c.id,
           c.user_id,
+          c.location_id,
           c.status,
           c.total_amount,
           c.created_at,
@@ -50,12 +58,17 @@ impl CartSQLString {
                 'item_id', ci.id,
                 'cart_id', ci.cart_id,
                 'product_id', ci.product_id,
+                'sell_unit', ci.sell_unit,
                 'quantity', ci.quantity,
-                'unit_amount', ci.unit_amount,
-                'line_total', ci.line_total,
+                'unit_amount', ci.unit_amount::text,
+                'list_amount', ci.list_amount::text,
+                'price_override_reason', ci.price_override_reason,
+                'line_total', ci.line_total::text,
+                'units_per_sell_unit', ci.units_per_sell_unit,
                 'product_name', p.name,
-                'product_price', p.price,
-                'product_pack_price', p.pack_price,
+                'product_price', p.price::text,
+                'product_pack_price', p.pack_price::text,
+                'product_units_per_pack', p.units_per_pack,
                 'product_created_at', p.created_at,
                 'product_updated_at', p.updated_at,
                 'created_at', ci.created_at,
@@ -67,41 +80,255 @@ impl CartSQLString {
             JOIN products p ON p.id = ci.product_id
             WHERE ci.cart_id = c.id
           ) AS items
+    "#;
+
+    /// Carts listed for a user (`$1`), filtered by status (`$2`), creation
+    /// time (`$3`, `$4`) and total (`$5`, `$6`).
+    pub const CART_LIST_FROM: &'static str = r#"
         FROM carts c
         WHERE c.user_id = $1
-        ORDER BY c.created_at DESC;
+          AND ($2::cart_status IS NULL OR c.status = $2)
+          AND ($3::TIMESTAMPTZ IS NULL OR c.created_at >= $3)
+          AND ($4::TIMESTAMPTZ IS NULL OR c.created_at <= $4)
+          AND ($5::NUMERIC IS NULL OR c.total_amount >= $5)
+          AND ($6::NUMERIC IS NULL OR c.total_amount <= $6)
     "#;
 
+    /// The user's open cart, or a new one at location `$2` when there is none.
     pub const CREATE_CART_ID: &'static str = r#"
         WITH existing AS (
-            SELECT id, user_id, status, total_amount, created_at, updated_at
+            SELECT id, user_id, location_id, status, total_amount, created_at, updated_at
             FROM carts
             WHERE user_id = $1 AND status = 'open'::cart_status
         ),
         inserted AS (
-            INSERT INTO carts (id, user_id, status, total_amount)
-            SELECT uuid_generate_v4(), $1, 'open'::cart_status, 0
+            INSERT INTO carts (id, user_id, location_id, status, total_amount)
+            SELECT uuid_generate_v4(), $1, $2, 'open'::cart_status, 0
             WHERE NOT EXISTS (SELECT 1 FROM existing)
-            RETURNING id, user_id, status, total_amount, created_at, updated_at
+            RETURNING id, user_id, location_id, status, total_amount, created_at, updated_at
         )
         SELECT * FROM inserted
         UNION ALL
         SELECT * FROM existing
         LIMIT 1;
     "#;
-    pub const INSERT_CART_ITEM: &'static str = r#"
-        INSERT INTO cart_items (cart_id, product_id, quantity, unit_amount)
-        VALUES ($1, $2, $3, $4)
-        RETURNING id, cart_id, product_id, quantity, unit_amount, line_total, created_at, updated_at;
+    /// Moves an open cart to location `$2`, but only while it has no lines.
+    pub const MOVE_EMPTY_CART_TO_LOCATION: &'static str = r#"
+        UPDATE carts
+        SET location_id = $2
+        WHERE id = $1
+          AND status = 'open'::cart_status
+          AND NOT EXISTS (SELECT 1 FROM cart_items WHERE cart_id = $1)
+        RETURNING id, user_id, location_id, status, total_amount, created_at, updated_at;
+    "#;
+    pub const LOCK_CART_BY_ID: &'static str = r#"
+        SELECT id, user_id, location_id, status, total_amount, created_at, updated_at
+        FROM carts
+        WHERE id = $1
+        FOR UPDATE;
+    "#;
+
+    pub const LOCK_OPEN_CART_BY_USER_ID: &'static str = r#"
+        SELECT id, user_id, location_id, status, total_amount, created_at, updated_at
+        FROM carts
+        WHERE user_id = $1 AND status = 'open'::cart_status
+        FOR UPDATE;
+    "#;
+
+    pub const LOCK_CART_LINES_FOR_CHECKOUT: &'static str = r#"
+        SELECT
+          ci.id,
+          ci.product_id,
+          ci.sell_unit,
+          ci.quantity,
+          ci.units_per_sell_unit,
+          ci.unit_amount,
+          ci.list_amount,
+          ci.price_override_reason,
+          p.name AS product_name,
+          p.price AS product_price,
+          p.pack_price AS product_pack_price,
+          p.units_per_pack AS product_units_per_pack,
+          -- A bundle holds no stock, so check the components its line holds.
+          LEAST(p.quantity, (
+            SELECT MIN(held.quantity)
+            FROM cart_item_allocations a
+            JOIN products held ON held.id = a.product_id
+            WHERE a.cart_item_id = ci.id
+          )) AS product_quantity,
+          EXISTS (
+            SELECT 1
+            FROM cart_item_allocations a
+            JOIN product_lots l ON l.id = a.lot_id
+            WHERE a.cart_item_id = ci.id AND l.expiry_date <= CURRENT_DATE
+          ) AS has_expired_lot
+        FROM cart_items ci
+        JOIN products p ON p.id = ci.product_id
+        WHERE ci.cart_id = $1
+        ORDER BY ci.id
+        FOR UPDATE OF ci, p;
+    "#;
+
+    /// Components of a bundle in id order, so concurrent sales lock them alike.
+    pub const GET_BUNDLE_COMPONENTS: &'static str = r#"
+        SELECT component_id, quantity
+        FROM bundle_components
+        WHERE bundle_id = $1
+        ORDER BY component_id;
+    "#;
+
+    /// A free-of-charge order (`$2` is its reason) records no revenue.
+    pub const INSERT_ORDER: &'static str = r#"
+        INSERT INTO orders (cart_id, user_id, total_amount, foc_reason)
+        SELECT id, user_id, CASE WHEN $2::TEXT IS NULL THEN total_amount ELSE 0 END, $2
+        FROM carts
+        WHERE id = $1
+        RETURNING id, order_number, cart_id, user_id, total_amount, foc_reason, created_at;
+    "#;
+
+    /// Fixes each line's cost of goods sold at what its allocated stock cost.
+    pub const SET_CART_ITEMS_COST_OF_GOODS: &'static str = r#"
+        UPDATE cart_items ci
+        SET cost_of_goods = round((
+            SELECT COALESCE(SUM(a.cost_value), 0)
+            FROM cart_item_allocations a
+            WHERE a.cart_item_id = ci.id
+        ), 2)
+        WHERE ci.cart_id = $1;
+    "#;
+
+    /// Lines of a free-of-charge order (`$3`) are given away at no charge;
+    /// `list_amount` keeps what they would have sold for.
+    pub const INSERT_ORDER_ITEMS: &'static str = r#"
+        INSERT INTO order_items (
+            order_id, cart_item_id, product_id, product_name, sell_unit, quantity,
+            units_per_sell_unit, unit_amount, list_amount, price_override_reason, line_total,
+            cost_of_goods
+        )
+        SELECT $1, ci.id, ci.product_id, p.name, ci.sell_unit, ci.quantity,
+               ci.units_per_sell_unit,
+               CASE WHEN $3 THEN 0 ELSE ci.unit_amount END,
+               ci.list_amount, ci.price_override_reason,
+               CASE WHEN $3 THEN 0 ELSE ci.line_total END,
+               ci.cost_of_goods
+        FROM cart_items ci
+        JOIN products p ON p.id = ci.product_id
+        WHERE ci.cart_id = $2
+        ORDER BY ci.id
+        RETURNING id, order_id, cart_item_id, product_id, product_name, sell_unit, quantity,
+                  units_per_sell_unit, unit_amount, list_amount, price_override_reason, line_total,
+                  cost_of_goods;
+    "#;
+
+    pub const SET_CART_STATUS: &'static str = r#"
+        UPDATE carts
+        SET status = $2
+        WHERE id = $1
+        RETURNING id, user_id, location_id, status, total_amount, created_at, updated_at;
+    "#;
+
+    pub const INSERT_CART_STATUS_HISTORY: &'static str = r#"
+        INSERT INTO cart_status_history (cart_id, from_status, to_status, changed_by, admin_override)
+        SELECT $1, $2, $3, $4, user_id <> $4
+        FROM carts
+        WHERE id = $1;
+    "#;
+
+    pub const GET_ORDER_BY_CART_ID: &'static str = r#"
+        SELECT id, order_number, cart_id, user_id, total_amount, foc_reason, created_at
+        FROM orders
+        WHERE cart_id = $1;
+    "#;
+
+    pub const LOCK_REFUNDABLE_CART_LINES: &'static str = r#"
+        SELECT
+          ci.id,
+          ci.product_id,
+          ci.quantity,
+          ci.units_per_sell_unit,
+          ci.unit_amount,
+          COALESCE((
+            SELECT SUM(ri.quantity) FROM refund_items ri WHERE ri.cart_item_id = ci.id
+          ), 0)::INTEGER AS refunded_quantity
+        FROM cart_items ci
+        WHERE ci.cart_id = $1
+        ORDER BY ci.id
+        FOR UPDATE OF ci;
+    "#;
+
+    pub const INSERT_REFUND: &'static str = r#"
+        INSERT INTO refunds (cart_id, order_id, refunded_by, reason, total_amount)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, cart_id, order_id, refunded_by, reason, total_amount, created_at;
+    "#;
+
+    pub const INSERT_REFUND_ITEM: &'static str = r#"
+        INSERT INTO refund_items (refund_id, cart_item_id, product_id, quantity, unit_amount, line_total)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, refund_id, cart_item_id, product_id, quantity, unit_amount, line_total;
+    "#;
+
+    pub const GET_CART_ID_BY_ITEM_ID: &'static str = r#"
+        SELECT cart_id FROM cart_items WHERE id = $1;
+    "#;
+
+    pub const DELETE_CART_ITEM_BY_ID: &'static str = r#"
+        DELETE FROM cart_items
+        WHERE id = $1 AND cart_id = $2
+        RETURNING id, cart_id, product_id, sell_unit, quantity, units_per_sell_unit, base_quantity,
+                  unit_amount, list_amount, line_total, price_override_reason, cost_of_goods,
+                  created_at, updated_at;
     "#;
 
-    pub const UPSERT_CART_ITEM: &'static str = r#"
-        INSERT INTO cart_items (cart_id, product_id, quantity, unit_amount)
-        VALUES ($1, $2, $3, $4)
-        ON CONFLICT (cart_id, product_id) DO UPDATE
-        SET quantity = cart_items.quantity + EXCLUDED.quantity,
-            unit_amount = EXCLUDED.unit_amount,
+    pub const LOCK_CART_ITEM_IDS: &'static str = r#"
+        SELECT id FROM cart_items WHERE cart_id = $1 ORDER BY id FOR UPDATE;
+    "#;
+
+    pub const DELETE_CART_ITEMS_BY_CART_ID: &'static str = r#"
+        DELETE FROM cart_items
+        WHERE cart_id = $1
+        RETURNING id, cart_id, product_id, sell_unit, quantity, units_per_sell_unit, base_quantity,
+                  unit_amount, list_amount, line_total, price_override_reason, cost_of_goods,
+                  created_at, updated_at;
+    "#;
+
+    pub const LOCK_CART_LINE_QUANTITY: &'static str = r#"
+        SELECT id, quantity, base_quantity FROM cart_items
+        WHERE cart_id = $1 AND product_id = $2 AND sell_unit = $3
+        FOR UPDATE;
+    "#;
+
+    pub const INSERT_CART_LINE: &'static str = r#"
+        INSERT INTO cart_items (
+            cart_id, product_id, sell_unit, quantity, unit_amount, list_amount,
+            price_override_reason, price_overridden_by, units_per_sell_unit
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING id, cart_id, product_id, sell_unit, quantity, units_per_sell_unit, base_quantity,
+                  unit_amount, list_amount, line_total, price_override_reason, cost_of_goods,
+                  created_at, updated_at;
+    "#;
+
+    pub const UPDATE_CART_LINE: &'static str = r#"
+        UPDATE cart_items
+        SET quantity = $4,
+            unit_amount = $5,
+            list_amount = $6,
+            price_override_reason = $7,
+            price_overridden_by = $8,
+            units_per_sell_unit = $9,
             updated_at = now()
-        RETURNING id, cart_id, product_id, quantity, unit_amount, line_total, created_at, updated_at;
+        WHERE cart_id = $1 AND product_id = $2 AND sell_unit = $3
+        RETURNING id, cart_id, product_id, sell_unit, quantity, units_per_sell_unit, base_quantity,
+                  unit_amount, list_amount, line_total, price_override_reason, cost_of_goods,
+                  created_at, updated_at;
+    "#;
+
+    pub const DELETE_CART_LINE: &'static str = r#"
+        DELETE FROM cart_items
+        WHERE cart_id = $1 AND product_id = $2 AND sell_unit = $3
+        RETURNING id, cart_id, product_id, sell_unit, quantity, units_per_sell_unit, base_quantity,
+                  unit_amount, list_amount, line_total, price_override_reason, cost_of_goods,
+                  created_at, updated_at;
     "#;
 }
@@ -0,0 +1,311 @@
+use crate::AppState;
+use crate::mcategory::models::{
+    CategoryCatalogModel, CategoryModel, CategoryStockRow, CategoryTreeModel,
+};
+use crate::mcategory::schema::{
+    AddCategorySchema, DeleteCategorySchema, GetCategoriesSchema, UpdateCategorySchema,
+};
+use crate::shared_var::MyBaseResponse;
+use crate::util::money::Money;
+use axum::Json;
+use axum::extract::{Query, State};
+
+use sqlx::{PgExecutor, query_as};
+
+/// Stock of each product, at one location (`$1`) or across all of them.
+const PRODUCT_QUANTITY: &str = r#"
+    CASE
+      WHEN $1::UUID IS NULL THEN p.quantity
+      ELSE COALESCE(
+        (SELECT s.quantity FROM product_stock s
+         WHERE s.product_id = p.id AND s.location_id = $1), 0)
+    END
+"#;
+
+/// Looks up a category to assign or nest under.
+pub async fn resolve_category<'e, E: PgExecutor<'e>>(
+    executor: E,
+    id: uuid::Uuid,
+) -> Result<CategoryModel, MyBaseResponse<()>> {
+    query_as::<_, CategoryModel>(r#"SELECT * FROM categories WHERE id = $1"#)
+        .bind(id)
+        .fetch_optional(executor)
+        .await
+        .map_err(MyBaseResponse::db_err)?
+        .ok_or_else(|| MyBaseResponse::error(404, "Category not found"))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/categories/get",
+    tag = "Categories",
+    params(
+        GetCategoriesSchema
+    ),
+    responses(
+        (status = 200, description = "Category tree with product counts and stock value", body = MyBaseResponse<CategoryCatalogModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn get_categories_handler(
+    State(app): State<AppState>,
+    Query(params): Query<GetCategoriesSchema>,
+) -> MyBaseResponse<CategoryCatalogModel> {
+    let select_sql = format!(
+        r#"
+        SELECT c.*,
+               COUNT(p.id) AS product_count,
+               COALESCE(SUM(q.quantity), 0)::BIGINT AS quantity,
+               COALESCE(SUM(round(q.quantity * p.average_cost, 2)), 0) AS stock_value
+        FROM categories c
+        LEFT JOIN products p ON p.category_id = c.id
+        LEFT JOIN LATERAL (SELECT {PRODUCT_QUANTITY} AS quantity) q ON p.id IS NOT NULL
+        GROUP BY c.id
+        ORDER BY lower(c.name)
+    "#
+    );
+    let rows = query_as::<_, CategoryStockRow>(&select_sql)
+        .bind(params.location_id)
+        .fetch_all(&app.db)
+        .await;
+    let rows = match rows {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("database query error: {}", e);
+            return MyBaseResponse::db_err(e);
+        }
+    };
+
+    let uncategorized_sql = format!(
+        r#"
+        SELECT COUNT(*),
+               COALESCE(SUM(q.quantity), 0)::BIGINT,
+               COALESCE(SUM(round(q.quantity * p.average_cost, 2)), 0)
+        FROM products p
+        CROSS JOIN LATERAL (SELECT {PRODUCT_QUANTITY} AS quantity) q
+        WHERE p.category_id IS NULL
+    "#
+    );
+    let uncategorized = sqlx::query_as::<_, (i64, i64, Money)>(&uncategorized_sql)
+        .bind(params.location_id)
+        .fetch_one(&app.db)
+        .await;
+    let (count, quantity, value) = match uncategorized {
+        Ok(u) => u,
+        Err(e) => {
+            eprintln!("database query error: {}", e);
+            return MyBaseResponse::db_err(e);
+        }
+    };
+
+    let catalog = CategoryCatalogModel {
+        categories: CategoryTreeModel::build(rows),
+        uncategorized_product_count: count,
+        uncategorized_quantity: quantity,
+        uncategorized_stock_value: value,
+    };
+    MyBaseResponse::ok(Some(catalog), Some("Categories fetched".into()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/categories/add",
+    tag = "Categories",
+    request_body = AddCategorySchema,
+    responses(
+        (status = 200, description = "Category created successfully", body = MyBaseResponse<CategoryModel>),
+        (status = 400, description = "Invalid category"),
+        (status = 404, description = "Parent category not found"),
+        (status = 409, description = "A sibling category has this name"),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn add_category_handler(
+    State(app): State<AppState>,
+    Json(payload): Json<AddCategorySchema>,
+) -> MyBaseResponse<CategoryModel> {
+    if payload.name.trim().is_empty() {
+        return MyBaseResponse::error(400, "Category name is required");
+    }
+    if let Some(parent_id) = payload.parent_id
+        && let Err(e) = resolve_category(&app.db, parent_id).await
+    {
+        return e.cast();
+    }
+
+    let insert_sql = r#"
+        INSERT INTO categories (name, parent_id, description)
+        VALUES ($1, $2, $3)
+        RETURNING *
+    "#;
+
+    let res = query_as::<_, CategoryModel>(insert_sql)
+        .bind(payload.name.trim())
+        .bind(payload.parent_id)
+        .bind(&payload.description)
+        .fetch_one(&app.db)
+        .await;
+
+    match res {
+        Ok(category) => MyBaseResponse::ok(Some(category), Some("Category created".into())),
+        Err(e) => {
+            eprintln!("database insert error: {}", e);
+            MyBaseResponse::db_err(e)
+        }
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/categories/update",
+    tag = "Categories",
+    request_body = UpdateCategorySchema,
+    responses(
+        (status = 200, description = "Category updated successfully", body = MyBaseResponse<CategoryModel>),
+        (status = 404, description = "Category or parent category not found"),
+        (status = 409, description = "A sibling category has this name, or the move would nest the category under itself"),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn update_category_handler(
+    State(app): State<AppState>,
+    Json(payload): Json<UpdateCategorySchema>,
+) -> MyBaseResponse<CategoryModel> {
+    if payload.name.as_ref().is_some_and(|n| n.trim().is_empty()) {
+        return MyBaseResponse::error(400, "Category name is required");
+    }
+
+    let mut tx = match app.db.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    // Moves are serialised so two of them cannot close a loop between them.
+    let parent_id = if payload.clear_parent { None } else { payload.parent_id };
+    if let Some(parent_id) = parent_id {
+        let lock = sqlx::query(r#"LOCK TABLE categories IN SHARE ROW EXCLUSIVE MODE"#)
+            .execute(&mut *tx)
+            .await;
+        if let Err(e) = lock {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+        if let Err(e) = resolve_category(&mut *tx, parent_id).await {
+            let _ = tx.rollback().await;
+            return e.cast();
+        }
+
+        let loop_sql = r#"
+            WITH RECURSIVE subtree AS (
+                SELECT id FROM categories WHERE id = $1
+                UNION
+                SELECT c.id FROM categories c JOIN subtree s ON c.parent_id = s.id
+            )
+            SELECT EXISTS (SELECT 1 FROM subtree WHERE id = $2)
+        "#;
+        let loops = sqlx::query_scalar::<_, bool>(loop_sql)
+            .bind(payload.id)
+            .bind(parent_id)
+            .fetch_one(&mut *tx)
+            .await;
+        match loops {
+            Ok(false) => {}
+            Ok(true) => {
+                let _ = tx.rollback().await;
+                return MyBaseResponse::error(
+                    409,
+                    "A category cannot move under itself or one of its subcategories",
+                );
+            }
+            Err(e) => {
+                let _ = tx.rollback().await;
+                return MyBaseResponse::db_err(e);
+            }
+        }
+    }
+
+    let update_sql = r#"
+        UPDATE categories SET
+            name = COALESCE($1, name),
+            description = COALESCE($2, description),
+            parent_id = CASE WHEN $3 THEN NULL ELSE COALESCE($4, parent_id) END
+        WHERE id = $5
+        RETURNING *
+    "#;
+
+    let res = query_as::<_, CategoryModel>(update_sql)
+        .bind(payload.name.as_deref().map(str::trim))
+        .bind(&payload.description)
+        .bind(payload.clear_parent)
+        .bind(parent_id)
+        .bind(payload.id)
+        .fetch_one(&mut *tx)
+        .await;
+
+    match res {
+        Ok(category) => {
+            if let Err(e) = tx.commit().await {
+                return MyBaseResponse::db_err(e);
+            }
+            MyBaseResponse::ok(Some(category), Some("Category updated".into()))
+        }
+        Err(e) => {
+            eprintln!("database update error: {}", e);
+            let _ = tx.rollback().await;
+            MyBaseResponse::db_err(e)
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/categories/delete",
+    tag = "Categories",
+    request_body = DeleteCategorySchema,
+    responses(
+        (status = 200, description = "Category deleted; its products are left uncategorized", body = MyBaseResponse<CategoryModel>),
+        (status = 404, description = "Category not found"),
+        (status = 409, description = "Category has subcategories"),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn delete_category_handler(
+    State(app): State<AppState>,
+    Json(payload): Json<DeleteCategorySchema>,
+) -> MyBaseResponse<CategoryModel> {
+    let has_children = sqlx::query_scalar::<_, bool>(
+        r#"SELECT EXISTS (SELECT 1 FROM categories WHERE parent_id = $1)"#,
+    )
+    .bind(payload.id)
+    .fetch_one(&app.db)
+    .await;
+    match has_children {
+        Ok(false) => {}
+        Ok(true) => {
+            return MyBaseResponse::error(
+                409,
+                "Category has subcategories; move or delete them first",
+            );
+        }
+        Err(e) => return MyBaseResponse::db_err(e),
+    }
+
+    let delete_sql = r#"
+        DELETE FROM categories
+        WHERE id = $1
+        RETURNING *
+    "#;
+
+    let res = query_as::<_, CategoryModel>(delete_sql)
+        .bind(payload.id)
+        .fetch_one(&app.db)
+        .await;
+
+    match res {
+        Ok(category) => MyBaseResponse::ok(Some(category), Some("Category deleted".into())),
+        Err(e) => {
+            eprintln!("database delete error: {}", e);
+            MyBaseResponse::db_err(e)
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+
+use crate::util::money::Money;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, FromRow, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct CategoryModel {
+    pub id: uuid::Uuid,
+    /// Empty for a top-level category.
+    #[serde(rename = "parentId")]
+    pub parent_id: Option<uuid::Uuid>,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// A category with the products assigned directly to it.
+#[derive(Clone, Debug, FromRow, PartialEq)]
+pub struct CategoryStockRow {
+    #[sqlx(flatten)]
+    pub category: CategoryModel,
+    pub product_count: i64,
+    pub quantity: i64,
+    pub stock_value: Money,
+}
+
+/// A category with its subcategories. Counts and stock include every
+/// subcategory below it.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct CategoryTreeModel {
+    #[serde(flatten)]
+    pub category: CategoryModel,
+    /// Products assigned to this category itself.
+    #[serde(rename = "ownProductCount")]
+    pub own_product_count: i64,
+    #[serde(rename = "productCount")]
+    pub product_count: i64,
+    /// Base units on hand.
+    pub quantity: i64,
+    /// On-hand stock at average cost.
+    #[serde(rename = "stockValue")]
+    #[schema(value_type = String)]
+    pub stock_value: Money,
+    #[schema(no_recursion)]
+    pub children: Vec<CategoryTreeModel>,
+}
+
+impl CategoryTreeModel {
+    /// Nests categories under their parents and rolls the totals up. Children
+    /// keep the order of `rows`.
+    pub fn build(rows: Vec<CategoryStockRow>) -> Vec<Self> {
+        let mut children: HashMap<Option<uuid::Uuid>, Vec<CategoryStockRow>> = HashMap::new();
+        for row in rows {
+            children.entry(row.category.parent_id).or_default().push(row);
+        }
+        Self::nest(None, &mut children)
+    }
+
+    fn nest(
+        parent_id: Option<uuid::Uuid>,
+        children: &mut HashMap<Option<uuid::Uuid>, Vec<CategoryStockRow>>,
+    ) -> Vec<Self> {
+        let rows = children.remove(&parent_id).unwrap_or_default();
+        rows.into_iter()
+            .map(|row| {
+                let nested = Self::nest(Some(row.category.id), children);
+                Self {
+                    own_product_count: row.product_count,
+                    product_count: row.product_count
+                        + nested.iter().map(|c| c.product_count).sum::<i64>(),
+                    quantity: row.quantity + nested.iter().map(|c| c.quantity).sum::<i64>(),
+                    stock_value: row.stock_value
+                        + nested.iter().map(|c| c.stock_value).sum::<Money>(),
+                    category: row.category,
+                    children: nested,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Every category with its rolled-up totals, plus the products in none.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct CategoryCatalogModel {
+    pub categories: Vec<CategoryTreeModel>,
+    #[serde(rename = "uncategorizedProductCount")]
+    pub uncategorized_product_count: i64,
+    #[serde(rename = "uncategorizedQuantity")]
+    pub uncategorized_quantity: i64,
+    #[serde(rename = "uncategorizedStockValue")]
+    #[schema(value_type = String)]
+    pub uncategorized_stock_value: Money,
+}
@@ -0,0 +1,70 @@
+use crate::AppState;
+use crate::mauth::layers::{MyAuthLayer, MyAuthPermsLayer};
+use crate::mcategory::handlers::{
+    add_category_handler, delete_category_handler, get_categories_handler,
+    update_category_handler,
+};
+use crate::mcategory::schema::{
+    AddCategorySchema, DeleteCategorySchema, GetCategoriesSchema, UpdateCategorySchema,
+};
+use axum::Json;
+use axum::extract::Query;
+use axum::routing::{delete, post, put};
+use axum::{Router, extract::State, routing::get};
+
+pub fn create_category_router(app: AppState) -> Router {
+    return Router::new()
+        .route(
+            "/get",
+            get(
+                |State(pool): State<AppState>, params: Query<GetCategoriesSchema>| async move {
+                    let app = AppState {
+                        db: pool.db.clone(),
+                        env: pool.env.clone(),
+                    };
+                    return get_categories_handler(State(app), params).await;
+                },
+            ),
+        )
+        .route(
+            "/add",
+            post(
+                |State(pool): State<AppState>, Json(payload): Json<AddCategorySchema>| async move {
+                    let app = AppState {
+                        db: pool.db.clone(),
+                        env: pool.env.clone(),
+                    };
+                    return add_category_handler(State(app), Json(payload)).await;
+                },
+            )
+            .layer(MyAuthPermsLayer {}),
+        )
+        .route(
+            "/update",
+            put(
+                |State(pool): State<AppState>, Json(payload): Json<UpdateCategorySchema>| async move {
+                    let app = AppState {
+                        db: pool.db.clone(),
+                        env: pool.env.clone(),
+                    };
+                    return update_category_handler(State(app), Json(payload)).await;
+                },
+            )
+            .layer(MyAuthPermsLayer {}),
+        )
+        .route(
+            "/delete",
+            delete(
+                |State(pool): State<AppState>, Json(payload): Json<DeleteCategorySchema>| async move {
+                    let app = AppState {
+                        db: pool.db.clone(),
+                        env: pool.env.clone(),
+                    };
+                    return delete_category_handler(State(app), Json(payload)).await;
+                },
+            )
+            .layer(MyAuthPermsLayer {}),
+        )
+        .layer(MyAuthLayer { state: app.clone() })
+        .with_state(app);
+}
@@ -0,0 +1,37 @@
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, ToSchema, IntoParams, PartialEq)]
+pub struct GetCategoriesSchema {
+    /// Count stock at this location only; every location when empty.
+    pub location_id: Option<uuid::Uuid>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct AddCategorySchema {
+    pub name: String,
+    /// Nest under this category; top level when empty.
+    pub parent_id: Option<uuid::Uuid>,
+    pub description: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct UpdateCategorySchema {
+    #[serde()]
+    pub id: uuid::Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Move under this category, with everything below it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<uuid::Uuid>,
+    /// Move to the top level; `parent_id` is ignored.
+    #[serde(default)]
+    pub clear_parent: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct DeleteCategorySchema {
+    #[serde()]
+    pub id: uuid::Uuid,
+}
@@ -0,0 +1,178 @@
+use crate::AppState;
+use crate::mlocation::models::LocationModel;
+use crate::mlocation::schemas::{AddLocationSchema, UpdateLocationSchema};
+use crate::mlocation::sql_string::LocationSQLString;
+use crate::shared_var::MyBaseResponse;
+
+use sqlx::{PgExecutor, query_as};
+
+/// Looks up the named location, or the default location when none is named.
+pub async fn resolve_location<'e, E: PgExecutor<'e>>(
+    executor: E,
+    location_id: Option<uuid::Uuid>,
+) -> Result<LocationModel, MyBaseResponse<()>> {
+    query_as::<_, LocationModel>(LocationSQLString::RESOLVE_LOCATION)
+        .bind(location_id)
+        .fetch_optional(executor)
+        .await
+        .map_err(MyBaseResponse::db_err)?
+        .ok_or_else(|| match location_id {
+            Some(_) => MyBaseResponse::error(404, "Location not found"),
+            None => MyBaseResponse::error(409, "No default location is set"),
+        })
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/locations/get",
+    tag = "Locations",
+    responses(
+        (status = 200, description = "Locations retrieved successfully", body = MyBaseResponse<Vec<LocationModel>>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn get_locations_handler(state: AppState) -> MyBaseResponse<Vec<LocationModel>> {
+    match query_as::<_, LocationModel>(LocationSQLString::GET_LOCATIONS)
+        .fetch_all(&state.db)
+        .await
+    {
+        Ok(l) => MyBaseResponse::ok(Some(l), Some("Locations retrieved successfully".into())),
+        Err(e) => MyBaseResponse::db_err(e),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/locations/add",
+    tag = "Locations",
+    request_body = AddLocationSchema,
+    responses(
+        (status = 200, description = "Location added successfully", body = MyBaseResponse<LocationModel>),
+        (status = 400, description = "Name is required", body = MyBaseResponse<LocationModel>),
+        (status = 409, description = "Name already in use", body = MyBaseResponse<LocationModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn add_location_handler(
+    payload: AddLocationSchema,
+    state: AppState,
+) -> MyBaseResponse<LocationModel> {
+    let name = payload.name.trim();
+    if name.is_empty() {
+        return MyBaseResponse::error(400, "Name is required");
+    }
+
+    let mut tx = match state.db.begin().await {
+        Ok(t) => t,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    if payload.is_default
+        && let Err(e) = sqlx::query(LocationSQLString::CLEAR_DEFAULT_LOCATION)
+            .bind(uuid::Uuid::nil())
+            .execute(&mut *tx)
+            .await
+    {
+        let _ = tx.rollback().await;
+        return MyBaseResponse::db_err(e);
+    }
+
+    let location = match query_as::<_, LocationModel>(LocationSQLString::INSERT_LOCATION)
+        .bind(name)
+        .bind(payload.is_default)
+        .fetch_one(&mut *tx)
+        .await
+    {
+        Ok(l) => l,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+
+    if let Err(e) = tx.commit().await {
+        return MyBaseResponse::db_err(e);
+    }
+    MyBaseResponse::ok(Some(location), Some("Location added successfully".into()))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/locations/update",
+    tag = "Locations",
+    request_body = UpdateLocationSchema,
+    responses(
+        (status = 200, description = "Location updated successfully", body = MyBaseResponse<LocationModel>),
+        (status = 400, description = "Invalid change", body = MyBaseResponse<LocationModel>),
+        (status = 404, description = "Location not found", body = MyBaseResponse<LocationModel>),
+        (status = 409, description = "Name already in use", body = MyBaseResponse<LocationModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn update_location_handler(
+    payload: UpdateLocationSchema,
+    state: AppState,
+) -> MyBaseResponse<LocationModel> {
+    if payload.is_default == Some(false) {
+        return MyBaseResponse::error(
+            400,
+            "Make another location the default instead of unsetting it",
+        );
+    }
+    let name = payload.name.as_deref().map(str::trim);
+    if name == Some("") {
+        return MyBaseResponse::error(400, "Name cannot be empty");
+    }
+
+    let mut tx = match state.db.begin().await {
+        Ok(t) => t,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let existing = match query_as::<_, LocationModel>(LocationSQLString::LOCK_LOCATION)
+        .bind(payload.id)
+        .fetch_optional(&mut *tx)
+        .await
+    {
+        Ok(Some(l)) => l,
+        Ok(None) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::error(404, "Location not found");
+        }
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+
+    let is_default = existing.is_default || payload.is_default == Some(true);
+    if is_default
+        && !existing.is_default
+        && let Err(e) = sqlx::query(LocationSQLString::CLEAR_DEFAULT_LOCATION)
+            .bind(existing.id)
+            .execute(&mut *tx)
+            .await
+    {
+        let _ = tx.rollback().await;
+        return MyBaseResponse::db_err(e);
+    }
+
+    let location = match query_as::<_, LocationModel>(LocationSQLString::UPDATE_LOCATION)
+        .bind(existing.id)
+        .bind(name.unwrap_or(&existing.name))
+        .bind(is_default)
+        .fetch_one(&mut *tx)
+        .await
+    {
+        Ok(l) => l,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+
+    if let Err(e) = tx.commit().await {
+        return MyBaseResponse::db_err(e);
+    }
+    MyBaseResponse::ok(Some(location), Some("Location updated successfully".into()))
+}
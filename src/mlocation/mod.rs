@@ -0,0 +1,5 @@
+pub mod handlers;
+pub mod models;
+pub mod schemas;
+pub mod routes;
+pub mod sql_string;
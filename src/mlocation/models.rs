@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+
+/// A place stock is held and sold from, such as the shop floor or the back store.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, FromRow, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct LocationModel {
+    pub id: uuid::Uuid,
+    pub name: String,
+    /// Used whenever a request does not name a location.
+    #[serde(rename = "isDefault")]
+    pub is_default: bool,
+    #[serde(rename = "createdAt")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Base units of a product held at one location.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, FromRow, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct LocationStockModel {
+    #[serde(skip)]
+    pub product_id: uuid::Uuid,
+    #[serde(rename = "locationId")]
+    pub location_id: uuid::Uuid,
+    #[serde(rename = "locationName")]
+    pub location_name: String,
+    pub quantity: i32,
+}
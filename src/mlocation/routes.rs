@@ -0,0 +1,45 @@
+use crate::{
+    AppState,
+    mauth::layers::{MyAuthLayer, MyAuthPermsLayer},
+    mlocation::{
+        self,
+        schemas::{AddLocationSchema, UpdateLocationSchema},
+    },
+};
+use axum::{
+    Json, Router,
+    extract::State,
+    routing::{get, post, put},
+};
+
+pub fn create_location_router(app: AppState) -> Router {
+    return Router::new()
+        .route(
+            "/get",
+            get(|pool: State<AppState>| async move {
+                return mlocation::handlers::get_locations_handler(pool.0.clone()).await;
+            }),
+        )
+        .route(
+            "/add",
+            post(
+                |pool: State<AppState>, Json(payload): Json<AddLocationSchema>| async move {
+                    return mlocation::handlers::add_location_handler(payload, pool.0.clone())
+                        .await;
+                },
+            )
+            .layer(MyAuthPermsLayer {}),
+        )
+        .route(
+            "/update",
+            put(
+                |pool: State<AppState>, Json(payload): Json<UpdateLocationSchema>| async move {
+                    return mlocation::handlers::update_location_handler(payload, pool.0.clone())
+                        .await;
+                },
+            )
+            .layer(MyAuthPermsLayer {}),
+        )
+        .layer(MyAuthLayer { state: app.clone() })
+        .with_state(app);
+}
@@ -0,0 +1,17 @@
+use utoipa::ToSchema;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct AddLocationSchema {
+    pub name: String,
+    /// Makes this the default location in place of the current one.
+    #[serde(default)]
+    pub is_default: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct UpdateLocationSchema {
+    pub id: uuid::Uuid,
+    pub name: Option<String>,
+    /// Only `true` is accepted; the default moves by making another location the default.
+    pub is_default: Option<bool>,
+}
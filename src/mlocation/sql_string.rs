@@ -0,0 +1,54 @@
+pub struct LocationSQLString;
+impl LocationSQLString {
+    pub const GET_LOCATIONS: &'static str = r#"
+        SELECT id, name, is_default, created_at, updated_at
+        FROM locations
+        ORDER BY is_default DESC, name;
+    "#;
+
+    /// The location with id `$1`, or the default location when `$1` is NULL.
+    pub const RESOLVE_LOCATION: &'static str = r#"
+        SELECT id, name, is_default, created_at, updated_at
+        FROM locations
+        WHERE ($1::UUID IS NULL AND is_default) OR id = $1;
+    "#;
+
+    pub const LOCK_LOCATION: &'static str = r#"
+        SELECT id, name, is_default, created_at, updated_at
+        FROM locations
+        WHERE id = $1
+        FOR UPDATE;
+    "#;
+
+    pub const CLEAR_DEFAULT_LOCATION: &'static str = r#"
+        UPDATE locations SET is_default = FALSE WHERE is_default AND id <> $1;
+    "#;
+
+    pub const INSERT_LOCATION: &'static str = r#"
+        INSERT INTO locations (name, is_default)
+        VALUES ($1, $2)
+        RETURNING id, name, is_default, created_at, updated_at;
+    "#;
+
+    pub const UPDATE_LOCATION: &'static str = r#"
+        UPDATE locations
+        SET name = $2, is_default = $3
+        WHERE id = $1
+        RETURNING id, name, is_default, created_at, updated_at;
+    "#;
+
+    /// Stock of each product in `$1` at every location, or only at `$2` when set.
+    /// Locations without a stock row show zero.
+    pub const GET_PRODUCT_STOCK_LEVELS: &'static str = r#"
+        SELECT
+          p.id AS product_id,
+          l.id AS location_id,
+          l.name AS location_name,
+          COALESCE(s.quantity, 0) AS quantity
+        FROM unnest($1::UUID[]) AS p(id)
+        CROSS JOIN locations l
+        LEFT JOIN product_stock s ON s.product_id = p.id AND s.location_id = l.id
+        WHERE $2::UUID IS NULL OR l.id = $2
+        ORDER BY p.id, l.is_default DESC, l.name;
+    "#;
+}
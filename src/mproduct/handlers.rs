@@ -1,75 +1,158 @@
+use std::collections::HashMap;
+
 use axum::Json;
 use axum::extract::{Query, State};
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
 // use
-use sqlx::query_as;
+use sqlx::postgres::PgArguments;
+use sqlx::query::QueryAs;
+use sqlx::{PgExecutor, PgPool, Postgres, Transaction, query_as};
 
 use crate::AppState;
-use crate::mproduct::models::ProductModel;
-use crate::mproduct::schema::{AddProductSchema, DeleteProductSchema, UpdateProductSchema};
-use crate::shared_var::{FilterOptions, MyBaseResponse};
+use crate::mauth::middlewares::JWTAuthMiddleware;
+use crate::mcategory::handlers::resolve_category;
+use crate::mlocation::handlers::resolve_location;
+use crate::mlocation::models::LocationStockModel;
+use crate::mlocation::sql_string::LocationSQLString;
+use crate::mproduct::labels::{Label, LabelBarcode, Symbology, render_pdf, render_zpl};
+use crate::mproduct::models::{
+    BarcodeType, BundleComponentModel, BundleModel, BundlePricing, LabelFormat,
+    ProductBarcodeModel, ProductLookupModel, ProductLotModel, ProductLotsModel, ProductModel,
+    ProductSuggestionModel, ProductWithStockModel, SellUnit,
+};
+use crate::mproduct::schema::{
+    AddProductBarcodeSchema, AddProductLotSchema, AddProductSchema, AdjustStockSchema,
+    AutocompleteQuery, BundleComponentSchema, DeleteProductBarcodeSchema, DeleteProductSchema, GetBundleSchema,
+    GetProductBarcodesSchema, GetProductLotsSchema, PrintLabelsSchema, ProductFilterOptions,
+    ProductLookupQuery, UpdateBundleSchema, UpdateProductSchema,
+};
+use crate::mstock::handlers::{apply_costed_stock_movement, apply_stock_movement};
+use crate::mstock::models::{NewStockMovement, StockAdjustmentReason, StockMovementReason};
+use crate::musers::models::UserRole;
+use crate::shared_var::MyBaseResponse;
+use crate::util::barcode::{MAX_CODE_LEN, is_valid_code, scan_variants};
+use crate::util::money::{Money, round_money};
+use crate::util::pagination::{Keyed, PageRequest, Sort, SortField, check_range};
 
 #[utoipa::path(
     get,
     path = "/api/v1/products/get", 
     tag = "Products",
     params(
-        FilterOptions
+        ProductFilterOptions
     ),
     responses(
-        (status = 200, description = "Products fetched successfully", body = MyBaseResponse<Vec<ProductModel>>),
-        (status = 500, description = "Database error", body = MyBaseResponse<Vec<ProductModel>>),
+        (status = 200, description = "One page of products, with its place in the list in `meta`", body = MyBaseResponse<Vec<ProductWithStockModel>>),
+        (status = 400, description = "Unknown sort field, invalid cursor or an empty range"),
+        (status = 404, description = "Location not found", body = MyBaseResponse<Vec<ProductWithStockModel>>),
+        (status = 500, description = "Database error", body = MyBaseResponse<Vec<ProductWithStockModel>>),
     ),
      security(("bearerAuth" = [])), 
 )]
 pub async fn get_product_handler(
-    Query(opts): Query<FilterOptions>,
+    Query(opts): Query<ProductFilterOptions>,
     State(app_state): State<AppState>,
-) -> MyBaseResponse<Vec<ProductModel>> {
-    let limit = opts.limit.unwrap_or(10);
+) -> MyBaseResponse<Vec<ProductWithStockModel>> {
+    let search = opts
+        .search
+        .as_ref()
+        .map(|term| term.trim().to_lowercase())
+        .filter(|term| !term.is_empty());
+    // Relevance only orders a search.
+    let (sorts, default_sort) = match search {
+        Some(_) => (&PRODUCT_SORTS[..], "-relevance"),
+        None => (&PRODUCT_SORTS[1..], "-createdAt"),
+    };
+    let page = match Sort::parse(opts.sort.as_deref(), sorts, default_sort)
+        .and_then(|sort| PageRequest::new(sort, opts.page, opts.limit, opts.cursor.as_deref()))
+    {
+        Ok(p) => p,
+        Err(e) => return e.cast(),
+    };
+    let ranges = check_range("price", opts.price_min.as_ref(), opts.price_max.as_ref())
+        .and(check_range("quantity", opts.quantity_min.as_ref(), opts.quantity_max.as_ref()))
+        .and(check_range("created", opts.created_from.as_ref(), opts.created_to.as_ref()));
+    if let Err(e) = ranges {
+        return e.cast();
+    }
 
-    let _offset = (opts.page.unwrap_or(1) - 1) * limit;
+    if let Some(location_id) = opts.location_id
+        && let Err(e) = resolve_location(&app_state.db, Some(location_id)).await
+    {
+        return e.cast();
+    }
 
-    if let Some(search_term) = opts.search {
-        println!("Searching for products with term: {}", search_term);
-        let search_result = query_as!(
-            ProductModel,
-            r#"
-            SELECT *
-            FROM products
-            WHERE name ILIKE $1
-            ORDER BY created_at DESC
-            "#,
-            format!("%{}%", search_term.to_string()),
-        )
-        .fetch_all(&app_state.db)
+    if let Some(category_id) = opts.category_id
+        && let Err(e) = resolve_category(&app_state.db, category_id).await
+    {
+        return e.cast();
+    }
+
+    let mut tx = match begin_search(&app_state.db).await {
+        Ok(t) => t,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let count_sql = format!("{PRODUCT_LIST_MATCHES} SELECT COUNT(*) {PRODUCT_LIST_FROM}");
+    let total = bind_product_filters(query_as::<_, (i64,)>(&count_sql), &opts, &search)
+        .fetch_one(&mut *tx)
         .await;
-        match search_result {
-            Ok(p) => {
-                println!("Fetched products: {:?}", p);
-                return MyBaseResponse::ok(Some(p), Some("Product retrieved successfully".into()));
-            }
-            Err(err) => {
-                eprintln!("database query error: {}", err);
-                return MyBaseResponse::error(500, "Database query failed");
-            }
+    let total = match total {
+        Ok((t,)) => t,
+        Err(err) => {
+            eprintln!("database query error: {}", err);
+            return MyBaseResponse::error(500, "Database query failed");
         }
+    };
+
+    let select_sql = format!(
+        "{PRODUCT_LIST_MATCHES} SELECT p.*, {} {PRODUCT_LIST_FROM} AND {} ORDER BY {} LIMIT $10 OFFSET $11",
+        page.sort.key_columns("p.id"),
+        page.sort.after("p.id", 12),
+        page.sort.order_by("p.id"),
+    );
+    let query_result =
+        bind_product_filters(query_as::<_, Keyed<ProductModel>>(&select_sql), &opts, &search)
+            .bind(page.fetch_limit())
+            .bind(page.offset())
+            .bind(page.cursor_key())
+            .bind(page.cursor_id())
+            .fetch_all(&mut *tx)
+            .await;
+
+    let rows = match query_result {
+        Ok(p) => p,
+        Err(err) => {
+            eprintln!("database query error: {}", err);
+            return MyBaseResponse::error(500, "Database query failed");
+        }
+    };
+    if let Err(e) = tx.commit().await {
+        return MyBaseResponse::db_err(e);
     }
+    let (mut products, meta) = page.finish(rows, total);
 
-    let query_result = query_as!(
+    let parent_ids: Vec<uuid::Uuid> = products.iter().map(|p| p.id).collect();
+    let variants = query_as!(
         ProductModel,
-        r#"
-        SELECT *
-        FROM products ORDER by created_at DESC
-
-        "#,
+        r#"SELECT * FROM products WHERE parent_id = ANY($1) ORDER BY lower(name)"#,
+        &parent_ids,
     )
     .fetch_all(&app_state.db)
     .await;
+    match variants {
+        Ok(v) => products.extend(v),
+        Err(err) => {
+            eprintln!("database query error: {}", err);
+            return MyBaseResponse::error(500, "Database query failed");
+        }
+    }
 
-    match query_result {
+    match with_location_stock(&app_state, products, opts.location_id).await {
         Ok(p) => {
-            println!("Fetched products: {:?}", p);
-            MyBaseResponse::ok(Some(p), Some("Product retrieved successfully".into()))
+            let p = group_variants(p);
+            MyBaseResponse::page(p, meta, Some("Product retrieved successfully".into()))
         }
         Err(err) => {
             eprintln!("database query error: {}", err);
@@ -78,6 +161,219 @@ pub async fn get_product_handler(
     }
 }
 
+/// Fields products may be sorted by. Relevance comes first so lists that
+/// are not a search can leave it out.
+static PRODUCT_SORTS: [SortField; 5] = [
+    SortField { name: "relevance", sql: "m.rank", sql_type: "REAL" },
+    SortField { name: "name", sql: "lower(p.name)", sql_type: "TEXT" },
+    SortField { name: "price", sql: "p.price", sql_type: "NUMERIC" },
+    SortField { name: "quantity", sql: "stock.quantity", sql_type: "BIGINT" },
+    SortField {
+        name: "createdAt",
+        sql: "COALESCE(p.created_at, '-infinity'::timestamptz)",
+        sql_type: "TIMESTAMPTZ",
+    },
+];
+
+/// Product families matching the search (`$1`) and their rank. Variants are
+/// listed under their parent, so a family matches, and ranks, by the best of
+/// the parent and its variants.
+const PRODUCT_LIST_MATCHES: &str = r#"
+    WITH RECURSIVE subtree AS (
+        SELECT id FROM categories WHERE id = $2
+        UNION
+        SELECT c.id FROM categories c JOIN subtree s ON c.parent_id = s.id
+    ),
+    matches AS (
+        SELECT COALESCE(p.parent_id, p.id) AS family_id,
+               MAX(ts_rank(s.document, product_search_query($1))
+                   + word_similarity($1, s.search_name)) AS rank
+        FROM product_search s
+        JOIN products p ON p.id = s.product_id
+        WHERE $1::TEXT IS NOT NULL
+          AND (s.document @@ product_search_query($1) OR $1 <% s.search_name)
+        GROUP BY 1
+    )
+"#;
+
+/// Top-level products passing the list filters bound by
+/// [`bind_product_filters`]. A family's stock is that of all its members, at
+/// one location if `$3` is given.
+const PRODUCT_LIST_FROM: &str = r#"
+    FROM products p
+    LEFT JOIN matches m ON m.family_id = p.id
+    CROSS JOIN LATERAL (
+        SELECT COALESCE(SUM(s.quantity), 0)::BIGINT AS quantity
+        FROM products f
+        JOIN product_stock s ON s.product_id = f.id
+        WHERE (f.id = p.id OR f.parent_id = p.id)
+          AND ($3::UUID IS NULL OR s.location_id = $3)
+    ) stock
+    WHERE p.parent_id IS NULL
+      AND ($1::TEXT IS NULL OR m.family_id IS NOT NULL)
+      AND ($2::UUID IS NULL OR p.category_id IN (SELECT id FROM subtree))
+      AND ($4::NUMERIC IS NULL OR p.price >= $4)
+      AND ($5::NUMERIC IS NULL OR p.price <= $5)
+      AND ($6::BIGINT IS NULL OR stock.quantity >= $6)
+      AND ($7::BIGINT IS NULL OR stock.quantity <= $7)
+      AND ($8::TIMESTAMPTZ IS NULL OR p.created_at >= $8)
+      AND ($9::TIMESTAMPTZ IS NULL OR p.created_at <= $9)
+"#;
+
+/// Binds `$1` to `$9` of [`PRODUCT_LIST_MATCHES`] and [`PRODUCT_LIST_FROM`].
+fn bind_product_filters<'q, O>(
+    query: QueryAs<'q, Postgres, O, PgArguments>,
+    opts: &'q ProductFilterOptions,
+    search: &'q Option<String>,
+) -> QueryAs<'q, Postgres, O, PgArguments> {
+    query
+        .bind(search)
+        .bind(opts.category_id)
+        .bind(opts.location_id)
+        .bind(opts.price_min)
+        .bind(opts.price_max)
+        .bind(opts.quantity_min)
+        .bind(opts.quantity_max)
+        .bind(opts.created_from)
+        .bind(opts.created_to)
+}
+
+/// Trigram word similarity a misspelt name needs to match. pg_trgm's default
+/// of 0.6 misses a single wrong letter in a short name.
+const FUZZY_NAME_THRESHOLD: &str = "0.4";
+
+/// Opens a read transaction in which fuzzy name matching (`<%`) uses
+/// [`FUZZY_NAME_THRESHOLD`].
+async fn begin_search(db: &PgPool) -> Result<Transaction<'static, Postgres>, sqlx::Error> {
+    let mut tx = db.begin().await?;
+    sqlx::query_scalar!(
+        r#"SELECT set_config('pg_trgm.word_similarity_threshold', $1, true)"#,
+        FUZZY_NAME_THRESHOLD,
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    Ok(tx)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/products/autocomplete",
+    tag = "Products",
+    params(
+        AutocompleteQuery
+    ),
+    responses(
+        (status = 200, description = "Best matching sellable products", body = MyBaseResponse<Vec<ProductSuggestionModel>>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn autocomplete_products_handler(
+    Query(params): Query<AutocompleteQuery>,
+    State(app_state): State<AppState>,
+) -> MyBaseResponse<Vec<ProductSuggestionModel>> {
+    let term = params.q.trim().to_lowercase();
+    if term.is_empty() {
+        return MyBaseResponse::ok(Some(Vec::new()), Some("No search term".into()));
+    }
+    let limit = params.limit.unwrap_or(8).clamp(1, MAX_SUGGESTIONS);
+
+    let mut tx = match begin_search(&app_state.db).await {
+        Ok(t) => t,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+    // Names starting with the term come first, as the cashier is usually
+    // typing the start of one.
+    let suggestions = query_as!(
+        ProductSuggestionModel,
+        r#"
+        SELECT p.id, p.name, p.sku, p.price, p.quantity
+        FROM product_search s
+        JOIN products p ON p.id = s.product_id
+        WHERE (s.document @@ product_search_query($1) OR $1 <% s.search_name)
+          AND NOT EXISTS (SELECT 1 FROM products v WHERE v.parent_id = p.id)
+        ORDER BY starts_with(s.search_name, $1) DESC,
+                 ts_rank(s.document, product_search_query($1))
+                   + word_similarity($1, s.search_name) DESC,
+                 s.search_name
+        LIMIT $2
+        "#,
+        term,
+        limit,
+    )
+    .fetch_all(&mut *tx)
+    .await;
+    let suggestions = match suggestions {
+        Ok(s) => s,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+    if let Err(e) = tx.commit().await {
+        return MyBaseResponse::db_err(e);
+    }
+    MyBaseResponse::ok(Some(suggestions), Some("Suggestions retrieved".into()))
+}
+
+/// Attaches each product's stock at every location, or at `location_id` only.
+async fn with_location_stock(
+    app_state: &AppState,
+    products: Vec<ProductModel>,
+    location_id: Option<uuid::Uuid>,
+) -> Result<Vec<ProductWithStockModel>, sqlx::Error> {
+    let ids: Vec<uuid::Uuid> = products.iter().map(|p| p.id).collect();
+    let levels = sqlx::query_as::<_, LocationStockModel>(LocationSQLString::GET_PRODUCT_STOCK_LEVELS)
+        .bind(&ids)
+        .bind(location_id)
+        .fetch_all(&app_state.db)
+        .await?;
+
+    let mut by_product: HashMap<uuid::Uuid, Vec<LocationStockModel>> = HashMap::new();
+    for level in levels {
+        by_product.entry(level.product_id).or_default().push(level);
+    }
+    Ok(products
+        .into_iter()
+        .map(|product| ProductWithStockModel {
+            locations: by_product.remove(&product.id).unwrap_or_default(),
+            product,
+            variants: Vec::new(),
+        })
+        .collect())
+}
+
+/// Moves each variant under its parent, keeping the order of both.
+fn group_variants(products: Vec<ProductWithStockModel>) -> Vec<ProductWithStockModel> {
+    let (variants, mut parents): (Vec<_>, Vec<_>) = products
+        .into_iter()
+        .partition(|p| p.product.parent_id.is_some());
+    let index: HashMap<uuid::Uuid, usize> = parents
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (p.product.id, i))
+        .collect();
+    for variant in variants {
+        if let Some(&i) = variant.product.parent_id.as_ref().and_then(|id| index.get(id)) {
+            parents[i].variants.push(variant);
+        }
+    }
+    parents
+}
+
+/// Trims a variant's attributes and checks they map names to non-empty text.
+fn normalize_variant_attributes(value: &serde_json::Value) -> Option<serde_json::Value> {
+    let attributes = value.as_object()?;
+    if attributes.is_empty() {
+        return None;
+    }
+    let mut normalized = serde_json::Map::new();
+    for (name, value) in attributes {
+        let (name, value) = (name.trim(), value.as_str()?.trim());
+        if name.is_empty() || value.is_empty() {
+            return None;
+        }
+        normalized.insert(name.to_string(), serde_json::Value::from(value));
+    }
+    Some(serde_json::Value::Object(normalized))
+}
+
 #[utoipa::path(
     post,
     path = "/api/v1/products/add", 
@@ -90,38 +386,191 @@ pub async fn get_product_handler(
      security(("bearerAuth" = [])), 
 )]
 pub async fn add_product_handler(
+    auth: JWTAuthMiddleware,
     Json(payload): Json<AddProductSchema>,
     State(app): State<AppState>,
 ) -> MyBaseResponse<ProductModel> {
     println!("Request received to add product: {:?}", payload);
+    if payload.unit_cost.is_some_and(|c| c.is_sign_negative()) {
+        return MyBaseResponse::error(400, "Unit cost cannot be negative");
+    }
+    if payload.reorder_level.is_some_and(|l| l < 0)
+        || payload.reorder_quantity.is_some_and(|q| q < 0)
+    {
+        return MyBaseResponse::error(400, "Reorder level and quantity cannot be negative");
+    }
+    if payload.units_per_pack.is_some_and(|u| u <= 0) {
+        return MyBaseResponse::error(400, "Units per pack must be > 0");
+    }
+    let sku = payload.sku.as_deref().map(str::trim);
+    if sku.is_some_and(|s| !is_valid_code(s)) {
+        return MyBaseResponse::error(
+            400,
+            format!("A SKU is 1 to {} characters without spaces", MAX_CODE_LEN),
+        );
+    }
+    if let Some(category_id) = payload.category_id
+        && let Err(e) = resolve_category(&app.db, category_id).await
+    {
+        return e.cast();
+    }
+    let variant_attributes = match (payload.parent_id, &payload.variant_attributes) {
+        (None, None) => None,
+        (None, Some(_)) => {
+            return MyBaseResponse::error(400, "Only a variant has variant attributes");
+        }
+        (Some(_), _) if payload.category_id.is_some() => {
+            return MyBaseResponse::error(400, "A variant is categorized with its parent");
+        }
+        (Some(_), attributes) => match attributes.as_ref().and_then(normalize_variant_attributes) {
+            Some(a) => Some(a),
+            None => {
+                return MyBaseResponse::error(
+                    400,
+                    "A variant needs attributes naming what sets it apart, e.g. {\"strength\": \"500mg\"}",
+                );
+            }
+        },
+    };
+    if let Some(parent_id) = payload.parent_id {
+        let parent = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (SELECT 1 FROM product_bundles b WHERE b.product_id = p.id) AS "is_bundle!"
+            FROM products p
+            WHERE p.id = $1
+            "#,
+            parent_id
+        )
+        .fetch_optional(&app.db)
+        .await;
+        match parent {
+            Ok(Some(false)) => {}
+            Ok(Some(true)) => return MyBaseResponse::error(400, "A bundle cannot have variants"),
+            Ok(None) => return MyBaseResponse::error(404, "Parent product not found"),
+            Err(e) => return MyBaseResponse::db_err(e),
+        }
+    }
+    if payload.components.is_some() || payload.bundle_pricing.is_some() {
+        if payload.parent_id.is_some() {
+            return MyBaseResponse::error(400, "A bundle cannot be a variant");
+        }
+        if payload.quantity != 0 {
+            return MyBaseResponse::error(400, "A bundle holds no stock; its components do");
+        }
+        let Some(components) = &payload.components else {
+            return MyBaseResponse::error(400, "A bundle needs its components");
+        };
+        if let Err(e) = check_bundle_components(&app.db, components).await {
+            return e.cast();
+        }
+    }
+
+    let mut tx = match app.db.begin().await {
+        Ok(t) => t,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
 
+    let location = match resolve_location(&mut *tx, payload.location_id).await {
+        Ok(l) => l,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return e.cast();
+        }
+    };
+
+    // The product starts empty and the opening stock arrives as a receipt at the location.
     let query_result = query_as!(
         ProductModel,
         r#"
-        INSERT INTO products (id, name, price, quantity, pack_price, created_at, updated_at)
-        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        INSERT INTO products (id, name, price, quantity, pack_price, units_per_pack, created_at, updated_at, reorder_level, reorder_quantity, sku, category_id, parent_id, variant_attributes)
+        VALUES ($1, $2, $3, 0, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
         RETURNING *
         "#,
         uuid::Uuid::new_v4(),
         payload.name,
         payload.price,
-        payload.quantity as i32,
         payload.pack_price,
+        payload.units_per_pack.unwrap_or(1),
         payload.created_at.unwrap_or_else(chrono::Utc::now),
         payload.updated_at.unwrap_or_else(chrono::Utc::now),
+        payload.reorder_level.unwrap_or(0),
+        payload.reorder_quantity.unwrap_or(0),
+        sku,
+        payload.category_id,
+        payload.parent_id,
+        variant_attributes,
     )
-    .fetch_one(&app.db)
+    .fetch_one(&mut *tx)
     .await;
-    match query_result {
-        Ok(p) => {
-            println!("Fetched products: {:?}", p);
-            return MyBaseResponse::ok(Some(p), Some("Product added successfully".into()));
-        }
+    let mut product = match query_result {
+        Ok(p) => p,
         Err(err) => {
             eprintln!("database query error: {}", err);
-            MyBaseResponse::db_err(err)
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(err);
+        }
+    };
+
+    if let Some(components) = &payload.components {
+        let pricing = payload.bundle_pricing.clone().unwrap_or_default();
+        let bundle = sqlx::query!(
+            r#"INSERT INTO product_bundles (product_id, pricing) VALUES ($1, $2)"#,
+            product.id,
+            pricing as BundlePricing,
+        )
+        .execute(&mut *tx)
+        .await;
+        if let Err(e) = bundle {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+        if let Err(e) = replace_bundle_components(&mut tx, product.id, components).await {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+        // A derived bundle has just been priced from its components.
+        let price = sqlx::query_scalar!(r#"SELECT price FROM products WHERE id = $1"#, product.id)
+            .fetch_one(&mut *tx)
+            .await;
+        match price {
+            Ok(p) => product.price = p,
+            Err(e) => {
+                let _ = tx.rollback().await;
+                return MyBaseResponse::db_err(e);
+            }
+        }
+    }
+
+    if payload.quantity != 0 {
+        let opening = NewStockMovement {
+            product_id: product.id,
+            location_id: location.id,
+            lot_id: None,
+            reason: StockMovementReason::Receipt,
+            quantity: payload.quantity as i32,
+            created_by: Some(auth.user.id),
+            reference_id: None,
+            adjustment_reason: None,
+            note: Some("Opening stock".into()),
+            unit_cost: payload.unit_cost,
+        };
+        match apply_costed_stock_movement(&mut tx, &opening).await {
+            Ok(Some(unit_cost)) => product.average_cost = unit_cost,
+            Ok(None) => {}
+            Err(e) => {
+                let _ = tx.rollback().await;
+                return MyBaseResponse::db_err(e);
+            }
         }
+        product.quantity = opening.quantity;
     }
+
+    if let Err(e) = tx.commit().await {
+        return MyBaseResponse::db_err(e);
+    }
+
+    println!("Fetched products: {:?}", product);
+    MyBaseResponse::ok(Some(product), Some("Product added successfully".into()))
 }
 
 #[utoipa::path(
@@ -137,62 +586,547 @@ pub async fn add_product_handler(
      security(("bearerAuth" = [])), 
 )]
 pub async fn update_product_handler(
+    auth: JWTAuthMiddleware,
     State(app_state): State<AppState>,
     Json(payload): Json<UpdateProductSchema>,
 ) -> MyBaseResponse<ProductModel> {
+    if payload.reorder_level.is_some_and(|l| l < 0)
+        || payload.reorder_quantity.is_some_and(|q| q < 0)
+    {
+        return MyBaseResponse::error(400, "Reorder level and quantity cannot be negative");
+    }
+    if payload.units_per_pack.is_some_and(|u| u <= 0) {
+        return MyBaseResponse::error(400, "Units per pack must be > 0");
+    }
+    let sku = payload.sku.as_deref().map(str::trim);
+    if sku.is_some_and(|s| !s.is_empty() && !is_valid_code(s)) {
+        return MyBaseResponse::error(
+            400,
+            format!("A SKU is 1 to {} characters without spaces", MAX_CODE_LEN),
+        );
+    }
+    if let Some(category_id) = payload.category_id.filter(|_| !payload.clear_category)
+        && let Err(e) = resolve_category(&app_state.db, category_id).await
+    {
+        return e.cast();
+    }
+
+    let mut tx = match app_state.db.begin().await {
+        Ok(t) => t,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
     let check_exists = query_as!(
         ProductModel,
         r#"
         SELECT *
         FROM products
         WHERE id = $1
+        FOR UPDATE
         "#,
         payload.id,
     )
-    .fetch_optional(&app_state.db)
+    .fetch_optional(&mut *tx)
     .await;
     if let Ok(Some(existing_product)) = check_exists {
+        let is_variant = existing_product.parent_id.is_some();
+        if is_variant && (payload.category_id.is_some() || payload.clear_category) {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::error(400, "A variant is categorized with its parent");
+        }
+        if payload.price.is_some() {
+            let derived = sqlx::query_scalar!(
+                r#"
+                SELECT EXISTS (
+                    SELECT 1 FROM product_bundles WHERE product_id = $1 AND pricing = 'derived'
+                ) AS "derived!"
+                "#,
+                existing_product.id,
+            )
+            .fetch_one(&mut *tx)
+            .await;
+            match derived {
+                Ok(false) => {}
+                Ok(true) => {
+                    let _ = tx.rollback().await;
+                    return MyBaseResponse::error(
+                        400,
+                        "A derived bundle is priced from its components",
+                    );
+                }
+                Err(e) => {
+                    let _ = tx.rollback().await;
+                    return MyBaseResponse::db_err(e);
+                }
+            }
+        }
+        let variant_attributes = match &payload.variant_attributes {
+            None => existing_product.variant_attributes,
+            Some(_) if !is_variant => {
+                let _ = tx.rollback().await;
+                return MyBaseResponse::error(400, "Only a variant has variant attributes");
+            }
+            Some(attributes) => match normalize_variant_attributes(attributes) {
+                Some(a) => Some(a),
+                None => {
+                    let _ = tx.rollback().await;
+                    return MyBaseResponse::error(
+                        400,
+                        "Variant attributes map names to non-empty text values",
+                    );
+                }
+            },
+        };
+
+        // Stock only changes through the ledger; a direct overwrite is an audited correction.
+        if let Some(quantity) = payload.quantity {
+            let location = match resolve_location(&mut *tx, payload.location_id).await {
+                Ok(l) => l,
+                Err(e) => {
+                    let _ = tx.rollback().await;
+                    return e.cast();
+                }
+            };
+            let on_hand = sqlx::query_scalar!(
+                r#"SELECT quantity FROM product_stock WHERE product_id = $1 AND location_id = $2"#,
+                existing_product.id,
+                location.id,
+            )
+            .fetch_optional(&mut *tx)
+            .await;
+            let on_hand = match on_hand {
+                Ok(q) => q.unwrap_or(0),
+                Err(e) => {
+                    let _ = tx.rollback().await;
+                    return MyBaseResponse::db_err(e);
+                }
+            };
+            let delta = quantity - on_hand;
+            if delta != 0 {
+                if auth.user.role != UserRole::Admin {
+                    let _ = tx.rollback().await;
+                    return MyBaseResponse::error(403, "Only admins can set quantity directly");
+                }
+                let reason = match payload.quantity_reason.as_deref().map(str::trim) {
+                    Some(r) if !r.is_empty() => r.to_string(),
+                    _ => {
+                        let _ = tx.rollback().await;
+                        return MyBaseResponse::error(
+                            400,
+                            "A quantity_reason is required to change quantity directly",
+                        );
+                    }
+                };
+                let adjustment = NewStockMovement {
+                    product_id: existing_product.id,
+                    location_id: location.id,
+                    lot_id: None,
+                    reason: StockMovementReason::Adjustment,
+                    quantity: delta,
+                    created_by: Some(auth.user.id),
+                    reference_id: None,
+                    adjustment_reason: Some(StockAdjustmentReason::Correction),
+                    note: Some(reason),
+                    unit_cost: None,
+                };
+                match apply_stock_movement(&mut tx, &adjustment).await {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        let _ = tx.rollback().await;
+                        return MyBaseResponse::error(
+                            400,
+                            "Stock cannot go below zero; stock held in lots is adjusted by lot_id",
+                        );
+                    }
+                    Err(e) => {
+                        let _ = tx.rollback().await;
+                        return MyBaseResponse::db_err(e);
+                    }
+                }
+            }
+        }
+
         let updated_prod = ProductModel {
             id: existing_product.id,
             name: payload.name.clone().unwrap_or(existing_product.name),
             price: payload.price.unwrap_or(existing_product.price),
-            quantity: payload.quantity.unwrap_or(existing_product.quantity),
+            quantity: existing_product.quantity,
             pack_price: payload.pack_price.or(existing_product.pack_price),
+            units_per_pack: payload.units_per_pack.unwrap_or(existing_product.units_per_pack),
+            average_cost: existing_product.average_cost,
             created_at: existing_product.created_at,
             updated_at: Some(chrono::Utc::now()),
+            reorder_level: payload.reorder_level.unwrap_or(existing_product.reorder_level),
+            reorder_quantity: payload
+                .reorder_quantity
+                .unwrap_or(existing_product.reorder_quantity),
+            sku: match sku {
+                None => existing_product.sku,
+                Some("") => None,
+                Some(s) => Some(s.to_string()),
+            },
+            category_id: if payload.clear_category {
+                None
+            } else {
+                payload.category_id.or(existing_product.category_id)
+            },
+            parent_id: existing_product.parent_id,
+            variant_attributes,
         };
         let query_result = query_as!(
             ProductModel,
             r#"
             UPDATE products
-            SET name = $1, price = $2, quantity = $3, pack_price = $4, updated_at = $5
-            WHERE id = $6
+            SET name = $1, price = $2, pack_price = $3, units_per_pack = $4, updated_at = $5,
+                reorder_level = $6, reorder_quantity = $7, sku = $8, category_id = $9,
+                variant_attributes = $10
+            WHERE id = $11
             RETURNING *
             "#,
             updated_prod.name,
             updated_prod.price,
-            updated_prod.quantity,
             updated_prod.pack_price,
+            updated_prod.units_per_pack,
             updated_prod.updated_at,
+            updated_prod.reorder_level,
+            updated_prod.reorder_quantity,
+            updated_prod.sku,
+            updated_prod.category_id,
+            updated_prod.variant_attributes,
             updated_prod.id,
         )
-        .fetch_one(&app_state.db)
+        .fetch_one(&mut *tx)
         .await;
         match query_result {
             Ok(p) => {
+                if let Err(e) = tx.commit().await {
+                    return MyBaseResponse::db_err(e);
+                }
                 println!("Fetched products: {:?}", p);
                 return MyBaseResponse::ok(Some(p), Some("Product updated successfully".into()));
             }
             Err(err) => {
                 eprintln!("database query error: {}", err);
+                let _ = tx.rollback().await;
                 return MyBaseResponse::db_err(err);
             }
         }
     }
 
+    let _ = tx.rollback().await;
     return MyBaseResponse::error(409, "Product not found!");
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/products/adjust-stock",
+    tag = "Products",
+    request_body = AdjustStockSchema,
+    responses(
+        (status = 200, description = "Stock adjusted successfully", body = MyBaseResponse<ProductModel>),
+        (status = 400, description = "Delta does not fit the reason or would take stock below zero", body = MyBaseResponse<ProductModel>),
+        (status = 404, description = "Product, lot or location not found", body = MyBaseResponse<ProductModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn adjust_stock_handler(
+    auth: JWTAuthMiddleware,
+    Json(payload): Json<AdjustStockSchema>,
+    State(app_state): State<AppState>,
+) -> MyBaseResponse<ProductModel> {
+    if !payload.reason.allows(payload.delta) {
+        let expected = match payload.reason {
+            StockAdjustmentReason::FoundStock => "a positive",
+            StockAdjustmentReason::Correction => "a non-zero",
+            _ => "a negative",
+        };
+        return MyBaseResponse::error(
+            400,
+            format!("A {} adjustment needs {} delta", payload.reason, expected),
+        );
+    }
+
+    let mut tx = match app_state.db.begin().await {
+        Ok(t) => t,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let exists = sqlx::query_scalar!(
+        r#"SELECT id FROM products WHERE id = $1 FOR UPDATE"#,
+        payload.product_id
+    )
+    .fetch_optional(&mut *tx)
+    .await;
+    match exists {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::error(404, "Product not found");
+        }
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    }
+
+    let location_id = match (payload.location_id, payload.lot_id) {
+        (None, Some(lot_id)) => {
+            let lot_location = sqlx::query_scalar!(
+                r#"SELECT location_id FROM product_lots WHERE id = $1 AND product_id = $2"#,
+                lot_id,
+                payload.product_id,
+            )
+            .fetch_optional(&mut *tx)
+            .await;
+            match lot_location {
+                Ok(Some(id)) => id,
+                Ok(None) => {
+                    let _ = tx.rollback().await;
+                    return MyBaseResponse::error(404, "Lot not found for this product");
+                }
+                Err(e) => {
+                    let _ = tx.rollback().await;
+                    return MyBaseResponse::db_err(e);
+                }
+            }
+        }
+        (location_id, _) => match resolve_location(&mut *tx, location_id).await {
+            Ok(l) => l.id,
+            Err(e) => {
+                let _ = tx.rollback().await;
+                return e.cast();
+            }
+        },
+    };
+
+    let adjustment = NewStockMovement {
+        product_id: payload.product_id,
+        location_id,
+        lot_id: payload.lot_id,
+        reason: StockMovementReason::Adjustment,
+        quantity: payload.delta,
+        created_by: Some(auth.user.id),
+        reference_id: None,
+        adjustment_reason: Some(payload.reason.clone()),
+        note: payload.note.clone(),
+        unit_cost: None,
+    };
+    match apply_stock_movement(&mut tx, &adjustment).await {
+        Ok(true) => {}
+        Ok(false) => {
+            let _ = tx.rollback().await;
+            let message = match payload.lot_id {
+                Some(_) => {
+                    "Lot not found for this product at this location, or its stock cannot go below zero"
+                }
+                None => {
+                    "Stock at this location cannot go below zero; stock held in lots is adjusted by lot_id"
+                }
+            };
+            return MyBaseResponse::error(400, message);
+        }
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    }
+
+    let product = query_as!(
+        ProductModel,
+        r#"SELECT * FROM products WHERE id = $1"#,
+        payload.product_id
+    )
+    .fetch_one(&mut *tx)
+    .await;
+    let product = match product {
+        Ok(p) => p,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+
+    if let Err(e) = tx.commit().await {
+        return MyBaseResponse::db_err(e);
+    }
+
+    MyBaseResponse::ok(Some(product), Some("Stock adjusted successfully".into()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/products/lots/add",
+    tag = "Products",
+    request_body = AddProductLotSchema,
+    responses(
+        (status = 200, description = "Lot received successfully", body = MyBaseResponse<ProductLotModel>),
+        (status = 400, description = "Invalid lot", body = MyBaseResponse<ProductLotModel>),
+        (status = 404, description = "Location not found", body = MyBaseResponse<ProductLotModel>),
+        (status = 409, description = "Lot number already exists for the product at this location", body = MyBaseResponse<ProductLotModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn add_product_lot_handler(
+    auth: JWTAuthMiddleware,
+    Json(payload): Json<AddProductLotSchema>,
+    State(app_state): State<AppState>,
+) -> MyBaseResponse<ProductLotModel> {
+    let lot_number = payload.lot_number.trim();
+    if lot_number.is_empty() {
+        return MyBaseResponse::error(400, "Lot number is required");
+    }
+    if payload.quantity <= 0 {
+        return MyBaseResponse::error(400, "Quantity must be > 0");
+    }
+    if payload.expiry_date <= chrono::Utc::now().date_naive() {
+        return MyBaseResponse::error(400, "Lot has already expired");
+    }
+
+    let mut tx = match app_state.db.begin().await {
+        Ok(t) => t,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let location = match resolve_location(&mut *tx, payload.location_id).await {
+        Ok(l) => l,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return e.cast();
+        }
+    };
+
+    // The lot starts empty and is filled by the receipt, so the ledger sees the stock arrive.
+    let lot_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO product_lots (product_id, location_id, lot_number, expiry_date, unit_cost)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id
+        "#,
+        payload.product_id,
+        location.id,
+        lot_number,
+        payload.expiry_date,
+        payload.unit_cost.map(round_money),
+    )
+    .fetch_one(&mut *tx)
+    .await;
+    let lot_id = match lot_id {
+        Ok(id) => id,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+
+    let receipt = NewStockMovement {
+        product_id: payload.product_id,
+        location_id: location.id,
+        lot_id: Some(lot_id),
+        reason: StockMovementReason::Receipt,
+        quantity: payload.quantity,
+        created_by: Some(auth.user.id),
+        reference_id: None,
+        adjustment_reason: None,
+        note: None,
+        unit_cost: payload.unit_cost,
+    };
+    if let Err(e) = apply_stock_movement(&mut tx, &receipt).await {
+        let _ = tx.rollback().await;
+        return MyBaseResponse::db_err(e);
+    }
+
+    let lot = query_as!(
+        ProductLotModel,
+        r#"
+        SELECT id, product_id, location_id, lot_number, expiry_date, quantity, unit_cost,
+               expiry_date <= CURRENT_DATE AS "expired!", created_at
+        FROM product_lots
+        WHERE id = $1
+        "#,
+        lot_id,
+    )
+    .fetch_one(&mut *tx)
+    .await;
+    let lot = match lot {
+        Ok(l) => l,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+
+    if let Err(e) = tx.commit().await {
+        return MyBaseResponse::db_err(e);
+    }
+
+    MyBaseResponse::ok(Some(lot), Some("Lot received successfully".into()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/products/lots/get",
+    tag = "Products",
+    params(
+        GetProductLotsSchema
+    ),
+    responses(
+        (status = 200, description = "Lots retrieved successfully", body = MyBaseResponse<ProductLotsModel>),
+        (status = 404, description = "Product not found", body = MyBaseResponse<ProductLotsModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn get_product_lots_handler(
+    Query(params): Query<GetProductLotsSchema>,
+    State(app_state): State<AppState>,
+) -> MyBaseResponse<ProductLotsModel> {
+    let product = query_as!(
+        ProductModel,
+        r#"SELECT * FROM products WHERE id = $1"#,
+        params.product_id
+    )
+    .fetch_optional(&app_state.db)
+    .await;
+    let product = match product {
+        Ok(Some(p)) => p,
+        Ok(None) => return MyBaseResponse::error(404, "Product not found"),
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let lots = query_as!(
+        ProductLotModel,
+        r#"
+        SELECT id, product_id, location_id, lot_number, expiry_date, quantity, unit_cost,
+               expiry_date <= CURRENT_DATE AS "expired!", created_at
+        FROM product_lots
+        WHERE product_id = $1
+        ORDER BY expiry_date, lot_number, location_id
+        "#,
+        params.product_id,
+    )
+    .fetch_all(&app_state.db)
+    .await;
+    let lots = match lots {
+        Ok(l) => l,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let unlotted_quantity = product.quantity - lots.iter().map(|l| l.quantity).sum::<i32>();
+    let sellable_quantity = unlotted_quantity
+        + lots
+            .iter()
+            .filter(|l| !l.expired)
+            .map(|l| l.quantity)
+            .sum::<i32>();
+    let summary = ProductLotsModel {
+        product_id: product.id,
+        quantity: product.quantity,
+        sellable_quantity,
+        unlotted_quantity,
+        lots,
+    };
+    MyBaseResponse::ok(Some(summary), Some("Lots retrieved successfully".into()))
+}
+
 #[utoipa::path(
     delete,
     path = "/products/delete", 
@@ -233,3 +1167,616 @@ pub async fn del_product_handler(
         }
     }
 }
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/products/lookup",
+    tag = "Products",
+    params(
+        ProductLookupQuery
+    ),
+    responses(
+        (status = 200, description = "Product and sell unit for the scanned code", body = MyBaseResponse<ProductLookupModel>),
+        (status = 400, description = "Empty code", body = MyBaseResponse<ProductLookupModel>),
+        (status = 404, description = "No product has this code", body = MyBaseResponse<ProductLookupModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn lookup_product_handler(
+    Query(params): Query<ProductLookupQuery>,
+    State(app_state): State<AppState>,
+) -> MyBaseResponse<ProductLookupModel> {
+    let code = params.code.trim();
+    if code.is_empty() {
+        return MyBaseResponse::error(400, "Code is required");
+    }
+
+    // Barcodes win over SKUs; a SKU always sells a single unit.
+    let barcode = query_as!(
+        ProductBarcodeModel,
+        r#"
+        SELECT id, product_id, code, barcode_type AS "barcode_type: BarcodeType",
+               sell_unit AS "sell_unit: SellUnit", created_at
+        FROM product_barcodes
+        WHERE code = ANY($1)
+        LIMIT 1
+        "#,
+        &scan_variants(code),
+    )
+    .fetch_optional(&app_state.db)
+    .await;
+    let barcode = match barcode {
+        Ok(b) => b,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+    let product = match &barcode {
+        Some(b) => {
+            query_as!(ProductModel, r#"SELECT * FROM products WHERE id = $1"#, b.product_id)
+                .fetch_optional(&app_state.db)
+                .await
+        }
+        None => {
+            query_as!(
+                ProductModel,
+                r#"SELECT * FROM products WHERE lower(sku) = lower($1)"#,
+                code
+            )
+            .fetch_optional(&app_state.db)
+            .await
+        }
+    };
+    let product = match product {
+        Ok(Some(p)) => p,
+        Ok(None) => return MyBaseResponse::error(404, "No product has this code"),
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+    let (code, barcode_type, sell_unit) = match barcode {
+        Some(b) => (b.code, Some(b.barcode_type), b.sell_unit),
+        None => (product.sku.clone().unwrap_or_default(), None, SellUnit::Unit),
+    };
+
+    let Some(unit_price) = product.list_price(&sell_unit) else {
+        return MyBaseResponse::error(
+            409,
+            format!("{} has a pack barcode but no pack price", product.name),
+        );
+    };
+    let lookup = ProductLookupModel {
+        code,
+        barcode_type,
+        units_per_sell_unit: product.units_per(&sell_unit),
+        sell_unit,
+        unit_price,
+        product,
+    };
+    MyBaseResponse::ok(Some(lookup), Some("Product found".into()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/products/barcodes/get",
+    tag = "Products",
+    params(
+        GetProductBarcodesSchema
+    ),
+    responses(
+        (status = 200, description = "Barcodes retrieved successfully", body = MyBaseResponse<Vec<ProductBarcodeModel>>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn get_product_barcodes_handler(
+    Query(params): Query<GetProductBarcodesSchema>,
+    State(app_state): State<AppState>,
+) -> MyBaseResponse<Vec<ProductBarcodeModel>> {
+    let barcodes = query_as!(
+        ProductBarcodeModel,
+        r#"
+        SELECT id, product_id, code, barcode_type AS "barcode_type: BarcodeType",
+               sell_unit AS "sell_unit: SellUnit", created_at
+        FROM product_barcodes
+        WHERE product_id = $1
+        ORDER BY id
+        "#,
+        params.product_id,
+    )
+    .fetch_all(&app_state.db)
+    .await;
+    match barcodes {
+        Ok(b) => MyBaseResponse::ok(Some(b), Some("Barcodes retrieved successfully".into())),
+        Err(e) => MyBaseResponse::db_err(e),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/products/barcodes/add",
+    tag = "Products",
+    request_body = AddProductBarcodeSchema,
+    responses(
+        (status = 200, description = "Barcode added successfully", body = MyBaseResponse<ProductBarcodeModel>),
+        (status = 400, description = "Invalid code, or a pack code for a product without a pack price", body = MyBaseResponse<ProductBarcodeModel>),
+        (status = 404, description = "Product not found", body = MyBaseResponse<ProductBarcodeModel>),
+        (status = 409, description = "Code is already in use", body = MyBaseResponse<ProductBarcodeModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn add_product_barcode_handler(
+    Json(payload): Json<AddProductBarcodeSchema>,
+    State(app_state): State<AppState>,
+) -> MyBaseResponse<ProductBarcodeModel> {
+    let code = payload.code.trim();
+    if let Err(message) = payload.barcode_type.validate(code) {
+        return MyBaseResponse::error(400, message);
+    }
+
+    let product = query_as!(
+        ProductModel,
+        r#"SELECT * FROM products WHERE id = $1"#,
+        payload.product_id
+    )
+    .fetch_optional(&app_state.db)
+    .await;
+    let product = match product {
+        Ok(Some(p)) => p,
+        Ok(None) => return MyBaseResponse::error(404, "Product not found"),
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+    if product.list_price(&payload.sell_unit).is_none() {
+        return MyBaseResponse::error(
+            400,
+            format!("{} has no pack price to sell a pack at", product.name),
+        );
+    }
+
+    // A UPC-A code and its EAN-13 form scan the same, so neither may be taken.
+    let taken = sqlx::query_scalar!(
+        r#"SELECT code FROM product_barcodes WHERE code = ANY($1) LIMIT 1"#,
+        &scan_variants(code),
+    )
+    .fetch_optional(&app_state.db)
+    .await;
+    match taken {
+        Ok(Some(existing)) => {
+            return MyBaseResponse::error(409, format!("Code {} is already in use", existing));
+        }
+        Ok(None) => {}
+        Err(e) => return MyBaseResponse::db_err(e),
+    }
+
+    let barcode = query_as!(
+        ProductBarcodeModel,
+        r#"
+        INSERT INTO product_barcodes (product_id, code, barcode_type, sell_unit)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, product_id, code, barcode_type AS "barcode_type: BarcodeType",
+                  sell_unit AS "sell_unit: SellUnit", created_at
+        "#,
+        payload.product_id,
+        code,
+        payload.barcode_type as BarcodeType,
+        payload.sell_unit as SellUnit,
+    )
+    .fetch_one(&app_state.db)
+    .await;
+    match barcode {
+        Ok(b) => MyBaseResponse::ok(Some(b), Some("Barcode added successfully".into())),
+        // Another request stored the code, or its other form, after the check above.
+        Err(sqlx::Error::Database(db)) if db.code().as_deref() == Some("23505") => {
+            MyBaseResponse::error(409, format!("Code {} is already in use", code))
+        }
+        Err(e) => MyBaseResponse::db_err(e),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/products/barcodes/delete",
+    tag = "Products",
+    request_body = DeleteProductBarcodeSchema,
+    responses(
+        (status = 200, description = "Barcode deleted successfully", body = MyBaseResponse<ProductBarcodeModel>),
+        (status = 404, description = "Barcode not found", body = MyBaseResponse<ProductBarcodeModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn delete_product_barcode_handler(
+    Json(payload): Json<DeleteProductBarcodeSchema>,
+    State(app_state): State<AppState>,
+) -> MyBaseResponse<ProductBarcodeModel> {
+    let barcode = query_as!(
+        ProductBarcodeModel,
+        r#"
+        DELETE FROM product_barcodes
+        WHERE id = $1
+        RETURNING id, product_id, code, barcode_type AS "barcode_type: BarcodeType",
+                  sell_unit AS "sell_unit: SellUnit", created_at
+        "#,
+        payload.id,
+    )
+    .fetch_optional(&app_state.db)
+    .await;
+    match barcode {
+        Ok(Some(b)) => MyBaseResponse::ok(Some(b), Some("Barcode deleted successfully".into())),
+        Ok(None) => MyBaseResponse::error(404, "Barcode not found"),
+        Err(e) => MyBaseResponse::db_err(e),
+    }
+}
+
+/// Checks a bundle's components: at least one, each listed once with a
+/// positive quantity, and each a product that holds stock of its own.
+async fn check_bundle_components<'e, E: PgExecutor<'e>>(
+    executor: E,
+    components: &[BundleComponentSchema],
+) -> Result<(), MyBaseResponse<()>> {
+    if components.is_empty() {
+        return Err(MyBaseResponse::error(400, "A bundle needs at least one component"));
+    }
+    let mut ids = Vec::with_capacity(components.len());
+    for component in components {
+        if component.quantity <= 0 {
+            return Err(MyBaseResponse::error(400, "Component quantity must be > 0"));
+        }
+        if ids.contains(&component.product_id) {
+            return Err(MyBaseResponse::error(
+                400,
+                format!("Component {} is listed more than once", component.product_id),
+            ));
+        }
+        ids.push(component.product_id);
+    }
+
+    let found = sqlx::query!(
+        r#"
+        SELECT p.id, p.name,
+               EXISTS (SELECT 1 FROM product_bundles b WHERE b.product_id = p.id) AS "is_bundle!",
+               EXISTS (SELECT 1 FROM products v WHERE v.parent_id = p.id) AS "has_variants!"
+        FROM products p
+        WHERE p.id = ANY($1)
+        "#,
+        &ids,
+    )
+    .fetch_all(executor)
+    .await
+    .map_err(MyBaseResponse::db_err)?;
+
+    for id in &ids {
+        let Some(product) = found.iter().find(|p| p.id == *id) else {
+            return Err(MyBaseResponse::error(404, format!("Component {} not found", id)));
+        };
+        if product.is_bundle {
+            return Err(MyBaseResponse::error(
+                400,
+                format!("{} is a bundle and cannot be a component", product.name),
+            ));
+        }
+        if product.has_variants {
+            return Err(MyBaseResponse::error(
+                400,
+                format!("{} comes in variants; use a variant as the component", product.name),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Sets a bundle's components to exactly `components`.
+async fn replace_bundle_components(
+    tx: &mut Transaction<'_, Postgres>,
+    bundle_id: uuid::Uuid,
+    components: &[BundleComponentSchema],
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(r#"DELETE FROM bundle_components WHERE bundle_id = $1"#, bundle_id)
+        .execute(&mut **tx)
+        .await?;
+    let ids: Vec<uuid::Uuid> = components.iter().map(|c| c.product_id).collect();
+    let quantities: Vec<i32> = components.iter().map(|c| c.quantity).collect();
+    sqlx::query!(
+        r#"
+        INSERT INTO bundle_components (bundle_id, component_id, quantity)
+        SELECT $1, c.component_id, c.quantity
+        FROM UNNEST($2::UUID[], $3::INT[]) AS c(component_id, quantity)
+        "#,
+        bundle_id,
+        &ids,
+        &quantities,
+    )
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Loads a bundle with its components' stock at `location_id`, or in total.
+async fn fetch_bundle(
+    db: &PgPool,
+    product_id: uuid::Uuid,
+    location_id: Option<uuid::Uuid>,
+) -> Result<BundleModel, MyBaseResponse<()>> {
+    let pricing = sqlx::query_scalar!(
+        r#"SELECT pricing AS "pricing: BundlePricing" FROM product_bundles WHERE product_id = $1"#,
+        product_id,
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(MyBaseResponse::db_err)?
+    .ok_or_else(|| MyBaseResponse::error(404, "Bundle not found"))?;
+
+    let product = query_as!(
+        ProductModel,
+        r#"SELECT * FROM products WHERE id = $1"#,
+        product_id
+    )
+    .fetch_one(db)
+    .await
+    .map_err(MyBaseResponse::db_err)?;
+
+    let components = query_as!(
+        BundleComponentModel,
+        r#"
+        SELECT c.id AS product_id, c.name, bc.quantity, c.price AS unit_price,
+               CASE
+                 WHEN $2::UUID IS NULL THEN c.quantity
+                 ELSE COALESCE(
+                   (SELECT s.quantity FROM product_stock s
+                    WHERE s.product_id = c.id AND s.location_id = $2), 0)
+               END AS "in_stock!"
+        FROM bundle_components bc
+        JOIN products c ON c.id = bc.component_id
+        WHERE bc.bundle_id = $1
+        ORDER BY lower(c.name)
+        "#,
+        product_id,
+        location_id,
+    )
+    .fetch_all(db)
+    .await
+    .map_err(MyBaseResponse::db_err)?;
+
+    Ok(BundleModel::new(product, pricing, components))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/products/bundles/get",
+    tag = "Products",
+    params(
+        GetBundleSchema
+    ),
+    responses(
+        (status = 200, description = "Bundle retrieved successfully", body = MyBaseResponse<BundleModel>),
+        (status = 404, description = "Bundle or location not found", body = MyBaseResponse<BundleModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn get_bundle_handler(
+    Query(params): Query<GetBundleSchema>,
+    State(app_state): State<AppState>,
+) -> MyBaseResponse<BundleModel> {
+    if let Some(location_id) = params.location_id
+        && let Err(e) = resolve_location(&app_state.db, Some(location_id)).await
+    {
+        return e.cast();
+    }
+    match fetch_bundle(&app_state.db, params.product_id, params.location_id).await {
+        Ok(b) => MyBaseResponse::ok(Some(b), Some("Bundle retrieved successfully".into())),
+        Err(e) => e.cast(),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/products/bundles/update",
+    tag = "Products",
+    request_body = UpdateBundleSchema,
+    responses(
+        (status = 200, description = "Bundle updated successfully", body = MyBaseResponse<BundleModel>),
+        (status = 400, description = "Invalid components", body = MyBaseResponse<BundleModel>),
+        (status = 404, description = "Bundle or component not found", body = MyBaseResponse<BundleModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn update_bundle_handler(
+    Json(payload): Json<UpdateBundleSchema>,
+    State(app_state): State<AppState>,
+) -> MyBaseResponse<BundleModel> {
+    if let Some(components) = &payload.components
+        && let Err(e) = check_bundle_components(&app_state.db, components).await
+    {
+        return e.cast();
+    }
+
+    let mut tx = match app_state.db.begin().await {
+        Ok(t) => t,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let bundle = sqlx::query!(
+        r#"
+        UPDATE product_bundles
+        SET pricing = COALESCE($2, pricing)
+        WHERE product_id = $1
+        RETURNING product_id
+        "#,
+        payload.product_id,
+        payload.pricing.clone() as Option<BundlePricing>,
+    )
+    .fetch_optional(&mut *tx)
+    .await;
+    match bundle {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::error(404, "Bundle not found");
+        }
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    }
+
+    if let Some(components) = &payload.components
+        && let Err(e) = replace_bundle_components(&mut tx, payload.product_id, components).await
+    {
+        let _ = tx.rollback().await;
+        return MyBaseResponse::db_err(e);
+    }
+
+    if let Err(e) = tx.commit().await {
+        return MyBaseResponse::db_err(e);
+    }
+
+    match fetch_bundle(&app_state.db, payload.product_id, None).await {
+        Ok(b) => MyBaseResponse::ok(Some(b), Some("Bundle updated successfully".into())),
+        Err(e) => e.cast(),
+    }
+}
+
+/// Most suggestions one autocomplete request returns.
+const MAX_SUGGESTIONS: i64 = 20;
+
+/// Most labels one request may print.
+const MAX_LABELS: usize = 2000;
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/products/labels",
+    tag = "Products",
+    request_body = PrintLabelsSchema,
+    responses(
+        (status = 200, description = "Labels as an A4 PDF or ZPL for a thermal printer", content(
+            (Vec<u8> = "application/pdf"),
+            (String = "text/plain"),
+        )),
+        (status = 400, description = "No products, too many labels, or no pack price", body = MyBaseResponse<String>),
+        (status = 404, description = "Product not found", body = MyBaseResponse<String>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn print_labels_handler(
+    Json(payload): Json<PrintLabelsSchema>,
+    State(app_state): State<AppState>,
+) -> Response {
+    let copies = payload.copies.unwrap_or(1) as usize;
+    if payload.product_ids.is_empty() {
+        return MyBaseResponse::<()>::error(400, "List at least one product").into_response();
+    }
+    if copies == 0 || payload.product_ids.len() * copies > MAX_LABELS {
+        return MyBaseResponse::<()>::error(
+            400,
+            format!("Print between 1 and {} labels at a time", MAX_LABELS),
+        )
+        .into_response();
+    }
+
+    let products = query_as!(
+        ProductModel,
+        r#"SELECT * FROM products WHERE id = ANY($1)"#,
+        &payload.product_ids
+    )
+    .fetch_all(&app_state.db)
+    .await;
+    let products: HashMap<uuid::Uuid, ProductModel> = match products {
+        Ok(p) => p.into_iter().map(|p| (p.id, p)).collect(),
+        Err(e) => return MyBaseResponse::<()>::db_err(e).into_response(),
+    };
+
+    // Retail barcodes print in preference to internal codes.
+    let barcodes = query_as!(
+        ProductBarcodeModel,
+        r#"
+        SELECT id, product_id, code, barcode_type AS "barcode_type: BarcodeType",
+               sell_unit AS "sell_unit: SellUnit", created_at
+        FROM product_barcodes
+        WHERE product_id = ANY($1) AND sell_unit = $2
+        ORDER BY barcode_type = 'internal', id
+        "#,
+        &payload.product_ids,
+        payload.sell_unit.clone() as SellUnit,
+    )
+    .fetch_all(&app_state.db)
+    .await;
+    let mut barcode_by_product: HashMap<uuid::Uuid, ProductBarcodeModel> = HashMap::new();
+    match barcodes {
+        Ok(b) => {
+            for barcode in b {
+                barcode_by_product.entry(barcode.product_id).or_insert(barcode);
+            }
+        }
+        Err(e) => return MyBaseResponse::<()>::db_err(e).into_response(),
+    }
+
+    let mut labels = Vec::with_capacity(payload.product_ids.len() * copies);
+    for id in &payload.product_ids {
+        let Some(product) = products.get(id) else {
+            return MyBaseResponse::<()>::error(404, format!("Product {} not found", id))
+                .into_response();
+        };
+        let Some(price) = product.list_price(&payload.sell_unit) else {
+            return MyBaseResponse::<()>::error(
+                400,
+                format!("{} has no pack price", product.name),
+            )
+            .into_response();
+        };
+        let detail = match payload.sell_unit {
+            SellUnit::Unit => product
+                .pack_price
+                .map(|p| format!("Pack of {}: {:.2}", product.units_per_pack, p)),
+            SellUnit::Pack => Some(format!(
+                "Pack of {}, {:.2} each",
+                product.units_per_pack,
+                round_money(price / Money::from(product.units_per_pack)),
+            )),
+        };
+        // A SKU scans as a single unit, so only unit labels fall back to it.
+        let barcode = match barcode_by_product.get(id) {
+            Some(b) => Some(LabelBarcode {
+                code: b.code.clone(),
+                symbology: match b.barcode_type {
+                    BarcodeType::Ean13 => Symbology::Ean13,
+                    BarcodeType::UpcA => Symbology::UpcA,
+                    BarcodeType::Internal => Symbology::Code128,
+                },
+            }),
+            None if payload.sell_unit == SellUnit::Unit => product
+                .sku
+                .as_ref()
+                .filter(|sku| is_valid_code(sku))
+                .map(|sku| LabelBarcode {
+                    code: sku.clone(),
+                    symbology: Symbology::Code128,
+                }),
+            None => None,
+        };
+        let label = Label {
+            name: product.name.clone(),
+            price,
+            detail,
+            barcode,
+        };
+        labels.extend(std::iter::repeat_n(label, copies));
+    }
+
+    let (body, content_type, file_name) = match payload.format {
+        LabelFormat::Pdf => (
+            render_pdf(&payload.template, &labels),
+            "application/pdf",
+            "labels.pdf",
+        ),
+        LabelFormat::Zpl => (
+            render_zpl(&payload.template, &labels).into_bytes(),
+            "text/plain; charset=utf-8",
+            "labels.zpl",
+        ),
+    };
+    (
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", file_name),
+            ),
+        ],
+        body,
+    )
+        .into_response()
+}
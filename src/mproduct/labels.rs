@@ -0,0 +1,434 @@
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str};
+
+use crate::mproduct::models::LabelTemplate;
+use crate::util::money::Money;
+
+const PT_PER_MM: f32 = 72.0 / 25.4;
+/// Thermal printers print at 203 dpi.
+const DOTS_PER_MM: f32 = 8.0;
+/// Sheets are A4, in millimetres.
+const SHEET_WIDTH: f32 = 210.0;
+const SHEET_HEIGHT: f32 = 297.0;
+/// Blank modules either side of a barcode so scanners find its edges.
+const QUIET_ZONE: usize = 10;
+/// Widest module drawn, the nominal EAN-13 size.
+const MAX_MODULE_MM: f32 = 0.33;
+/// Rough Helvetica advance per character, in ems; digits are exactly this wide.
+const CHAR_WIDTH_EM: f32 = 0.556;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Symbology {
+    Ean13,
+    UpcA,
+    Code128,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelBarcode {
+    pub code: String,
+    pub symbology: Symbology,
+}
+
+/// What goes on one label.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub name: String,
+    pub price: Money,
+    /// A smaller line under the price, e.g. the pack price.
+    pub detail: Option<String>,
+    pub barcode: Option<LabelBarcode>,
+}
+
+/// Label size and, for sheets, where the labels sit on the page. Sizes are in
+/// millimetres and type sizes in points.
+struct Layout {
+    width: f32,
+    height: f32,
+    columns: usize,
+    rows: usize,
+    left: f32,
+    top: f32,
+    pitch_x: f32,
+    pitch_y: f32,
+    padding: f32,
+    name_size: f32,
+    price_size: f32,
+    detail_size: f32,
+    barcode_height: f32,
+}
+
+fn layout(template: &LabelTemplate) -> Layout {
+    match template {
+        // 24 labels of 70 x 37 mm to an A4 sheet.
+        LabelTemplate::Shelf => Layout {
+            width: 70.0,
+            height: 37.0,
+            columns: 3,
+            rows: 8,
+            left: 0.0,
+            top: 0.5,
+            pitch_x: 70.0,
+            pitch_y: 37.0,
+            padding: 2.0,
+            name_size: 10.0,
+            price_size: 20.0,
+            detail_size: 8.0,
+            barcode_height: 10.0,
+        },
+        // 65 stickers of 38.1 x 21.2 mm to an A4 sheet.
+        LabelTemplate::Sticker => Layout {
+            width: 38.1,
+            height: 21.2,
+            columns: 5,
+            rows: 13,
+            left: 4.75,
+            top: 10.7,
+            pitch_x: 40.6,
+            pitch_y: 21.2,
+            padding: 1.5,
+            name_size: 6.0,
+            price_size: 10.0,
+            detail_size: 5.0,
+            barcode_height: 6.0,
+        },
+    }
+}
+
+/// Renders labels onto A4 sheets, filling each sheet row by row.
+pub fn render_pdf(template: &LabelTemplate, labels: &[Label]) -> Vec<u8> {
+    let layout = layout(template);
+    let catalog_id = Ref::new(1);
+    let tree_id = Ref::new(2);
+    let font_id = Ref::new(3);
+    let bold_id = Ref::new(4);
+    let mut next_id = 5;
+
+    let mut pdf = Pdf::new();
+    let mut page_ids = Vec::new();
+    for sheet in labels.chunks(layout.columns * layout.rows) {
+        let page_id = Ref::new(next_id);
+        let content_id = Ref::new(next_id + 1);
+        next_id += 2;
+        page_ids.push(page_id);
+
+        let mut page = pdf.page(page_id);
+        page.media_box(Rect::new(
+            0.0,
+            0.0,
+            SHEET_WIDTH * PT_PER_MM,
+            SHEET_HEIGHT * PT_PER_MM,
+        ));
+        page.parent(tree_id);
+        page.contents(content_id);
+        page.resources()
+            .fonts()
+            .pair(Name(b"F1"), font_id)
+            .pair(Name(b"F2"), bold_id);
+        page.finish();
+
+        let mut content = Content::new();
+        for (i, label) in sheet.iter().enumerate() {
+            let x = layout.left + (i % layout.columns) as f32 * layout.pitch_x;
+            let top = layout.top + (i / layout.columns) as f32 * layout.pitch_y;
+            draw_label(&mut content, &layout, x, SHEET_HEIGHT - top, label);
+        }
+        pdf.stream(content_id, &content.finish());
+    }
+
+    pdf.catalog(catalog_id).pages(tree_id);
+    pdf.pages(tree_id)
+        .kids(page_ids.iter().copied())
+        .count(page_ids.len() as i32);
+    pdf.type1_font(font_id)
+        .base_font(Name(b"Helvetica"))
+        .encoding_predefined(Name(b"WinAnsiEncoding"));
+    pdf.type1_font(bold_id)
+        .base_font(Name(b"Helvetica-Bold"))
+        .encoding_predefined(Name(b"WinAnsiEncoding"));
+    pdf.finish()
+}
+
+/// Draws one label whose top-left corner is at `x`, `top` (millimetres from
+/// the bottom-left of the page).
+fn draw_label(content: &mut Content, layout: &Layout, x: f32, top: f32, label: &Label) {
+    let inner = layout.width - 2.0 * layout.padding;
+    let left = (x + layout.padding) * PT_PER_MM;
+    let mut baseline = (top - layout.padding) * PT_PER_MM;
+
+    let mut text = |font: &[u8], size: f32, line: &str, baseline: f32| {
+        content
+            .begin_text()
+            .set_font(Name(font), size)
+            .next_line(left, baseline)
+            .show(Str(&win_ansi(&fit(line, inner * PT_PER_MM, size))))
+            .end_text();
+    };
+    baseline -= layout.name_size;
+    text(b"F2", layout.name_size, &label.name, baseline);
+    baseline -= layout.price_size;
+    text(b"F2", layout.price_size, &format!("{:.2}", label.price), baseline);
+    if let Some(detail) = &label.detail {
+        baseline -= layout.detail_size * 1.2;
+        text(b"F1", layout.detail_size, detail, baseline);
+    }
+
+    let Some(barcode) = &label.barcode else {
+        return;
+    };
+    let modules = modules(barcode);
+    let module = (inner / (modules.len() + 2 * QUIET_ZONE) as f32).min(MAX_MODULE_MM);
+    let bars_left = x + (layout.width - module * modules.len() as f32) / 2.0;
+    let caption_bottom = top - layout.height + layout.padding;
+    let bars_bottom = caption_bottom + layout.detail_size * 1.2 / PT_PER_MM;
+
+    let mut i = 0;
+    while i < modules.len() {
+        if !modules[i] {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < modules.len() && modules[i] {
+            i += 1;
+        }
+        content.rect(
+            (bars_left + start as f32 * module) * PT_PER_MM,
+            bars_bottom * PT_PER_MM,
+            (i - start) as f32 * module * PT_PER_MM,
+            layout.barcode_height * PT_PER_MM,
+        );
+    }
+    content.fill_nonzero();
+
+    let caption_width = text_width(&barcode.code, layout.detail_size);
+    content
+        .begin_text()
+        .set_font(Name(b"F1"), layout.detail_size)
+        .next_line(
+            (x + layout.width / 2.0) * PT_PER_MM - caption_width / 2.0,
+            caption_bottom * PT_PER_MM,
+        )
+        .show(Str(&win_ansi(&barcode.code)))
+        .end_text();
+}
+
+/// Renders one ZPL label format per label, sized for the template's stock.
+/// The printer draws the barcodes itself.
+pub fn render_zpl(template: &LabelTemplate, labels: &[Label]) -> String {
+    let layout = layout(template);
+    let dots = |mm: f32| (mm * DOTS_PER_MM).round() as i32;
+    let font = |pt: f32| dots(pt / PT_PER_MM);
+    let width = dots(layout.width);
+    let height = dots(layout.height);
+    let padding = dots(layout.padding);
+    let inner = layout.width - 2.0 * layout.padding;
+
+    let mut zpl = String::new();
+    for label in labels {
+        zpl.push_str(&format!("^XA^CI28^PW{}^LL{}\n", width, height));
+        let mut y = padding;
+        let mut text = |size: f32, line: &str, y: i32| {
+            let h = font(size);
+            zpl.push_str(&format!(
+                "^FO{},{}^A0N,{},{}^FH^FD{}^FS\n",
+                padding,
+                y,
+                h,
+                h,
+                zpl_escape(&fit(line, inner * PT_PER_MM, size)),
+            ));
+        };
+        text(layout.name_size, &label.name, y);
+        y += font(layout.name_size) + font(layout.price_size) / 4;
+        text(layout.price_size, &format!("{:.2}", label.price), y);
+        y += font(layout.price_size);
+        if let Some(detail) = &label.detail {
+            text(layout.detail_size, detail, y + font(layout.detail_size) / 4);
+        }
+
+        if let Some(barcode) = &label.barcode {
+            let modules = modules(barcode).len() as i32;
+            let module = (dots(inner) / (modules + 2 * QUIET_ZONE as i32)).clamp(1, 3);
+            let bar_height = dots(layout.barcode_height);
+            let x = (width - module * modules) / 2;
+            let y = height - padding - bar_height - font(layout.detail_size) * 3 / 2;
+            let symbol = match barcode.symbology {
+                // EAN-13 and UPC-A take the code without its check digit.
+                Symbology::Ean13 => format!("^BEN,{},Y,N^FD{}", bar_height, &barcode.code[..12]),
+                Symbology::UpcA => format!("^BUN,{},Y,N,Y^FD{}", bar_height, &barcode.code[..11]),
+                Symbology::Code128 => format!(
+                    "^BCN,{},Y,N,N,A^FH^FD{}",
+                    bar_height,
+                    zpl_escape(&barcode.code)
+                ),
+            };
+            zpl.push_str(&format!("^FO{},{}^BY{}{}^FS\n", x, y, module, symbol));
+        }
+        zpl.push_str("^XZ\n");
+    }
+    zpl
+}
+
+/// Bars of a barcode, one entry per module, `true` where it is dark.
+fn modules(barcode: &LabelBarcode) -> Vec<bool> {
+    match barcode.symbology {
+        Symbology::Ean13 => ean13_modules(&barcode.code),
+        // A UPC-A symbol is the EAN-13 symbol of the code with a leading zero.
+        Symbology::UpcA => ean13_modules(&format!("0{}", barcode.code)),
+        Symbology::Code128 => code128_modules(&barcode.code),
+    }
+}
+
+/// Left-hand odd-parity digit patterns; right-hand ones are their complement
+/// and even-parity ones the complement reversed.
+const EAN_L: [&str; 10] = [
+    "0001101", "0011001", "0010011", "0111101", "0100011", "0110001", "0101111", "0111011",
+    "0110111", "0001011",
+];
+/// Parity of the six left-hand digits, chosen by the first digit.
+const EAN_PARITY: [&str; 10] = [
+    "LLLLLL", "LLGLGG", "LLGGLG", "LLGGGL", "LGLLGG", "LGGLLG", "LGGGLL", "LGLGLG", "LGLGGL",
+    "LGGLGL",
+];
+
+/// Expects 13 digits with a valid check digit.
+fn ean13_modules(code: &str) -> Vec<bool> {
+    let digits: Vec<usize> = code.bytes().map(|b| (b - b'0') as usize).collect();
+    let odd = |d: usize| EAN_L[d].chars().map(|c| c == '1').collect::<Vec<_>>();
+    let right = |d: usize| odd(d).into_iter().map(|m| !m).collect::<Vec<_>>();
+
+    let mut modules = vec![true, false, true];
+    for (i, parity) in EAN_PARITY[digits[0]].chars().enumerate() {
+        match parity {
+            'L' => modules.extend(odd(digits[i + 1])),
+            _ => modules.extend(right(digits[i + 1]).into_iter().rev()),
+        }
+    }
+    modules.extend([false, true, false, true, false]);
+    for &d in &digits[7..] {
+        modules.extend(right(d));
+    }
+    modules.extend([true, false, true]);
+    modules
+}
+
+/// Bar and space widths of Code 128 symbols 0 to 105.
+const CODE128: [&str; 106] = [
+    "212222", "222122", "222221", "121223", "121322", "131222", "122213", "122312", "132212",
+    "221213", "221312", "231212", "112232", "122132", "122231", "113222", "123122", "123221",
+    "223211", "221132", "221231", "213212", "223112", "312131", "311222", "321122", "321221",
+    "312212", "322112", "322211", "212123", "212321", "232121", "111323", "131123", "131321",
+    "112313", "132113", "132311", "211313", "231113", "231311", "112133", "112331", "132131",
+    "113123", "113321", "133121", "313121", "211331", "231131", "213113", "213311", "213131",
+    "311123", "311321", "331121", "312113", "312311", "332111", "314111", "221411", "431111",
+    "111224", "111422", "121124", "121421", "141122", "141221", "112214", "112412", "122114",
+    "122411", "142112", "142211", "241211", "221114", "413111", "241112", "134111", "111242",
+    "121142", "121241", "114212", "124112", "124211", "411212", "421112", "421211", "212141",
+    "214121", "412121", "111143", "111341", "131141", "114113", "114311", "411113", "411311",
+    "113141", "114131", "311141", "411131", "211412", "211214", "211232",
+];
+const CODE128_START_B: usize = 104;
+const CODE128_STOP: &str = "2331112";
+
+/// Encodes printable ASCII in code set B.
+fn code128_modules(code: &str) -> Vec<bool> {
+    let values: Vec<usize> = code.bytes().map(|b| (b - b' ') as usize).collect();
+    let checksum = values
+        .iter()
+        .enumerate()
+        .fold(CODE128_START_B, |sum, (i, v)| sum + (i + 1) * v)
+        % 103;
+
+    let mut modules = Vec::new();
+    let mut push = |widths: &str| {
+        for (i, w) in widths.bytes().enumerate() {
+            let dark = i % 2 == 0;
+            modules.extend(std::iter::repeat_n(dark, (w - b'0') as usize));
+        }
+    };
+    push(CODE128[CODE128_START_B]);
+    for v in values {
+        push(CODE128[v]);
+    }
+    push(CODE128[checksum]);
+    push(CODE128_STOP);
+    modules
+}
+
+fn text_width(text: &str, size: f32) -> f32 {
+    text.chars().count() as f32 * size * CHAR_WIDTH_EM
+}
+
+/// Shortens `text` with an ellipsis so it fits in `width` points.
+fn fit(text: &str, width: f32, size: f32) -> String {
+    if text_width(text, size) <= width {
+        return text.to_string();
+    }
+    let keep = ((width / (size * CHAR_WIDTH_EM)) as usize).saturating_sub(3);
+    let mut short: String = text.chars().take(keep).collect();
+    short.push_str("...");
+    short
+}
+
+/// Latin-1 bytes for the standard PDF fonts; anything else prints as `?`.
+fn win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c as u32 {
+            0x20..=0x7e | 0xa0..=0xff => c as u8,
+            _ => b'?',
+        })
+        .collect()
+}
+
+/// Hex-escapes the characters ZPL treats as commands inside a `^FH` field.
+fn zpl_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '^' | '~' | '_' | '\\' => escaped.push_str(&format!("_{:02X}", c as u32)),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bars(modules: &[bool]) -> String {
+        modules.iter().map(|&m| if m { '1' } else { '0' }).collect()
+    }
+
+    #[test]
+    fn encodes_ean13() {
+        // 4 sets the left-hand parity to LGLLGG.
+        let expected = [
+            "101", "0001101", "0100111", "0101111", "0111101", "0001001", "0110011", "01010",
+            "1000010", "1000010", "1000010", "1110100", "1000010", "1100110", "101",
+        ]
+        .concat();
+        assert_eq!(bars(&ean13_modules("4006381333931")), expected);
+    }
+
+    #[test]
+    fn encodes_upc_a_as_ean13_with_leading_zero() {
+        let barcode = LabelBarcode {
+            symbology: Symbology::UpcA,
+            code: "036000291452".to_string(),
+        };
+        assert_eq!(modules(&barcode), ean13_modules("0036000291452"));
+    }
+
+    #[test]
+    fn encodes_code128() {
+        // Start B, "A" (33), checksum (104 + 33) % 103 = 34, stop.
+        let expected = ["11010010000", "10100011000", "10001011000", "1100011101011"].concat();
+        assert_eq!(bars(&code128_modules("A")), expected);
+    }
+
+    #[test]
+    fn code128_symbols_are_eleven_modules() {
+        assert_eq!(code128_modules("SKU-42").len(), 11 * (6 + 2) + 13);
+    }
+}
@@ -1,18 +1,325 @@
-use chrono::{DateTime, Utc};
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, NaiveDate, Utc};
 use sqlx::prelude::FromRow;
 use utoipa::ToSchema;
 
+use crate::mlocation::models::LocationStockModel;
+use crate::util::barcode::{MAX_CODE_LEN, has_valid_check_digit, is_valid_code};
+use crate::util::money::Money;
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, FromRow, ToSchema, PartialEq)]
 #[allow(non_snake_case)]
 pub struct ProductModel {
     pub id: uuid::Uuid,
     pub name: String,
-    pub price: f64,
+    #[schema(value_type = String)]
+    pub price: Money,
     pub quantity: i32,
     #[serde(rename = "packPrice")]
-    pub pack_price: Option<f64>,
+    #[schema(value_type = Option<String>)]
+    pub pack_price: Option<Money>,
+    /// Base units in one pack; stock is always counted in base units.
+    #[serde(rename = "unitsPerPack")]
+    pub units_per_pack: i32,
+    /// Weighted average cost per base unit of the stock received so far.
+    #[serde(rename = "averageCost")]
+    #[schema(value_type = String)]
+    pub average_cost: Money,
     #[serde(rename = "createdAt")]
     pub created_at: Option<DateTime<Utc>>,
     #[serde(rename = "updatedAt")]
     pub updated_at: Option<DateTime<Utc>>,
+    /// Stock at or below this many base units is due for reordering; 0 turns it off.
+    #[serde(rename = "reorderLevel")]
+    pub reorder_level: i32,
+    /// Base units to order when the product is due.
+    #[serde(rename = "reorderQuantity")]
+    pub reorder_quantity: i32,
+    /// The shop's own code for the product; also resolves on a scan.
+    pub sku: Option<String>,
+    #[serde(rename = "categoryId")]
+    pub category_id: Option<uuid::Uuid>,
+    /// The product this one is a variant of.
+    #[serde(rename = "parentId")]
+    pub parent_id: Option<uuid::Uuid>,
+    /// What sets a variant apart from its siblings, e.g. `{"strength": "500mg"}`.
+    #[serde(rename = "variantAttributes")]
+    #[schema(value_type = Option<Object>)]
+    pub variant_attributes: Option<serde_json::Value>,
+}
+
+/// A sellable product offered while a search is typed.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, FromRow, ToSchema, PartialEq)]
+pub struct ProductSuggestionModel {
+    pub id: uuid::Uuid,
+    pub name: String,
+    pub sku: Option<String>,
+    #[schema(value_type = String)]
+    pub price: Money,
+    pub quantity: i32,
+}
+
+/// A product with its stock at each location.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, ToSchema, PartialEq)]
+pub struct ProductWithStockModel {
+    #[serde(flatten)]
+    pub product: ProductModel,
+    /// Every location, or only the one asked for.
+    pub locations: Vec<LocationStockModel>,
+    /// Variants of a parent product, each with its own stock.
+    #[serde(default)]
+    #[schema(no_recursion)]
+    pub variants: Vec<ProductWithStockModel>,
+}
+
+/// A received batch of a product, held at one location. A lot is expired from
+/// its expiry date on and is never allocated to a cart.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, FromRow, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct ProductLotModel {
+    pub id: uuid::Uuid,
+    #[serde(rename = "productId")]
+    pub product_id: uuid::Uuid,
+    #[serde(rename = "locationId")]
+    pub location_id: uuid::Uuid,
+    #[serde(rename = "lotNumber")]
+    pub lot_number: String,
+    #[serde(rename = "expiryDate")]
+    pub expiry_date: NaiveDate,
+    pub quantity: i32,
+    /// Cost per base unit, when it was recorded at receipt.
+    #[serde(rename = "unitCost")]
+    #[schema(value_type = Option<String>)]
+    pub unit_cost: Option<Money>,
+    pub expired: bool,
+    #[serde(rename = "createdAt")]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// A product's stock split into lots and unlotted stock.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct ProductLotsModel {
+    #[serde(rename = "productId")]
+    pub product_id: uuid::Uuid,
+    /// Total on hand across locations, expired lots included.
+    pub quantity: i32,
+    /// Stock that can be added to a cart: unexpired lots plus unlotted stock.
+    #[serde(rename = "sellableQuantity")]
+    pub sellable_quantity: i32,
+    #[serde(rename = "unlottedQuantity")]
+    pub unlotted_quantity: i32,
+    pub lots: Vec<ProductLotModel>,
+}
+
+/// The unit a product is sold in: a single unit at `price` or a pack at `pack_price`.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize, sqlx::Type, PartialEq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "sell_unit", rename_all = "lowercase")]
+pub enum SellUnit {
+    #[default]
+    Unit,
+    Pack,
+}
+
+impl fmt::Display for SellUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            SellUnit::Unit => "unit",
+            SellUnit::Pack => "pack",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for SellUnit {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Unit" | "unit" => Ok(SellUnit::Unit),
+            "Pack" | "pack" => Ok(SellUnit::Pack),
+            _ => Err(()),
+        }
+    }
+}
+
+impl ProductModel {
+    /// Catalog price for one sell unit, if the product is sold that way.
+    pub fn list_price(&self, sell_unit: &SellUnit) -> Option<Money> {
+        match sell_unit {
+            SellUnit::Unit => Some(self.price),
+            SellUnit::Pack => self.pack_price,
+        }
+    }
+
+    /// Base units taken out of stock by one sell unit.
+    pub fn units_per(&self, sell_unit: &SellUnit) -> i32 {
+        match sell_unit {
+            SellUnit::Unit => 1,
+            SellUnit::Pack => self.units_per_pack,
+        }
+    }
+}
+
+/// The symbology of a product barcode. EAN-13 and UPC-A carry a check digit;
+/// internal codes are the shop's own and are taken as printed.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, sqlx::Type, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "barcode_type", rename_all = "snake_case")]
+pub enum BarcodeType {
+    Ean13,
+    UpcA,
+    Internal,
+}
+
+impl fmt::Display for BarcodeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            BarcodeType::Ean13 => "EAN-13",
+            BarcodeType::UpcA => "UPC-A",
+            BarcodeType::Internal => "internal",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl BarcodeType {
+    /// Checks length, characters and check digit of a code of this type.
+    pub fn validate(&self, code: &str) -> Result<(), String> {
+        let digits = match self {
+            BarcodeType::Ean13 => 13,
+            BarcodeType::UpcA => 12,
+            BarcodeType::Internal => {
+                if is_valid_code(code) {
+                    return Ok(());
+                }
+                return Err(format!(
+                    "An internal code is 1 to {} characters without spaces",
+                    MAX_CODE_LEN
+                ));
+            }
+        };
+        if code.len() != digits || !code.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(format!("An {} code is {} digits", self, digits));
+        }
+        if !has_valid_check_digit(code) {
+            return Err(format!("Invalid {} check digit", self));
+        }
+        Ok(())
+    }
+}
+
+/// How a bundle is priced: its own `price`, or the sum of its components'
+/// unit prices, kept up to date as they change.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize, sqlx::Type, PartialEq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "bundle_pricing", rename_all = "lowercase")]
+pub enum BundlePricing {
+    #[default]
+    Fixed,
+    Derived,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, FromRow, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct BundleComponentModel {
+    #[serde(rename = "productId")]
+    pub product_id: uuid::Uuid,
+    pub name: String,
+    /// Base units of the component in one bundle.
+    pub quantity: i32,
+    #[serde(rename = "unitPrice")]
+    #[schema(value_type = String)]
+    pub unit_price: Money,
+    /// Base units of the component in stock, at one location or in total.
+    #[serde(rename = "inStock")]
+    pub in_stock: i32,
+}
+
+/// A bundle with its components and how many can be made from their stock.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, ToSchema, PartialEq)]
+pub struct BundleModel {
+    #[serde(flatten)]
+    pub product: ProductModel,
+    pub pricing: BundlePricing,
+    pub components: Vec<BundleComponentModel>,
+    pub available: i32,
+}
+
+impl BundleModel {
+    pub fn new(
+        product: ProductModel,
+        pricing: BundlePricing,
+        components: Vec<BundleComponentModel>,
+    ) -> Self {
+        let available = components
+            .iter()
+            .map(|c| c.in_stock.max(0) / c.quantity)
+            .min()
+            .unwrap_or(0);
+        Self {
+            product,
+            pricing,
+            components,
+            available,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, FromRow, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct ProductBarcodeModel {
+    pub id: i64,
+    #[serde(rename = "productId")]
+    pub product_id: uuid::Uuid,
+    pub code: String,
+    #[serde(rename = "barcodeType")]
+    pub barcode_type: BarcodeType,
+    /// What one scan of this code sells.
+    #[serde(rename = "sellUnit")]
+    pub sell_unit: SellUnit,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// A scanned code resolved to the product and what one scan sells.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct ProductLookupModel {
+    /// The code as it is stored, which may differ from a UPC-A scan by a leading zero.
+    pub code: String,
+    /// Empty when the code matched the product's SKU.
+    #[serde(rename = "barcodeType")]
+    pub barcode_type: Option<BarcodeType>,
+    #[serde(rename = "sellUnit")]
+    pub sell_unit: SellUnit,
+    /// Catalog price of one sell unit.
+    #[serde(rename = "unitPrice")]
+    #[schema(value_type = String)]
+    pub unit_price: Money,
+    #[serde(rename = "unitsPerSellUnit")]
+    pub units_per_sell_unit: i32,
+    pub product: ProductModel,
+}
+
+/// Label stock to print on. Both print as A4 sheets in a PDF, or one label at
+/// a time in ZPL.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LabelTemplate {
+    /// 70 x 37 mm shelf-edge labels, 24 to a sheet.
+    Shelf,
+    /// 38.1 x 21.2 mm price stickers, 65 to a sheet.
+    Sticker,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LabelFormat {
+    #[default]
+    Pdf,
+    /// For Zebra-compatible thermal printers.
+    Zpl,
 }
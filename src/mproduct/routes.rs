@@ -1,17 +1,26 @@
 use axum::{
     Router,
-    extract::{Query, State},
+    Extension,
+    extract::State,
     routing::{delete, get, post, put},
 };
 
 use crate::{
     AppState,
-    mauth::layers::{MyAuthLayer, MyAuthPermsLayer},
+    mauth::{
+        layers::{MyAuthLayer, MyAuthPermsLayer},
+        middlewares::JWTAuthMiddleware,
+    },
     mproduct::{
         self,
-        schema::{AddProductSchema, DeleteProductSchema, UpdateProductSchema},
+        schema::{
+            AddProductBarcodeSchema, AddProductLotSchema, AddProductSchema, AdjustStockSchema,
+            AutocompleteQuery, DeleteProductBarcodeSchema, DeleteProductSchema, GetBundleSchema,
+            GetProductBarcodesSchema, UpdateBundleSchema,
+            GetProductLotsSchema, PrintLabelsSchema, ProductFilterOptions, ProductLookupQuery,
+            UpdateProductSchema,
+        },
     },
-    shared_var::FilterOptions,
 };
 
 pub fn create_prod_router(app: AppState) -> Router {
@@ -20,18 +29,12 @@ pub fn create_prod_router(app: AppState) -> Router {
             "/get",
             get(
                 |pool: axum::extract::State<AppState>,
-                 filter: axum::extract::Query<FilterOptions>| async move {
-                    let op = Query(FilterOptions {
-                        limit: filter.limit.as_ref().and_then(|f| Some(f.clone())),
-                        page: filter.page.as_ref().and_then(|f| Some(f.clone())),
-                        // search: Some("Amoxil".to_string()),
-                        search: filter.search.as_ref().and_then(|f| Some(f.clone())),
-                    });
+                 filter: axum::extract::Query<ProductFilterOptions>| async move {
                     let state = AppState {
                         db: pool.0.db,
                         env: pool.0.env,
                     };
-                    return mproduct::handlers::get_product_handler(op, State(state)).await;
+                    return mproduct::handlers::get_product_handler(filter, State(state)).await;
                 },
             ),
         )
@@ -39,16 +42,174 @@ pub fn create_prod_router(app: AppState) -> Router {
             "/update",
             put(
                 |pool: axum::extract::State<AppState>,
+                 Extension(auth): Extension<JWTAuthMiddleware>,
                  payload: axum::extract::Json<UpdateProductSchema>| async move {
                     let state = AppState {
                         db: pool.0.db,
                         env: pool.0.env,
                     };
-                    return mproduct::handlers::update_product_handler(State(state), payload).await;
+                    return mproduct::handlers::update_product_handler(auth, State(state), payload)
+                        .await;
+                },
+            )
+            .layer(MyAuthPermsLayer {}),
+        )
+        .route(
+            "/adjust-stock",
+            post(
+                |pool: axum::extract::State<AppState>,
+                 Extension(auth): Extension<JWTAuthMiddleware>,
+                 payload: axum::extract::Json<AdjustStockSchema>| async move {
+                    let state = AppState {
+                        db: pool.0.db,
+                        env: pool.0.env,
+                    };
+                    return mproduct::handlers::adjust_stock_handler(auth, payload, State(state))
+                        .await;
+                },
+            )
+            .layer(MyAuthPermsLayer {}),
+        )
+        .route(
+            "/lots/add",
+            post(
+                |pool: axum::extract::State<AppState>,
+                 Extension(auth): Extension<JWTAuthMiddleware>,
+                 payload: axum::extract::Json<AddProductLotSchema>| async move {
+                    let state = AppState {
+                        db: pool.0.db,
+                        env: pool.0.env,
+                    };
+                    return mproduct::handlers::add_product_lot_handler(auth, payload, State(state))
+                        .await;
+                },
+            )
+            .layer(MyAuthPermsLayer {}),
+        )
+        .route(
+            "/lots/get",
+            get(
+                |pool: axum::extract::State<AppState>,
+                 params: axum::extract::Query<GetProductLotsSchema>| async move {
+                    let state = AppState {
+                        db: pool.0.db,
+                        env: pool.0.env,
+                    };
+                    return mproduct::handlers::get_product_lots_handler(params, State(state)).await;
+                },
+            ),
+        )
+        .route(
+            "/lookup",
+            get(
+                |pool: axum::extract::State<AppState>,
+                 params: axum::extract::Query<ProductLookupQuery>| async move {
+                    let state = AppState {
+                        db: pool.0.db,
+                        env: pool.0.env,
+                    };
+                    return mproduct::handlers::lookup_product_handler(params, State(state)).await;
+                },
+            ),
+        )
+        .route(
+            "/autocomplete",
+            get(
+                |pool: axum::extract::State<AppState>,
+                 params: axum::extract::Query<AutocompleteQuery>| async move {
+                    let state = AppState {
+                        db: pool.0.db,
+                        env: pool.0.env,
+                    };
+                    return mproduct::handlers::autocomplete_products_handler(params, State(state))
+                        .await;
+                },
+            ),
+        )
+        .route(
+            "/barcodes/get",
+            get(
+                |pool: axum::extract::State<AppState>,
+                 params: axum::extract::Query<GetProductBarcodesSchema>| async move {
+                    let state = AppState {
+                        db: pool.0.db,
+                        env: pool.0.env,
+                    };
+                    return mproduct::handlers::get_product_barcodes_handler(params, State(state))
+                        .await;
+                },
+            ),
+        )
+        .route(
+            "/barcodes/add",
+            post(
+                |pool: axum::extract::State<AppState>,
+                 payload: axum::extract::Json<AddProductBarcodeSchema>| async move {
+                    let state = AppState {
+                        db: pool.0.db,
+                        env: pool.0.env,
+                    };
+                    return mproduct::handlers::add_product_barcode_handler(payload, State(state))
+                        .await;
+                },
+            )
+            .layer(MyAuthPermsLayer {}),
+        )
+        .route(
+            "/barcodes/delete",
+            delete(
+                |pool: axum::extract::State<AppState>,
+                 payload: axum::extract::Json<DeleteProductBarcodeSchema>| async move {
+                    let state = AppState {
+                        db: pool.0.db,
+                        env: pool.0.env,
+                    };
+                    return mproduct::handlers::delete_product_barcode_handler(payload, State(state))
+                        .await;
+                },
+            )
+            .layer(MyAuthPermsLayer {}),
+        )
+        .route(
+            "/bundles/get",
+            get(
+                |pool: axum::extract::State<AppState>,
+                 params: axum::extract::Query<GetBundleSchema>| async move {
+                    let state = AppState {
+                        db: pool.0.db,
+                        env: pool.0.env,
+                    };
+                    return mproduct::handlers::get_bundle_handler(params, State(state)).await;
+                },
+            ),
+        )
+        .route(
+            "/bundles/update",
+            put(
+                |pool: axum::extract::State<AppState>,
+                 payload: axum::extract::Json<UpdateBundleSchema>| async move {
+                    let state = AppState {
+                        db: pool.0.db,
+                        env: pool.0.env,
+                    };
+                    return mproduct::handlers::update_bundle_handler(payload, State(state)).await;
                 },
             )
             .layer(MyAuthPermsLayer {}),
         )
+        .route(
+            "/labels",
+            post(
+                |pool: axum::extract::State<AppState>,
+                 payload: axum::extract::Json<PrintLabelsSchema>| async move {
+                    let state = AppState {
+                        db: pool.0.db,
+                        env: pool.0.env,
+                    };
+                    return mproduct::handlers::print_labels_handler(payload, State(state)).await;
+                },
+            ),
+        )
         .route(
             "/delete",
             delete(
@@ -66,12 +227,13 @@ pub fn create_prod_router(app: AppState) -> Router {
             "/add",
             post(
                 |pool: axum::extract::State<AppState>,
+                 Extension(auth): Extension<JWTAuthMiddleware>,
                  payload: axum::extract::Json<AddProductSchema>| async move {
                     let state = AppState {
                         db: pool.0.db,
                         env: pool.0.env,
                     };
-                    return mproduct::handlers::add_product_handler(payload, State(state)).await;
+                    return mproduct::handlers::add_product_handler(auth, payload, State(state)).await;
                 },
             ),
         )
@@ -1,18 +1,87 @@
-use chrono::{DateTime, Utc};
-use utoipa::ToSchema;
+use chrono::{DateTime, NaiveDate, Utc};
+use utoipa::{IntoParams, ToSchema};
 use validator::Validate;
 
+use crate::mproduct::models::{BarcodeType, BundlePricing, LabelFormat, LabelTemplate, SellUnit};
+use crate::mstock::models::StockAdjustmentReason;
+use crate::util::money::Money;
+
+#[derive(Debug, Default, Clone, serde::Deserialize, ToSchema, IntoParams, PartialEq)]
+pub struct ProductFilterOptions {
+    pub page: Option<i64>,
+    /// 10 by default, at most 100.
+    pub limit: Option<i64>,
+    /// Words or code prefixes across name, SKU, barcodes and category, or a
+    /// misspelt name. Results are ranked by how well they match.
+    pub search: Option<String>,
+    /// Only report stock held at this location.
+    pub location_id: Option<uuid::Uuid>,
+    /// Only products in this category or any of its subcategories.
+    pub category_id: Option<uuid::Uuid>,
+    /// `relevance` (searches only), `name`, `price`, `quantity` or
+    /// `createdAt`; prefix with `-` for descending. Searches default to
+    /// `-relevance`, other lists to `-createdAt`.
+    pub sort: Option<String>,
+    /// `nextCursor` of the previous page, to resume after it instead of
+    /// counting pages.
+    pub cursor: Option<String>,
+    #[schema(value_type = Option<String>)]
+    #[param(value_type = Option<String>)]
+    pub price_min: Option<Money>,
+    #[schema(value_type = Option<String>)]
+    #[param(value_type = Option<String>)]
+    pub price_max: Option<Money>,
+    /// Bounds on stock, across a product's variants and at `location_id` if given.
+    pub quantity_min: Option<i64>,
+    pub quantity_max: Option<i64>,
+    pub created_from: Option<DateTime<Utc>>,
+    pub created_to: Option<DateTime<Utc>>,
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Debug, Validate, ToSchema, PartialEq)]
 pub struct AddProductSchema {
     #[serde()]
     pub id: Option<uuid::Uuid>,
     #[validate(length(min = 1))]
     pub name: String,
-    #[validate(range(min = 0.0))]
-    pub price: f64,
+    #[schema(value_type = String)]
+    pub price: Money,
     pub quantity: u32,
+    /// Where the opening `quantity` is received. Defaults to the default location.
+    #[serde(rename = "locationId")]
+    pub location_id: Option<uuid::Uuid>,
     #[serde(rename = "packPrice")]
-    pub pack_price: Option<f64>,
+    #[schema(value_type = Option<String>)]
+    pub pack_price: Option<Money>,
+    #[serde(rename = "unitsPerPack")]
+    pub units_per_pack: Option<i32>,
+    /// Cost per base unit of the opening `quantity`.
+    #[serde(rename = "unitCost")]
+    #[schema(value_type = Option<String>)]
+    pub unit_cost: Option<Money>,
+    /// Stock at or below this many base units is due for reordering; 0 turns it off.
+    #[serde(rename = "reorderLevel")]
+    pub reorder_level: Option<i32>,
+    /// Base units to order when the product is due.
+    #[serde(rename = "reorderQuantity")]
+    pub reorder_quantity: Option<i32>,
+    /// Unique regardless of case; printable characters without spaces.
+    pub sku: Option<String>,
+    /// Categorized with its parent when this is a variant.
+    #[serde(rename = "categoryId")]
+    pub category_id: Option<uuid::Uuid>,
+    /// Adds the product as a variant of this one. A parent cannot hold stock.
+    #[serde(rename = "parentId")]
+    pub parent_id: Option<uuid::Uuid>,
+    /// Required for a variant: attribute names to non-empty text values.
+    #[serde(rename = "variantAttributes")]
+    #[schema(value_type = Option<Object>)]
+    pub variant_attributes: Option<serde_json::Value>,
+    /// Makes the product a bundle of these components; a bundle holds no stock.
+    pub components: Option<Vec<BundleComponentSchema>>,
+    /// For a bundle; defaults to `fixed`. A `derived` bundle ignores `price`.
+    #[serde(rename = "bundlePricing")]
+    pub bundle_pricing: Option<BundlePricing>,
     #[serde(rename = "createdAt")]
     pub created_at: Option<DateTime<Utc>>,
     #[serde(rename = "updatedAt")]
@@ -26,11 +95,71 @@ pub struct UpdateProductSchema {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub price: Option<f64>,
+    #[schema(value_type = Option<String>)]
+    pub price: Option<Money>,
+    /// Admin only; prefer `/products/adjust-stock`. Requires `quantity_reason`.
+    /// Sets the stock at `location_id`, not the total.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub quantity: Option<i32>,
+    /// Location whose stock `quantity` sets. Defaults to the default location.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location_id: Option<uuid::Uuid>,
+    /// Audit reason recorded with a direct `quantity` change.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantity_reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    pub pack_price: Option<Money>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub units_per_pack: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reorder_level: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reorder_quantity: Option<i32>,
+    /// An empty string clears the SKU.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sku: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category_id: Option<uuid::Uuid>,
+    /// Leaves the product uncategorized; takes precedence over `category_id`.
+    #[serde(default)]
+    pub clear_category: bool,
+    /// Variants only; replaces all of the variant's attributes.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub pack_price: Option<f64>,
+    #[schema(value_type = Option<Object>)]
+    pub variant_attributes: Option<serde_json::Value>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct AdjustStockSchema {
+    pub product_id: uuid::Uuid,
+    /// Signed change in base units.
+    pub delta: i32,
+    pub reason: StockAdjustmentReason,
+    /// Lot to adjust; leave empty for stock that is not in a lot.
+    pub lot_id: Option<uuid::Uuid>,
+    /// Defaults to the lot's location, or the default location for unlotted stock.
+    pub location_id: Option<uuid::Uuid>,
+    pub note: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct AddProductLotSchema {
+    pub product_id: uuid::Uuid,
+    /// Where the lot is received. Defaults to the default location.
+    pub location_id: Option<uuid::Uuid>,
+    pub lot_number: String,
+    pub expiry_date: NaiveDate,
+    /// Base units received into the lot.
+    pub quantity: i32,
+    /// Cost per base unit.
+    #[schema(value_type = Option<String>)]
+    pub unit_cost: Option<Money>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, IntoParams, PartialEq)]
+pub struct GetProductLotsSchema {
+    pub product_id: uuid::Uuid,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
@@ -38,3 +167,73 @@ pub struct DeleteProductSchema {
     #[serde()]
     pub id: uuid::Uuid,
 }
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, IntoParams, PartialEq)]
+pub struct ProductLookupQuery {
+    /// A scanned barcode or a SKU.
+    pub code: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, IntoParams, PartialEq)]
+pub struct AutocompleteQuery {
+    /// What has been typed so far.
+    pub q: String,
+    /// 8 by default, at most 20.
+    pub limit: Option<i64>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, IntoParams, PartialEq)]
+pub struct GetProductBarcodesSchema {
+    pub product_id: uuid::Uuid,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct AddProductBarcodeSchema {
+    pub product_id: uuid::Uuid,
+    pub code: String,
+    pub barcode_type: BarcodeType,
+    /// What one scan sells. Defaults to a single unit; `pack` needs a pack price.
+    #[serde(default)]
+    pub sell_unit: SellUnit,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct DeleteProductBarcodeSchema {
+    pub id: i64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, ToSchema, PartialEq)]
+pub struct BundleComponentSchema {
+    pub product_id: uuid::Uuid,
+    /// Base units of the component in one bundle.
+    pub quantity: i32,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, IntoParams, PartialEq)]
+pub struct GetBundleSchema {
+    pub product_id: uuid::Uuid,
+    /// Count stock at this location only.
+    pub location_id: Option<uuid::Uuid>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct UpdateBundleSchema {
+    pub product_id: uuid::Uuid,
+    pub pricing: Option<BundlePricing>,
+    /// Replaces every component. Lines already in carts keep what they hold.
+    pub components: Option<Vec<BundleComponentSchema>>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct PrintLabelsSchema {
+    /// Labels print in this order; list a product twice for two labels.
+    pub product_ids: Vec<uuid::Uuid>,
+    pub template: LabelTemplate,
+    #[serde(default)]
+    pub format: LabelFormat,
+    /// Price and barcode of this sell unit. Defaults to a single unit.
+    #[serde(default)]
+    pub sell_unit: SellUnit,
+    /// Labels per product. Defaults to 1.
+    pub copies: Option<u32>,
+}
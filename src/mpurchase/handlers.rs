@@ -0,0 +1,997 @@
+use std::collections::{HashMap, HashSet};
+
+use axum::Json;
+use axum::extract::{Query, State};
+use sqlx::{PgExecutor, Postgres, Transaction, query_as};
+
+use crate::AppState;
+use crate::mauth::middlewares::JWTAuthMiddleware;
+use crate::mlocation::handlers::resolve_location;
+use crate::mpurchase::models::{
+    PurchaseOrderLineModel, PurchaseOrderModel, PurchaseOrderStatus, PurchaseOrderWithLinesModel,
+    PurchaseReceiptLineModel, PurchaseSuggestionModel,
+};
+use crate::mpurchase::schema::{
+    AddPurchaseOrderSchema, DraftSuggestedOrdersSchema, GetPurchaseOrdersSchema,
+    PurchaseOrderIdSchema, PurchaseOrderLineSchema, PurchaseSuggestionsQuery,
+    ReceivePurchaseOrderLineSchema, ReceivePurchaseOrderSchema,
+};
+use crate::mstock::handlers::{apply_stock_movement, low_stock_products};
+use crate::mstock::models::{NewStockMovement, StockMovementReason};
+use crate::shared_var::MyBaseResponse;
+use crate::util::money::{Money, round_money};
+
+async fn load_order<'e, E: PgExecutor<'e>>(
+    executor: E,
+    id: uuid::Uuid,
+) -> Result<Option<PurchaseOrderModel>, sqlx::Error> {
+    query_as!(
+        PurchaseOrderModel,
+        r#"
+        SELECT po.id, po.po_number, po.supplier_id, s.name AS supplier_name,
+               po.location_id, l.name AS location_name, po.status AS "status: PurchaseOrderStatus",
+               po.expected_date, po.note,
+               (SELECT COALESCE(SUM(pl.quantity * pl.unit_cost), 0)
+                FROM purchase_order_lines pl WHERE pl.purchase_order_id = po.id) AS "order_total!",
+               po.created_by, po.sent_by, po.sent_at, po.closed_at, po.created_at, po.updated_at
+        FROM purchase_orders po
+        JOIN suppliers s ON s.id = po.supplier_id
+        JOIN locations l ON l.id = po.location_id
+        WHERE po.id = $1
+        FOR UPDATE OF po
+        "#,
+        id,
+    )
+    .fetch_optional(executor)
+    .await
+}
+
+async fn load_lines<'e, E: PgExecutor<'e>>(
+    executor: E,
+    order_ids: &[uuid::Uuid],
+) -> Result<Vec<PurchaseOrderLineModel>, sqlx::Error> {
+    query_as!(
+        PurchaseOrderLineModel,
+        r#"
+        SELECT pl.id, pl.purchase_order_id, pl.product_id, p.name AS product_name, pl.quantity,
+               pl.unit_cost, pl.received_quantity, pl.quantity - pl.received_quantity AS "outstanding!"
+        FROM purchase_order_lines pl
+        JOIN products p ON p.id = pl.product_id
+        WHERE pl.purchase_order_id = ANY($1)
+        ORDER BY pl.id
+        "#,
+        order_ids,
+    )
+    .fetch_all(executor)
+    .await
+}
+
+async fn load_receipts<'e, E: PgExecutor<'e>>(
+    executor: E,
+    order_ids: &[uuid::Uuid],
+) -> Result<Vec<PurchaseReceiptLineModel>, sqlx::Error> {
+    query_as!(
+        PurchaseReceiptLineModel,
+        r#"
+        SELECT rl.id, rl.receipt_id, r.purchase_order_id, rl.line_id, pl.product_id,
+               p.name AS product_name, rl.lot_id, lot.lot_number AS "lot_number?", rl.quantity,
+               rl.unit_cost, r.note, r.received_by, r.received_at
+        FROM purchase_receipt_lines rl
+        JOIN purchase_receipts r ON r.id = rl.receipt_id
+        JOIN purchase_order_lines pl ON pl.id = rl.line_id
+        JOIN products p ON p.id = pl.product_id
+        LEFT JOIN product_lots lot ON lot.id = rl.lot_id
+        WHERE r.purchase_order_id = ANY($1)
+        ORDER BY r.received_at, rl.id
+        "#,
+        order_ids,
+    )
+    .fetch_all(executor)
+    .await
+}
+
+fn with_lines(
+    orders: Vec<PurchaseOrderModel>,
+    lines: Vec<PurchaseOrderLineModel>,
+    receipts: Vec<PurchaseReceiptLineModel>,
+) -> Vec<PurchaseOrderWithLinesModel> {
+    let mut lines_by_order: HashMap<uuid::Uuid, Vec<PurchaseOrderLineModel>> = HashMap::new();
+    for line in lines {
+        lines_by_order
+            .entry(line.purchase_order_id)
+            .or_default()
+            .push(line);
+    }
+    let mut receipts_by_order: HashMap<uuid::Uuid, Vec<PurchaseReceiptLineModel>> =
+        HashMap::new();
+    for receipt in receipts {
+        receipts_by_order
+            .entry(receipt.purchase_order_id)
+            .or_default()
+            .push(receipt);
+    }
+    orders
+        .into_iter()
+        .map(|order| PurchaseOrderWithLinesModel {
+            lines: lines_by_order.remove(&order.id).unwrap_or_default(),
+            receipts: receipts_by_order.remove(&order.id).unwrap_or_default(),
+            order,
+        })
+        .collect()
+}
+
+/// Rejects a step the order's current status does not allow.
+fn check_transition(
+    order: &PurchaseOrderModel,
+    next: PurchaseOrderStatus,
+) -> Result<(), MyBaseResponse<()>> {
+    if order.status.can_transition_to(&next) {
+        return Ok(());
+    }
+    let allowed: Vec<String> = order
+        .status
+        .allowed_next()
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    Err(MyBaseResponse::error(
+        409,
+        format!(
+            "Purchase order is {}; cannot move to {} (allowed: {})",
+            order.status,
+            next,
+            if allowed.is_empty() { "none".to_string() } else { allowed.join(", ") }
+        ),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/purchase-orders/get",
+    tag = "Purchasing",
+    params(
+        GetPurchaseOrdersSchema
+    ),
+    responses(
+        (status = 200, description = "Purchase orders retrieved successfully", body = MyBaseResponse<Vec<PurchaseOrderWithLinesModel>>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn get_purchase_orders_handler(
+    Query(params): Query<GetPurchaseOrdersSchema>,
+    State(app_state): State<AppState>,
+) -> MyBaseResponse<Vec<PurchaseOrderWithLinesModel>> {
+    let orders = query_as!(
+        PurchaseOrderModel,
+        r#"
+        SELECT po.id, po.po_number, po.supplier_id, s.name AS supplier_name,
+               po.location_id, l.name AS location_name, po.status AS "status: PurchaseOrderStatus",
+               po.expected_date, po.note,
+               (SELECT COALESCE(SUM(pl.quantity * pl.unit_cost), 0)
+                FROM purchase_order_lines pl WHERE pl.purchase_order_id = po.id) AS "order_total!",
+               po.created_by, po.sent_by, po.sent_at, po.closed_at, po.created_at, po.updated_at
+        FROM purchase_orders po
+        JOIN suppliers s ON s.id = po.supplier_id
+        JOIN locations l ON l.id = po.location_id
+        WHERE ($1::TEXT IS NULL OR po.status::TEXT = $1)
+          AND ($2::UUID IS NULL OR po.supplier_id = $2)
+        ORDER BY po.created_at DESC
+        "#,
+        params.status.map(|s| s.to_string()),
+        params.supplier_id,
+    )
+    .fetch_all(&app_state.db)
+    .await;
+    let orders = match orders {
+        Ok(o) => o,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let ids: Vec<uuid::Uuid> = orders.iter().map(|o| o.id).collect();
+    let lines = match load_lines(&app_state.db, &ids).await {
+        Ok(l) => l,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+    match load_receipts(&app_state.db, &ids).await {
+        Ok(receipts) => MyBaseResponse::ok(
+            Some(with_lines(orders, lines, receipts)),
+            Some("Purchase orders retrieved successfully".into()),
+        ),
+        Err(e) => MyBaseResponse::db_err(e),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/purchase-orders/add",
+    tag = "Purchasing",
+    request_body = AddPurchaseOrderSchema,
+    responses(
+        (status = 200, description = "Draft purchase order created", body = MyBaseResponse<PurchaseOrderWithLinesModel>),
+        (status = 400, description = "Invalid purchase order", body = MyBaseResponse<PurchaseOrderWithLinesModel>),
+        (status = 404, description = "Supplier, location or product not found", body = MyBaseResponse<PurchaseOrderWithLinesModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn add_purchase_order_handler(
+    auth: JWTAuthMiddleware,
+    Json(payload): Json<AddPurchaseOrderSchema>,
+    State(app_state): State<AppState>,
+) -> MyBaseResponse<PurchaseOrderWithLinesModel> {
+    if payload.lines.is_empty() {
+        return MyBaseResponse::error(400, "A purchase order needs at least one line");
+    }
+    let mut seen = HashSet::new();
+    for line in &payload.lines {
+        if line.quantity <= 0 {
+            return MyBaseResponse::error(400, "Quantity must be > 0");
+        }
+        if line.unit_cost.is_some_and(|c| c.is_sign_negative()) {
+            return MyBaseResponse::error(400, "Unit cost cannot be negative");
+        }
+        if !seen.insert(line.product_id) {
+            return MyBaseResponse::error(
+                400,
+                format!("Product {} is listed more than once", line.product_id),
+            );
+        }
+    }
+
+    let mut tx = match app_state.db.begin().await {
+        Ok(t) => t,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let supplier = sqlx::query!(
+        r#"SELECT name, is_active FROM suppliers WHERE id = $1"#,
+        payload.supplier_id,
+    )
+    .fetch_optional(&mut *tx)
+    .await;
+    match supplier {
+        Ok(Some(s)) if !s.is_active => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::error(400, format!("Supplier {} is inactive", s.name));
+        }
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::error(404, "Supplier not found");
+        }
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    }
+
+    let location = match resolve_location(&mut *tx, payload.location_id).await {
+        Ok(l) => l,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return e.cast();
+        }
+    };
+
+    // Lines without a cost take the supplier's last cost for the product.
+    let product_ids: Vec<uuid::Uuid> = payload.lines.iter().map(|l| l.product_id).collect();
+    let known = sqlx::query!(
+        r#"
+        SELECT p.id, p.name, ps.last_cost AS "last_cost?"
+        FROM products p
+        LEFT JOIN product_suppliers ps ON ps.product_id = p.id AND ps.supplier_id = $2
+        WHERE p.id = ANY($1)
+        "#,
+        &product_ids,
+        payload.supplier_id,
+    )
+    .fetch_all(&mut *tx)
+    .await;
+    let known: HashMap<uuid::Uuid, (String, Option<Money>)> = match known {
+        Ok(rows) => rows
+            .into_iter()
+            .map(|r| (r.id, (r.name, r.last_cost)))
+            .collect(),
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+
+    let mut unit_costs = Vec::with_capacity(payload.lines.len());
+    for line in &payload.lines {
+        let Some((name, last_cost)) = known.get(&line.product_id) else {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::error(404, format!("Product {} not found", line.product_id));
+        };
+        match line.unit_cost.or(*last_cost) {
+            Some(cost) => unit_costs.push(round_money(cost)),
+            None => {
+                let _ = tx.rollback().await;
+                return MyBaseResponse::error(
+                    400,
+                    format!("{}: no unit cost given and no last cost from this supplier", name),
+                );
+            }
+        }
+    }
+
+    match insert_order(&mut tx, &payload, location.id, &unit_costs, auth.user.id).await {
+        Ok(order_id) => finish(tx, order_id, "Draft purchase order created").await,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            MyBaseResponse::db_err(e)
+        }
+    }
+}
+
+/// Writes a draft order and its lines, costed at `unit_costs` in line order.
+async fn insert_order(
+    tx: &mut Transaction<'_, Postgres>,
+    payload: &AddPurchaseOrderSchema,
+    location_id: uuid::Uuid,
+    unit_costs: &[Money],
+    created_by: uuid::Uuid,
+) -> Result<uuid::Uuid, sqlx::Error> {
+    let order_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO purchase_orders (supplier_id, location_id, expected_date, note, created_by)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id
+        "#,
+        payload.supplier_id,
+        location_id,
+        payload.expected_date,
+        payload.note,
+        created_by,
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    let product_ids: Vec<uuid::Uuid> = payload.lines.iter().map(|l| l.product_id).collect();
+    let quantities: Vec<i32> = payload.lines.iter().map(|l| l.quantity).collect();
+    sqlx::query!(
+        r#"
+        INSERT INTO purchase_order_lines (purchase_order_id, product_id, quantity, unit_cost)
+        SELECT $1, l.product_id, l.quantity, l.unit_cost
+        FROM UNNEST($2::UUID[], $3::INTEGER[], $4::NUMERIC[]) AS l(product_id, quantity, unit_cost)
+        "#,
+        order_id,
+        &product_ids,
+        &quantities,
+        unit_costs,
+    )
+    .execute(&mut **tx)
+    .await?;
+    Ok(order_id)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/purchase-orders/suggestions",
+    tag = "Purchasing",
+    params(
+        PurchaseSuggestionsQuery
+    ),
+    responses(
+        (status = 200, description = "Suggested orders for low-stock products, by preferred supplier", body = MyBaseResponse<Vec<PurchaseSuggestionModel>>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn get_purchase_suggestions_handler(
+    Query(params): Query<PurchaseSuggestionsQuery>,
+    State(app_state): State<AppState>,
+) -> MyBaseResponse<Vec<PurchaseSuggestionModel>> {
+    match low_stock_products(&app_state.db, params.location_id).await {
+        Ok(products) => MyBaseResponse::ok(
+            Some(PurchaseSuggestionModel::group(products)),
+            Some("Purchase suggestions generated successfully".into()),
+        ),
+        Err(e) => MyBaseResponse::db_err(e),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/purchase-orders/suggestions/draft",
+    tag = "Purchasing",
+    request_body = DraftSuggestedOrdersSchema,
+    responses(
+        (status = 200, description = "One draft purchase order per preferred supplier", body = MyBaseResponse<Vec<PurchaseOrderWithLinesModel>>),
+        (status = 404, description = "Location not found, or nothing to reorder from a preferred supplier", body = MyBaseResponse<Vec<PurchaseOrderWithLinesModel>>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn draft_suggested_orders_handler(
+    auth: JWTAuthMiddleware,
+    Json(payload): Json<DraftSuggestedOrdersSchema>,
+    State(app_state): State<AppState>,
+) -> MyBaseResponse<Vec<PurchaseOrderWithLinesModel>> {
+    let mut tx = match app_state.db.begin().await {
+        Ok(t) => t,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let location = match resolve_location(&mut *tx, payload.location_id).await {
+        Ok(l) => l,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return e.cast();
+        }
+    };
+    let products = match low_stock_products(&mut *tx, payload.location_id).await {
+        Ok(p) => p,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+
+    // Open drafts count as on order, so running this again does not order twice.
+    let mut order_ids = Vec::new();
+    let mut unassigned = 0;
+    for suggestion in PurchaseSuggestionModel::group(products) {
+        let Some(supplier_id) = suggestion.supplier_id else {
+            unassigned += suggestion.lines.len();
+            continue;
+        };
+        let order = AddPurchaseOrderSchema {
+            supplier_id,
+            location_id: Some(location.id),
+            expected_date: payload.expected_date,
+            note: payload.note.clone(),
+            lines: suggestion
+                .lines
+                .iter()
+                .map(|l| PurchaseOrderLineSchema {
+                    product_id: l.product_id,
+                    quantity: l.suggested_quantity,
+                    unit_cost: Some(l.unit_cost),
+                })
+                .collect(),
+        };
+        let unit_costs: Vec<Money> = suggestion.lines.iter().map(|l| l.unit_cost).collect();
+        match insert_order(&mut tx, &order, location.id, &unit_costs, auth.user.id).await {
+            Ok(id) => order_ids.push(id),
+            Err(e) => {
+                let _ = tx.rollback().await;
+                return MyBaseResponse::db_err(e);
+            }
+        }
+    }
+    if order_ids.is_empty() {
+        let _ = tx.rollback().await;
+        return MyBaseResponse::error(
+            404,
+            match unassigned {
+                0 => "No products need reordering".to_string(),
+                n => format!("{} products need reordering but have no preferred supplier", n),
+            },
+        );
+    }
+
+    let mut orders = Vec::with_capacity(order_ids.len());
+    for id in &order_ids {
+        match load_order(&mut *tx, *id).await {
+            Ok(Some(o)) => orders.push(o),
+            Ok(None) => {
+                let _ = tx.rollback().await;
+                return MyBaseResponse::error(404, "Purchase order not found");
+            }
+            Err(e) => {
+                let _ = tx.rollback().await;
+                return MyBaseResponse::db_err(e);
+            }
+        }
+    }
+    let lines = match load_lines(&mut *tx, &order_ids).await {
+        Ok(l) => l,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+    if let Err(e) = tx.commit().await {
+        return MyBaseResponse::db_err(e);
+    }
+
+    let mut message = format!("{} draft purchase orders created", orders.len());
+    if unassigned > 0 {
+        message.push_str(&format!(
+            "; {} products have no preferred supplier",
+            unassigned
+        ));
+    }
+    MyBaseResponse::ok(Some(with_lines(orders, lines, Vec::new())), Some(message))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/purchase-orders/send",
+    tag = "Purchasing",
+    request_body = PurchaseOrderIdSchema,
+    responses(
+        (status = 200, description = "Purchase order sent to the supplier", body = MyBaseResponse<PurchaseOrderWithLinesModel>),
+        (status = 404, description = "Purchase order not found", body = MyBaseResponse<PurchaseOrderWithLinesModel>),
+        (status = 409, description = "Purchase order is not a draft", body = MyBaseResponse<PurchaseOrderWithLinesModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn send_purchase_order_handler(
+    auth: JWTAuthMiddleware,
+    Json(payload): Json<PurchaseOrderIdSchema>,
+    State(app_state): State<AppState>,
+) -> MyBaseResponse<PurchaseOrderWithLinesModel> {
+    let mut tx = match app_state.db.begin().await {
+        Ok(t) => t,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let order = match load_order(&mut *tx, payload.id).await {
+        Ok(Some(o)) => o,
+        Ok(None) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::error(404, "Purchase order not found");
+        }
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+    if let Err(e) = check_transition(&order, PurchaseOrderStatus::Sent) {
+        let _ = tx.rollback().await;
+        return e.cast();
+    }
+
+    let updated = sqlx::query!(
+        r#"
+        UPDATE purchase_orders
+        SET status = 'sent', sent_by = $2, sent_at = now()
+        WHERE id = $1
+        "#,
+        order.id,
+        auth.user.id,
+    )
+    .execute(&mut *tx)
+    .await;
+    if let Err(e) = updated {
+        let _ = tx.rollback().await;
+        return MyBaseResponse::db_err(e);
+    }
+
+    finish(tx, order.id, "Purchase order sent").await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/purchase-orders/receive",
+    tag = "Purchasing",
+    request_body = ReceivePurchaseOrderSchema,
+    responses(
+        (status = 200, description = "Goods received into stock; the order closes once every line is in", body = MyBaseResponse<PurchaseOrderWithLinesModel>),
+        (status = 400, description = "Invalid received quantities or lot", body = MyBaseResponse<PurchaseOrderWithLinesModel>),
+        (status = 404, description = "Purchase order not found", body = MyBaseResponse<PurchaseOrderWithLinesModel>),
+        (status = 409, description = "Purchase order is not open for receiving", body = MyBaseResponse<PurchaseOrderWithLinesModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn receive_purchase_order_handler(
+    auth: JWTAuthMiddleware,
+    Json(payload): Json<ReceivePurchaseOrderSchema>,
+    State(app_state): State<AppState>,
+) -> MyBaseResponse<PurchaseOrderWithLinesModel> {
+    let mut tx = match app_state.db.begin().await {
+        Ok(t) => t,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let order = match load_order(&mut *tx, payload.id).await {
+        Ok(Some(o)) => o,
+        Ok(None) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::error(404, "Purchase order not found");
+        }
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+    if !order.status.can_receive() {
+        let _ = tx.rollback().await;
+        return MyBaseResponse::error(
+            409,
+            format!("Purchase order is {}; only a sent order can be received", order.status),
+        );
+    }
+
+    let lines = match load_lines(&mut *tx, &[order.id]).await {
+        Ok(l) => l,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+
+    let entries: Vec<ReceivePurchaseOrderLineSchema> = match payload.lines {
+        Some(entries) => entries,
+        None => lines
+            .iter()
+            .filter(|l| l.outstanding > 0)
+            .map(|l| ReceivePurchaseOrderLineSchema {
+                line_id: l.id,
+                quantity: l.outstanding,
+                unit_cost: None,
+                lot_number: None,
+                expiry_date: None,
+            })
+            .collect(),
+    };
+    if entries.is_empty() {
+        let _ = tx.rollback().await;
+        return MyBaseResponse::error(400, "Nothing to receive");
+    }
+
+    let today = chrono::Utc::now().date_naive();
+    let mut seen = HashSet::new();
+    for entry in &entries {
+        let Some(line) = lines.iter().find(|l| l.id == entry.line_id) else {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::error(
+                400,
+                format!("Line {} is not on this purchase order", entry.line_id),
+            );
+        };
+        if !seen.insert(entry.line_id) {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::error(
+                400,
+                format!("Line {} is listed more than once", entry.line_id),
+            );
+        }
+        if entry.quantity <= 0 || entry.quantity > line.outstanding {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::error(
+                400,
+                format!(
+                    "{}: received quantity must be between 1 and the {} outstanding",
+                    line.product_name, line.outstanding
+                ),
+            );
+        }
+        if entry.unit_cost.is_some_and(|c| c.is_sign_negative()) {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::error(400, "Unit cost cannot be negative");
+        }
+        match (&entry.lot_number, entry.expiry_date) {
+            (Some(lot_number), Some(expiry_date)) => {
+                if lot_number.trim().is_empty() {
+                    let _ = tx.rollback().await;
+                    return MyBaseResponse::error(400, "Lot number is required");
+                }
+                if expiry_date <= today {
+                    let _ = tx.rollback().await;
+                    return MyBaseResponse::error(
+                        400,
+                        format!("{}: lot has already expired", line.product_name),
+                    );
+                }
+            }
+            (None, None) => {}
+            _ => {
+                let _ = tx.rollback().await;
+                return MyBaseResponse::error(
+                    400,
+                    "Lot number and expiry date must be given together",
+                );
+            }
+        }
+    }
+
+    let receipt_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO purchase_receipts (purchase_order_id, note, received_by)
+        VALUES ($1, $2, $3)
+        RETURNING id
+        "#,
+        order.id,
+        payload.note,
+        auth.user.id,
+    )
+    .fetch_one(&mut *tx)
+    .await;
+    let receipt_id = match receipt_id {
+        Ok(id) => id,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+
+    for entry in &entries {
+        let Some(line) = lines.iter().find(|l| l.id == entry.line_id) else {
+            continue;
+        };
+        let unit_cost = entry.unit_cost.map(round_money).unwrap_or(line.unit_cost);
+
+        // A lot that already exists at the location is topped up, as long as
+        // the expiry matches.
+        let lot_id = match (&entry.lot_number, entry.expiry_date) {
+            (Some(lot_number), Some(expiry_date)) => {
+                let lot = sqlx::query_scalar!(
+                    r#"
+                    INSERT INTO product_lots (product_id, location_id, lot_number, expiry_date, unit_cost)
+                    VALUES ($1, $2, $3, $4, $5)
+                    ON CONFLICT (product_id, location_id, lot_number)
+                    DO UPDATE SET unit_cost = COALESCE(product_lots.unit_cost, EXCLUDED.unit_cost)
+                    WHERE product_lots.expiry_date = EXCLUDED.expiry_date
+                    RETURNING id
+                    "#,
+                    line.product_id,
+                    order.location_id,
+                    lot_number.trim(),
+                    expiry_date,
+                    unit_cost,
+                )
+                .fetch_optional(&mut *tx)
+                .await;
+                match lot {
+                    Ok(Some(id)) => Some(id),
+                    Ok(None) => {
+                        let _ = tx.rollback().await;
+                        return MyBaseResponse::error(
+                            400,
+                            format!(
+                                "{}: lot {} is already on hand with a different expiry date",
+                                line.product_name,
+                                lot_number.trim()
+                            ),
+                        );
+                    }
+                    Err(e) => {
+                        let _ = tx.rollback().await;
+                        return MyBaseResponse::db_err(e);
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        let movement = NewStockMovement {
+            product_id: line.product_id,
+            location_id: order.location_id,
+            lot_id,
+            reason: StockMovementReason::Receipt,
+            quantity: entry.quantity,
+            created_by: Some(auth.user.id),
+            reference_id: Some(receipt_id),
+            adjustment_reason: None,
+            note: None,
+            unit_cost: Some(unit_cost),
+        };
+        if let Err(e) = apply_stock_movement(&mut tx, &movement).await {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+
+        let recorded = sqlx::query!(
+            r#"
+            INSERT INTO purchase_receipt_lines (receipt_id, line_id, lot_id, quantity, unit_cost)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            receipt_id,
+            line.id,
+            lot_id,
+            entry.quantity,
+            unit_cost,
+        )
+        .execute(&mut *tx)
+        .await;
+        if let Err(e) = recorded {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+
+        let updated = sqlx::query!(
+            r#"
+            UPDATE purchase_order_lines
+            SET received_quantity = received_quantity + $2
+            WHERE id = $1
+            "#,
+            line.id,
+            entry.quantity,
+        )
+        .execute(&mut *tx)
+        .await;
+        if let Err(e) = updated {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+
+        // Keep the supplier link's last cost current for the next order.
+        let updated = sqlx::query!(
+            r#"
+            UPDATE product_suppliers SET last_cost = $3
+            WHERE product_id = $1 AND supplier_id = $2
+            "#,
+            line.product_id,
+            order.supplier_id,
+            unit_cost,
+        )
+        .execute(&mut *tx)
+        .await;
+        if let Err(e) = updated {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    }
+
+    let updated = sqlx::query!(
+        r#"
+        UPDATE purchase_orders po
+        SET status = CASE WHEN open.lines = 0 THEN 'closed' ELSE 'partially_received' END::purchase_order_status,
+            closed_at = CASE WHEN open.lines = 0 THEN now() END
+        FROM (
+            SELECT COUNT(*) AS lines
+            FROM purchase_order_lines
+            WHERE purchase_order_id = $1 AND received_quantity < quantity
+        ) open
+        WHERE po.id = $1
+        "#,
+        order.id,
+    )
+    .execute(&mut *tx)
+    .await;
+    if let Err(e) = updated {
+        let _ = tx.rollback().await;
+        return MyBaseResponse::db_err(e);
+    }
+
+    finish(tx, order.id, "Goods received").await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/purchase-orders/close",
+    tag = "Purchasing",
+    request_body = PurchaseOrderIdSchema,
+    responses(
+        (status = 200, description = "Partially received purchase order closed short", body = MyBaseResponse<PurchaseOrderWithLinesModel>),
+        (status = 404, description = "Purchase order not found", body = MyBaseResponse<PurchaseOrderWithLinesModel>),
+        (status = 409, description = "Purchase order is not partially received", body = MyBaseResponse<PurchaseOrderWithLinesModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn close_purchase_order_handler(
+    Json(payload): Json<PurchaseOrderIdSchema>,
+    State(app_state): State<AppState>,
+) -> MyBaseResponse<PurchaseOrderWithLinesModel> {
+    let mut tx = match app_state.db.begin().await {
+        Ok(t) => t,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let order = match load_order(&mut *tx, payload.id).await {
+        Ok(Some(o)) => o,
+        Ok(None) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::error(404, "Purchase order not found");
+        }
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+    // A sent order with nothing received is cancelled, not closed.
+    if order.status != PurchaseOrderStatus::PartiallyReceived {
+        let _ = tx.rollback().await;
+        return MyBaseResponse::error(
+            409,
+            format!(
+                "Purchase order is {}; only a partially received order can be closed short",
+                order.status
+            ),
+        );
+    }
+
+    let updated = sqlx::query!(
+        r#"UPDATE purchase_orders SET status = 'closed', closed_at = now() WHERE id = $1"#,
+        order.id,
+    )
+    .execute(&mut *tx)
+    .await;
+    if let Err(e) = updated {
+        let _ = tx.rollback().await;
+        return MyBaseResponse::db_err(e);
+    }
+
+    finish(tx, order.id, "Purchase order closed").await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/purchase-orders/cancel",
+    tag = "Purchasing",
+    request_body = PurchaseOrderIdSchema,
+    responses(
+        (status = 200, description = "Purchase order cancelled", body = MyBaseResponse<PurchaseOrderWithLinesModel>),
+        (status = 404, description = "Purchase order not found", body = MyBaseResponse<PurchaseOrderWithLinesModel>),
+        (status = 409, description = "Goods have already been received against the order", body = MyBaseResponse<PurchaseOrderWithLinesModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn cancel_purchase_order_handler(
+    Json(payload): Json<PurchaseOrderIdSchema>,
+    State(app_state): State<AppState>,
+) -> MyBaseResponse<PurchaseOrderWithLinesModel> {
+    let mut tx = match app_state.db.begin().await {
+        Ok(t) => t,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let order = match load_order(&mut *tx, payload.id).await {
+        Ok(Some(o)) => o,
+        Ok(None) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::error(404, "Purchase order not found");
+        }
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+    if let Err(e) = check_transition(&order, PurchaseOrderStatus::Cancelled) {
+        let _ = tx.rollback().await;
+        return e.cast();
+    }
+
+    let updated = sqlx::query!(
+        r#"UPDATE purchase_orders SET status = 'cancelled' WHERE id = $1"#,
+        order.id,
+    )
+    .execute(&mut *tx)
+    .await;
+    if let Err(e) = updated {
+        let _ = tx.rollback().await;
+        return MyBaseResponse::db_err(e);
+    }
+
+    finish(tx, order.id, "Purchase order cancelled").await
+}
+
+/// Reads the order back with its lines and receipts and commits.
+async fn finish(
+    mut tx: sqlx::Transaction<'_, sqlx::Postgres>,
+    order_id: uuid::Uuid,
+    message: &str,
+) -> MyBaseResponse<PurchaseOrderWithLinesModel> {
+    let order = match load_order(&mut *tx, order_id).await {
+        Ok(Some(o)) => o,
+        Ok(None) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::error(404, "Purchase order not found");
+        }
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+    let lines = match load_lines(&mut *tx, &[order_id]).await {
+        Ok(l) => l,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+    let receipts = match load_receipts(&mut *tx, &[order_id]).await {
+        Ok(r) => r,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+
+    if let Err(e) = tx.commit().await {
+        return MyBaseResponse::db_err(e);
+    }
+    let order = with_lines(vec![order], lines, receipts).remove(0);
+    MyBaseResponse::ok(Some(order), Some(message.into()))
+}
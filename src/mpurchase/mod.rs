@@ -0,0 +1,4 @@
+pub mod handlers;
+pub mod models;
+pub mod routes;
+pub mod schema;
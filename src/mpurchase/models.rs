@@ -0,0 +1,205 @@
+use std::fmt;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+
+use crate::mstock::models::LowStockProductModel;
+use crate::util::money::{Money, round_money};
+
+/// Where a purchase order is in its life: drafted, sent to the supplier, then
+/// received in one or more deliveries until it is closed.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, sqlx::Type, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "purchase_order_status", rename_all = "snake_case")]
+pub enum PurchaseOrderStatus {
+    Draft,
+    Sent,
+    PartiallyReceived,
+    Closed,
+    Cancelled,
+}
+
+impl fmt::Display for PurchaseOrderStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            PurchaseOrderStatus::Draft => "draft",
+            PurchaseOrderStatus::Sent => "sent",
+            PurchaseOrderStatus::PartiallyReceived => "partially_received",
+            PurchaseOrderStatus::Closed => "closed",
+            PurchaseOrderStatus::Cancelled => "cancelled",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl PurchaseOrderStatus {
+    /// States a purchase order may move to from this one. Closed and cancelled are terminal.
+    pub fn allowed_next(&self) -> Vec<PurchaseOrderStatus> {
+        match self {
+            PurchaseOrderStatus::Draft => {
+                vec![PurchaseOrderStatus::Sent, PurchaseOrderStatus::Cancelled]
+            }
+            PurchaseOrderStatus::Sent => vec![
+                PurchaseOrderStatus::PartiallyReceived,
+                PurchaseOrderStatus::Closed,
+                PurchaseOrderStatus::Cancelled,
+            ],
+            PurchaseOrderStatus::PartiallyReceived => vec![PurchaseOrderStatus::Closed],
+            PurchaseOrderStatus::Closed | PurchaseOrderStatus::Cancelled => vec![],
+        }
+    }
+
+    pub fn can_transition_to(&self, next: &PurchaseOrderStatus) -> bool {
+        self.allowed_next().contains(next)
+    }
+
+    /// Goods can be received against a sent order until it is closed.
+    pub fn can_receive(&self) -> bool {
+        matches!(
+            self,
+            PurchaseOrderStatus::Sent | PurchaseOrderStatus::PartiallyReceived
+        )
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, FromRow, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct PurchaseOrderModel {
+    pub id: uuid::Uuid,
+    #[serde(rename = "poNumber")]
+    pub po_number: i64,
+    #[serde(rename = "supplierId")]
+    pub supplier_id: uuid::Uuid,
+    #[serde(rename = "supplierName")]
+    pub supplier_name: String,
+    #[serde(rename = "locationId")]
+    pub location_id: uuid::Uuid,
+    #[serde(rename = "locationName")]
+    pub location_name: String,
+    pub status: PurchaseOrderStatus,
+    #[serde(rename = "expectedDate")]
+    pub expected_date: Option<NaiveDate>,
+    pub note: Option<String>,
+    /// Sum of ordered quantity times unit cost over all lines.
+    #[serde(rename = "orderTotal")]
+    #[schema(value_type = String)]
+    pub order_total: Money,
+    #[serde(rename = "createdBy")]
+    pub created_by: Option<uuid::Uuid>,
+    #[serde(rename = "sentBy")]
+    pub sent_by: Option<uuid::Uuid>,
+    #[serde(rename = "sentAt")]
+    pub sent_at: Option<DateTime<Utc>>,
+    #[serde(rename = "closedAt")]
+    pub closed_at: Option<DateTime<Utc>>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, FromRow, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct PurchaseOrderLineModel {
+    pub id: i64,
+    #[serde(rename = "purchaseOrderId")]
+    pub purchase_order_id: uuid::Uuid,
+    #[serde(rename = "productId")]
+    pub product_id: uuid::Uuid,
+    #[serde(rename = "productName")]
+    pub product_name: String,
+    /// Base units ordered.
+    pub quantity: i32,
+    /// Agreed cost per base unit.
+    #[serde(rename = "unitCost")]
+    #[schema(value_type = String)]
+    pub unit_cost: Money,
+    #[serde(rename = "receivedQuantity")]
+    pub received_quantity: i32,
+    /// Base units still to arrive.
+    pub outstanding: i32,
+}
+
+/// One line of one delivery: what arrived, at what cost, and into which lot.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, FromRow, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct PurchaseReceiptLineModel {
+    pub id: i64,
+    #[serde(rename = "receiptId")]
+    pub receipt_id: uuid::Uuid,
+    #[serde(skip)]
+    pub purchase_order_id: uuid::Uuid,
+    #[serde(rename = "lineId")]
+    pub line_id: i64,
+    #[serde(rename = "productId")]
+    pub product_id: uuid::Uuid,
+    #[serde(rename = "productName")]
+    pub product_name: String,
+    #[serde(rename = "lotId")]
+    pub lot_id: Option<uuid::Uuid>,
+    #[serde(rename = "lotNumber")]
+    pub lot_number: Option<String>,
+    pub quantity: i32,
+    #[serde(rename = "unitCost")]
+    #[schema(value_type = String)]
+    pub unit_cost: Money,
+    pub note: Option<String>,
+    #[serde(rename = "receivedBy")]
+    pub received_by: Option<uuid::Uuid>,
+    #[serde(rename = "receivedAt")]
+    pub received_at: DateTime<Utc>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, ToSchema, PartialEq)]
+pub struct PurchaseOrderWithLinesModel {
+    #[serde(flatten)]
+    pub order: PurchaseOrderModel,
+    pub lines: Vec<PurchaseOrderLineModel>,
+    pub receipts: Vec<PurchaseReceiptLineModel>,
+}
+
+/// Low-stock products that one supplier is preferred for, as they would go on
+/// a draft order. Products without a preferred supplier form their own group.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct PurchaseSuggestionModel {
+    #[serde(rename = "supplierId")]
+    pub supplier_id: Option<uuid::Uuid>,
+    #[serde(rename = "supplierName")]
+    pub supplier_name: Option<String>,
+    /// Sum of suggested quantity times unit cost.
+    #[serde(rename = "orderTotal")]
+    #[schema(value_type = String)]
+    pub order_total: Money,
+    pub lines: Vec<LowStockProductModel>,
+}
+
+impl PurchaseSuggestionModel {
+    /// Groups products, already ordered by supplier, into one suggestion per
+    /// supplier. Products with nothing to order are left out.
+    pub fn group(products: Vec<LowStockProductModel>) -> Vec<Self> {
+        let mut suggestions: Vec<Self> = Vec::new();
+        for product in products {
+            if product.suggested_quantity <= 0 {
+                continue;
+            }
+            let entry = match suggestions.last_mut() {
+                Some(s) if s.supplier_id == product.supplier_id => s,
+                _ => {
+                    suggestions.push(Self {
+                        supplier_id: product.supplier_id,
+                        supplier_name: product.supplier_name.clone(),
+                        order_total: Money::ZERO,
+                        lines: Vec::new(),
+                    });
+                    suggestions.last_mut().unwrap()
+                }
+            };
+            entry.order_total +=
+                round_money(product.unit_cost * Money::from(product.suggested_quantity));
+            entry.lines.push(product);
+        }
+        suggestions
+    }
+}
@@ -0,0 +1,170 @@
+use axum::{
+    Extension, Router,
+    extract::State,
+    routing::{get, post},
+};
+
+use crate::{
+    AppState,
+    mauth::{
+        layers::{MyAuthLayer, MyAuthPermsLayer},
+        middlewares::JWTAuthMiddleware,
+    },
+    mpurchase::{
+        self,
+        schema::{
+            AddPurchaseOrderSchema, DraftSuggestedOrdersSchema, GetPurchaseOrdersSchema,
+            PurchaseOrderIdSchema, PurchaseSuggestionsQuery, ReceivePurchaseOrderSchema,
+        },
+    },
+};
+
+pub fn create_purchase_router(app: AppState) -> Router {
+    return Router::new()
+        .route(
+            "/get",
+            get(
+                |pool: axum::extract::State<AppState>,
+                 params: axum::extract::Query<GetPurchaseOrdersSchema>| async move {
+                    let state = AppState {
+                        db: pool.0.db,
+                        env: pool.0.env,
+                    };
+                    return mpurchase::handlers::get_purchase_orders_handler(params, State(state))
+                        .await;
+                },
+            ),
+        )
+        .route(
+            "/add",
+            post(
+                |pool: axum::extract::State<AppState>,
+                 Extension(auth): Extension<JWTAuthMiddleware>,
+                 payload: axum::extract::Json<AddPurchaseOrderSchema>| async move {
+                    let state = AppState {
+                        db: pool.0.db,
+                        env: pool.0.env,
+                    };
+                    return mpurchase::handlers::add_purchase_order_handler(
+                        auth,
+                        payload,
+                        State(state),
+                    )
+                    .await;
+                },
+            )
+            .layer(MyAuthPermsLayer {}),
+        )
+        .route(
+            "/suggestions",
+            get(
+                |pool: axum::extract::State<AppState>,
+                 params: axum::extract::Query<PurchaseSuggestionsQuery>| async move {
+                    let state = AppState {
+                        db: pool.0.db,
+                        env: pool.0.env,
+                    };
+                    return mpurchase::handlers::get_purchase_suggestions_handler(
+                        params,
+                        State(state),
+                    )
+                    .await;
+                },
+            ),
+        )
+        .route(
+            "/suggestions/draft",
+            post(
+                |pool: axum::extract::State<AppState>,
+                 Extension(auth): Extension<JWTAuthMiddleware>,
+                 payload: axum::extract::Json<DraftSuggestedOrdersSchema>| async move {
+                    let state = AppState {
+                        db: pool.0.db,
+                        env: pool.0.env,
+                    };
+                    return mpurchase::handlers::draft_suggested_orders_handler(
+                        auth,
+                        payload,
+                        State(state),
+                    )
+                    .await;
+                },
+            )
+            .layer(MyAuthPermsLayer {}),
+        )
+        .route(
+            "/send",
+            post(
+                |pool: axum::extract::State<AppState>,
+                 Extension(auth): Extension<JWTAuthMiddleware>,
+                 payload: axum::extract::Json<PurchaseOrderIdSchema>| async move {
+                    let state = AppState {
+                        db: pool.0.db,
+                        env: pool.0.env,
+                    };
+                    return mpurchase::handlers::send_purchase_order_handler(
+                        auth,
+                        payload,
+                        State(state),
+                    )
+                    .await;
+                },
+            )
+            .layer(MyAuthPermsLayer {}),
+        )
+        .route(
+            "/receive",
+            post(
+                |pool: axum::extract::State<AppState>,
+                 Extension(auth): Extension<JWTAuthMiddleware>,
+                 payload: axum::extract::Json<ReceivePurchaseOrderSchema>| async move {
+                    let state = AppState {
+                        db: pool.0.db,
+                        env: pool.0.env,
+                    };
+                    return mpurchase::handlers::receive_purchase_order_handler(
+                        auth,
+                        payload,
+                        State(state),
+                    )
+                    .await;
+                },
+            )
+            .layer(MyAuthPermsLayer {}),
+        )
+        .route(
+            "/close",
+            post(
+                |pool: axum::extract::State<AppState>,
+                 payload: axum::extract::Json<PurchaseOrderIdSchema>| async move {
+                    let state = AppState {
+                        db: pool.0.db,
+                        env: pool.0.env,
+                    };
+                    return mpurchase::handlers::close_purchase_order_handler(payload, State(state))
+                        .await;
+                },
+            )
+            .layer(MyAuthPermsLayer {}),
+        )
+        .route(
+            "/cancel",
+            post(
+                |pool: axum::extract::State<AppState>,
+                 payload: axum::extract::Json<PurchaseOrderIdSchema>| async move {
+                    let state = AppState {
+                        db: pool.0.db,
+                        env: pool.0.env,
+                    };
+                    return mpurchase::handlers::cancel_purchase_order_handler(
+                        payload,
+                        State(state),
+                    )
+                    .await;
+                },
+            )
+            .layer(MyAuthPermsLayer {}),
+        )
+        .layer(MyAuthLayer { state: app.clone() })
+        .with_state(app);
+}
@@ -0,0 +1,72 @@
+use chrono::NaiveDate;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::mpurchase::models::PurchaseOrderStatus;
+use crate::util::money::Money;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct PurchaseOrderLineSchema {
+    pub product_id: uuid::Uuid,
+    /// Base units to order.
+    pub quantity: i32,
+    /// Cost per base unit. Defaults to the supplier's last cost for the product.
+    #[schema(value_type = Option<String>)]
+    pub unit_cost: Option<Money>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct AddPurchaseOrderSchema {
+    pub supplier_id: uuid::Uuid,
+    /// Where the goods will be received. Defaults to the default location.
+    pub location_id: Option<uuid::Uuid>,
+    pub expected_date: Option<NaiveDate>,
+    pub note: Option<String>,
+    pub lines: Vec<PurchaseOrderLineSchema>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct PurchaseOrderIdSchema {
+    pub id: uuid::Uuid,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct ReceivePurchaseOrderLineSchema {
+    pub line_id: i64,
+    /// Base units that arrived, at most what is still outstanding on the line.
+    pub quantity: i32,
+    /// Invoiced cost per base unit. Defaults to the cost on the order.
+    #[schema(value_type = Option<String>)]
+    pub unit_cost: Option<Money>,
+    /// Receive into this lot. Requires `expiry_date`.
+    pub lot_number: Option<String>,
+    pub expiry_date: Option<NaiveDate>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct ReceivePurchaseOrderSchema {
+    pub id: uuid::Uuid,
+    pub note: Option<String>,
+    /// What arrived in this delivery. Left out, everything outstanding is received.
+    pub lines: Option<Vec<ReceivePurchaseOrderLineSchema>>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, ToSchema, IntoParams, PartialEq)]
+pub struct GetPurchaseOrdersSchema {
+    pub status: Option<PurchaseOrderStatus>,
+    pub supplier_id: Option<uuid::Uuid>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, ToSchema, IntoParams, PartialEq)]
+pub struct PurchaseSuggestionsQuery {
+    /// Compare stock at this location with the reorder level; total stock when empty.
+    pub location_id: Option<uuid::Uuid>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct DraftSuggestedOrdersSchema {
+    /// Reorder for this location and deliver there. When empty, total stock is
+    /// compared and orders are delivered to the default location.
+    pub location_id: Option<uuid::Uuid>,
+    pub expected_date: Option<NaiveDate>,
+    pub note: Option<String>,
+}
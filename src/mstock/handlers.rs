@@ -0,0 +1,587 @@
+use std::collections::HashMap;
+
+use crate::AppState;
+use crate::mauth::middlewares::JWTAuthMiddleware;
+use crate::mproduct::models::ProductModel;
+use crate::mstock::models::{
+    CartItemAllocation, CostBasis, CostLayer, CostingMethod, CostingSettingsModel,
+    ExpiredWriteOffModel, ExpiringLotModel, ExpiryReportModel, ExpiryReportProductModel,
+    LotBalance, LowStockProductModel, NewStockMovement, ProductValuationModel, StockAdjustmentReason,
+    StockHistoryModel, StockMovementModel, StockMovementReason, StockMovementSource,
+    StockValuationModel,
+};
+use crate::mstock::schemas::{
+    ExpiryReportQuery, LowStockQuery, SetCostingMethodSchema, StockValuationQuery,
+    WriteOffExpiredSchema,
+};
+use crate::mstock::sql_string::StockSQLString;
+use crate::shared_var::MyBaseResponse;
+use crate::util::money::{Money, round_cost, round_money};
+
+use sqlx::{PgExecutor, Postgres, Transaction, query_as};
+
+/// Changes product stock at a location and writes the ledger row in the same
+/// statement. Returns `false` when a decrease would take stock below zero.
+pub async fn apply_stock_movement(
+    tx: &mut Transaction<'_, Postgres>,
+    movement: &NewStockMovement,
+) -> Result<bool, sqlx::Error> {
+    Ok(apply_costed_stock_movement(tx, movement).await?.is_some())
+}
+
+/// Applies a movement like [`apply_stock_movement`] and books it at a cost.
+/// Incoming stock opens a cost layer at its location and moves the average cost;
+/// outgoing stock uses up the oldest layers at its location and is costed by the
+/// costing method. Returns the
+/// cost per base unit, or `None` when a decrease would take stock below zero.
+pub async fn apply_costed_stock_movement(
+    tx: &mut Transaction<'_, Postgres>,
+    movement: &NewStockMovement,
+) -> Result<Option<Money>, sqlx::Error> {
+    let Some(basis) = query_as::<_, CostBasis>(StockSQLString::LOCK_PRODUCT_COST)
+        .bind(movement.product_id)
+        .fetch_optional(&mut **tx)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let mut layer_takes: Vec<(i64, i32)> = Vec::new();
+    let unit_cost = if movement.quantity >= 0 {
+        round_cost(movement.unit_cost.unwrap_or(basis.average_cost))
+    } else {
+        let needed = -movement.quantity;
+        let layers = query_as::<_, CostLayer>(StockSQLString::LOCK_OPEN_COST_LAYERS)
+            .bind(movement.product_id)
+            .bind(movement.location_id)
+            .fetch_all(&mut **tx)
+            .await?;
+        let mut remaining = needed;
+        let mut value = Money::ZERO;
+        for layer in layers {
+            if remaining == 0 {
+                break;
+            }
+            let take = remaining.min(layer.remaining);
+            value += layer.unit_cost * Money::from(take);
+            layer_takes.push((layer.id, take));
+            remaining -= take;
+        }
+        // Stock with no layer left (e.g. counted before costing) goes at the average.
+        value += basis.average_cost * Money::from(remaining);
+        match basis.costing_method {
+            CostingMethod::Fifo => round_cost(value / Money::from(needed)),
+            CostingMethod::WeightedAverage => basis.average_cost,
+        }
+    };
+
+    if movement.quantity > 0 {
+        sqlx::query(StockSQLString::ENSURE_PRODUCT_STOCK)
+            .bind(movement.product_id)
+            .bind(movement.location_id)
+            .execute(&mut **tx)
+            .await?;
+    }
+    let Some(movement_id) = sqlx::query_scalar::<_, i64>(StockSQLString::APPLY_STOCK_MOVEMENT)
+        .bind(movement.product_id)
+        .bind(movement.quantity)
+        .bind(&movement.reason)
+        .bind(movement.created_by)
+        .bind(movement.reference_id)
+        .bind(&movement.adjustment_reason)
+        .bind(&movement.note)
+        .bind(movement.lot_id)
+        .bind(movement.location_id)
+        .bind(unit_cost)
+        .fetch_optional(&mut **tx)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    if movement.quantity > 0 {
+        sqlx::query(StockSQLString::UPDATE_AVERAGE_COST)
+            .bind(movement.product_id)
+            .bind(basis.quantity)
+            .bind(movement.quantity)
+            .bind(unit_cost)
+            .execute(&mut **tx)
+            .await?;
+        sqlx::query(StockSQLString::INSERT_COST_LAYER)
+            .bind(movement.product_id)
+            .bind(movement.location_id)
+            .bind(movement_id)
+            .bind(unit_cost)
+            .bind(movement.quantity)
+            .execute(&mut **tx)
+            .await?;
+    }
+    for (layer_id, take) in layer_takes {
+        sqlx::query(StockSQLString::CONSUME_COST_LAYER)
+            .bind(layer_id)
+            .bind(take)
+            .execute(&mut **tx)
+            .await?;
+    }
+    Ok(Some(unit_cost))
+}
+
+/// Plans where `quantity` base units of sellable stock come from at one location:
+/// earliest-expiring lot first, unlotted stock last, never an expired lot. Each
+/// take is a lot (`None` for unlotted) and a quantity. Returns `None` when the
+/// location does not have that much sellable stock. The product and its lots
+/// stay locked until the transaction ends.
+pub async fn plan_fefo_takes(
+    tx: &mut Transaction<'_, Postgres>,
+    product_id: uuid::Uuid,
+    location_id: uuid::Uuid,
+    quantity: i32,
+) -> Result<Option<Vec<(Option<uuid::Uuid>, i32)>>, sqlx::Error> {
+    let Some(on_hand) = sqlx::query_scalar::<_, i32>(StockSQLString::LOCK_LOCATION_QUANTITY)
+        .bind(product_id)
+        .bind(location_id)
+        .fetch_optional(&mut **tx)
+        .await?
+    else {
+        return Ok(None);
+    };
+    let lots = query_as::<_, LotBalance>(StockSQLString::LOCK_PRODUCT_LOTS)
+        .bind(product_id)
+        .bind(location_id)
+        .fetch_all(&mut **tx)
+        .await?;
+
+    let unlotted = on_hand - lots.iter().map(|l| l.quantity).sum::<i32>();
+    let sellable: i32 = unlotted
+        + lots
+            .iter()
+            .filter(|l| !l.expired)
+            .map(|l| l.quantity)
+            .sum::<i32>();
+    if sellable < quantity {
+        return Ok(None);
+    }
+
+    let mut takes: Vec<(Option<uuid::Uuid>, i32)> = Vec::new();
+    let mut remaining = quantity;
+    for lot in lots.iter().filter(|l| !l.expired && l.quantity > 0) {
+        if remaining == 0 {
+            break;
+        }
+        let take = remaining.min(lot.quantity);
+        takes.push((Some(lot.id), take));
+        remaining -= take;
+    }
+    if remaining > 0 {
+        takes.push((None, remaining));
+    }
+    Ok(Some(takes))
+}
+
+/// Takes `quantity` base units for a cart line from one location in FEFO order.
+/// `units_per_item` is how many of them one base unit of the line holds.
+/// Returns `false` when the location does not have that much sellable stock.
+pub async fn allocate_cart_stock(
+    tx: &mut Transaction<'_, Postgres>,
+    cart_item_id: i64,
+    product_id: uuid::Uuid,
+    location_id: uuid::Uuid,
+    quantity: i32,
+    units_per_item: i32,
+    source: &StockMovementSource,
+) -> Result<bool, sqlx::Error> {
+    let Some(takes) = plan_fefo_takes(tx, product_id, location_id, quantity).await? else {
+        return Ok(false);
+    };
+
+    for (lot_id, take) in takes {
+        let movement = NewStockMovement {
+            product_id,
+            location_id,
+            lot_id,
+            reason: source.reason.clone(),
+            quantity: -take,
+            created_by: source.created_by,
+            reference_id: source.reference_id,
+            adjustment_reason: None,
+            note: None,
+            unit_cost: None,
+        };
+        let Some(unit_cost) = apply_costed_stock_movement(tx, &movement).await? else {
+            return Ok(false);
+        };
+        sqlx::query(StockSQLString::ADD_CART_ITEM_ALLOCATION)
+            .bind(cart_item_id)
+            .bind(product_id)
+            .bind(lot_id)
+            .bind(take)
+            .bind(unit_cost * Money::from(take))
+            .bind(units_per_item)
+            .execute(&mut **tx)
+            .await?;
+    }
+    Ok(true)
+}
+
+/// Returns stock held by a cart line to the lots it came from, unlotted stock
+/// first and then the latest-expiring lot, at the cost it was taken at.
+/// `quantity` is in base units of the line, so each product the line holds gets
+/// back its own share; `None` releases everything held.
+pub async fn release_cart_stock(
+    tx: &mut Transaction<'_, Postgres>,
+    cart_item_id: i64,
+    quantity: Option<i32>,
+    source: &StockMovementSource,
+) -> Result<(), sqlx::Error> {
+    let allocations = query_as::<_, CartItemAllocation>(StockSQLString::LOCK_CART_ITEM_ALLOCATIONS)
+        .bind(cart_item_id)
+        .fetch_all(&mut **tx)
+        .await?;
+
+    let mut remaining: HashMap<uuid::Uuid, i32> = HashMap::new();
+    for allocation in allocations {
+        let remaining = remaining.entry(allocation.product_id).or_insert_with(|| {
+            quantity.map_or(i32::MAX, |q| q.saturating_mul(allocation.units_per_item))
+        });
+        if *remaining == 0 {
+            continue;
+        }
+        let take = (*remaining).min(allocation.quantity);
+        let unit_cost = round_cost(allocation.cost_value / Money::from(allocation.quantity));
+        let movement = NewStockMovement {
+            product_id: allocation.product_id,
+            location_id: allocation.location_id,
+            lot_id: allocation.lot_id,
+            reason: source.reason.clone(),
+            quantity: take,
+            created_by: source.created_by,
+            reference_id: source.reference_id,
+            adjustment_reason: None,
+            note: None,
+            unit_cost: Some(unit_cost),
+        };
+        apply_stock_movement(tx, &movement).await?;
+
+        if take == allocation.quantity {
+            sqlx::query(StockSQLString::DELETE_CART_ITEM_ALLOCATION)
+                .bind(allocation.id)
+                .execute(&mut **tx)
+                .await?;
+        } else {
+            sqlx::query(StockSQLString::REDUCE_CART_ITEM_ALLOCATION)
+                .bind(allocation.id)
+                .bind(take)
+                .bind(unit_cost * Money::from(take))
+                .execute(&mut **tx)
+                .await?;
+        }
+        *remaining -= take;
+    }
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/stock/products/{id}/movements",
+    tag = "Stock",
+    params(
+        ("id" = uuid::Uuid, Path, description = "Product id")
+    ),
+    responses(
+        (status = 200, description = "Stock history rebuilt from the ledger", body = MyBaseResponse<StockHistoryModel>),
+        (status = 404, description = "Product not found", body = MyBaseResponse<StockHistoryModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn get_stock_history_handler(
+    product_id: uuid::Uuid,
+    state: AppState,
+) -> MyBaseResponse<StockHistoryModel> {
+    let product = match query_as!(
+        ProductModel,
+        r#"SELECT * FROM products WHERE id = $1"#,
+        product_id
+    )
+    .fetch_optional(&state.db)
+    .await
+    {
+        Ok(Some(p)) => p,
+        Ok(None) => return MyBaseResponse::error(404, "Product not found"),
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let movements = match query_as::<_, StockMovementModel>(StockSQLString::GET_PRODUCT_MOVEMENTS)
+        .bind(product_id)
+        .fetch_all(&state.db)
+        .await
+    {
+        Ok(m) => m,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let ledger_quantity = movements.last().map_or(0, |m| m.balance_after);
+    let history = StockHistoryModel {
+        product_id: product.id,
+        product_name: product.name,
+        quantity: product.quantity,
+        ledger_quantity,
+        movements,
+    };
+    MyBaseResponse::ok(Some(history), Some("Stock history retrieved successfully".into()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/stock/expiry-report",
+    tag = "Stock",
+    params(
+        ExpiryReportQuery
+    ),
+    responses(
+        (status = 200, description = "Expired and near-expiry stock by product", body = MyBaseResponse<ExpiryReportModel>),
+        (status = 400, description = "Invalid number of days", body = MyBaseResponse<ExpiryReportModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn get_expiry_report_handler(
+    params: ExpiryReportQuery,
+    state: AppState,
+) -> MyBaseResponse<ExpiryReportModel> {
+    let days = params.days.unwrap_or(30);
+    if !(0..=3650).contains(&days) {
+        return MyBaseResponse::error(400, "days must be between 0 and 3650");
+    }
+
+    let lots = match query_as::<_, ExpiringLotModel>(StockSQLString::GET_EXPIRING_LOTS)
+        .bind(days)
+        .bind(params.location_id)
+        .fetch_all(&state.db)
+        .await
+    {
+        Ok(l) => l,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let products = ExpiryReportProductModel::group(lots);
+    let as_of = chrono::Utc::now().date_naive();
+    let report = ExpiryReportModel {
+        as_of,
+        through: as_of + chrono::Days::new(days as u64),
+        quantity: products.iter().map(|p| p.quantity).sum(),
+        value_at_cost: products.iter().map(|p| p.value_at_cost).sum(),
+        products,
+    };
+    MyBaseResponse::ok(Some(report), Some("Expiry report generated successfully".into()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/stock/write-off-expired",
+    tag = "Stock",
+    request_body = WriteOffExpiredSchema,
+    responses(
+        (status = 200, description = "Expired lots written off", body = MyBaseResponse<ExpiredWriteOffModel>),
+        (status = 404, description = "No expired stock to write off", body = MyBaseResponse<ExpiredWriteOffModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn write_off_expired_handler(
+    auth: JWTAuthMiddleware,
+    payload: WriteOffExpiredSchema,
+    state: AppState,
+) -> MyBaseResponse<ExpiredWriteOffModel> {
+    let mut tx = match state.db.begin().await {
+        Ok(t) => t,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let lots = match query_as::<_, ExpiringLotModel>(StockSQLString::LOCK_EXPIRED_LOTS)
+        .bind(&payload.product_ids)
+        .bind(payload.location_id)
+        .fetch_all(&mut *tx)
+        .await
+    {
+        Ok(l) => l,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+    if lots.is_empty() {
+        let _ = tx.rollback().await;
+        return MyBaseResponse::error(404, "No expired stock to write off");
+    }
+
+    // One reference ties together every ledger row of this write-off.
+    let reference_id = uuid::Uuid::new_v4();
+    for lot in &lots {
+        let movement = NewStockMovement {
+            product_id: lot.product_id,
+            location_id: lot.location_id,
+            lot_id: Some(lot.lot_id),
+            reason: StockMovementReason::Adjustment,
+            quantity: -lot.quantity,
+            created_by: Some(auth.user.id),
+            reference_id: Some(reference_id),
+            adjustment_reason: Some(StockAdjustmentReason::ExpiryWriteOff),
+            note: payload.note.clone(),
+            unit_cost: None,
+        };
+        match apply_stock_movement(&mut tx, &movement).await {
+            Ok(true) => {}
+            Ok(false) => {
+                let _ = tx.rollback().await;
+                return MyBaseResponse::error(
+                    409,
+                    format!("Lot {} of {} changed during the write-off", lot.lot_number, lot.product_name),
+                );
+            }
+            Err(e) => {
+                let _ = tx.rollback().await;
+                return MyBaseResponse::db_err(e);
+            }
+        }
+    }
+
+    if let Err(e) = tx.commit().await {
+        return MyBaseResponse::db_err(e);
+    }
+
+    let written_off = ExpiredWriteOffModel {
+        reference_id,
+        quantity: lots.iter().map(|l| l.quantity).sum(),
+        value_at_cost: lots.iter().map(|l| l.value_at_cost.unwrap_or_default()).sum::<Money>(),
+        lots,
+    };
+    MyBaseResponse::ok(Some(written_off), Some("Expired stock written off".into()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/stock/costing-method",
+    tag = "Stock",
+    responses(
+        (status = 200, description = "Current costing method", body = MyBaseResponse<CostingSettingsModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn get_costing_method_handler(state: AppState) -> MyBaseResponse<CostingSettingsModel> {
+    match query_as::<_, CostingSettingsModel>(StockSQLString::GET_COSTING_SETTINGS)
+        .fetch_one(&state.db)
+        .await
+    {
+        Ok(settings) => MyBaseResponse::ok(Some(settings), Some("Costing method retrieved".into())),
+        Err(e) => MyBaseResponse::db_err(e),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/stock/costing-method",
+    tag = "Stock",
+    request_body = SetCostingMethodSchema,
+    responses(
+        (status = 200, description = "Costing method changed; movements already booked keep their cost", body = MyBaseResponse<CostingSettingsModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn set_costing_method_handler(
+    payload: SetCostingMethodSchema,
+    state: AppState,
+) -> MyBaseResponse<CostingSettingsModel> {
+    match query_as::<_, CostingSettingsModel>(StockSQLString::SET_COSTING_METHOD)
+        .bind(&payload.costing_method)
+        .fetch_one(&state.db)
+        .await
+    {
+        Ok(settings) => MyBaseResponse::ok(Some(settings), Some("Costing method updated".into())),
+        Err(e) => MyBaseResponse::db_err(e),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/stock/valuation",
+    tag = "Stock",
+    params(
+        StockValuationQuery
+    ),
+    responses(
+        (status = 200, description = "Stock on hand and its value at cost as of the date", body = MyBaseResponse<StockValuationModel>),
+        (status = 400, description = "Date is in the future", body = MyBaseResponse<StockValuationModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn get_stock_valuation_handler(
+    params: StockValuationQuery,
+    state: AppState,
+) -> MyBaseResponse<StockValuationModel> {
+    let today = chrono::Utc::now().date_naive();
+    let as_of = params.as_of.unwrap_or(today);
+    if as_of > today {
+        return MyBaseResponse::error(400, "Valuation date cannot be in the future");
+    }
+
+    let settings = match query_as::<_, CostingSettingsModel>(StockSQLString::GET_COSTING_SETTINGS)
+        .fetch_one(&state.db)
+        .await
+    {
+        Ok(s) => s,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let products = match query_as::<_, ProductValuationModel>(StockSQLString::GET_STOCK_VALUATION)
+        .bind(as_of)
+        .bind(params.location_id)
+        .fetch_all(&state.db)
+        .await
+    {
+        Ok(p) => p,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let valuation = StockValuationModel {
+        as_of,
+        location_id: params.location_id,
+        costing_method: settings.costing_method,
+        quantity: products.iter().map(|p| p.quantity).sum(),
+        total_value: round_money(products.iter().map(|p| p.value).sum()),
+        products,
+    };
+    MyBaseResponse::ok(Some(valuation), Some("Stock valuation generated successfully".into()))
+}
+
+/// Products at or below their reorder level, grouped by preferred supplier.
+pub async fn low_stock_products<'e, E: PgExecutor<'e>>(
+    executor: E,
+    location_id: Option<uuid::Uuid>,
+) -> Result<Vec<LowStockProductModel>, sqlx::Error> {
+    query_as::<_, LowStockProductModel>(StockSQLString::GET_LOW_STOCK_PRODUCTS)
+        .bind(location_id)
+        .fetch_all(executor)
+        .await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/stock/low-stock",
+    tag = "Stock",
+    params(
+        LowStockQuery
+    ),
+    responses(
+        (status = 200, description = "Products at or below their reorder level", body = MyBaseResponse<Vec<LowStockProductModel>>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn get_low_stock_handler(
+    params: LowStockQuery,
+    state: AppState,
+) -> MyBaseResponse<Vec<LowStockProductModel>> {
+    match low_stock_products(&state.db, params.location_id).await {
+        Ok(products) => {
+            MyBaseResponse::ok(Some(products), Some("Low stock retrieved successfully".into()))
+        }
+        Err(e) => MyBaseResponse::db_err(e),
+    }
+}
@@ -0,0 +1,391 @@
+use std::fmt;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+
+use crate::util::money::Money;
+
+/// Why product stock changed.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, sqlx::Type, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "stock_movement_reason", rename_all = "snake_case")]
+pub enum StockMovementReason {
+    /// Stock reserved by a cart line.
+    Sale,
+    /// Reserved stock returned when a cart line shrinks or is removed.
+    CartRelease,
+    Refund,
+    Adjustment,
+    Receipt,
+    /// Stock leaving a location on a dispatched transfer.
+    TransferOut,
+    /// Transferred stock arriving at its destination.
+    TransferIn,
+}
+
+impl fmt::Display for StockMovementReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            StockMovementReason::Sale => "sale",
+            StockMovementReason::CartRelease => "cart_release",
+            StockMovementReason::Refund => "refund",
+            StockMovementReason::Adjustment => "adjustment",
+            StockMovementReason::Receipt => "receipt",
+            StockMovementReason::TransferOut => "transfer_out",
+            StockMovementReason::TransferIn => "transfer_in",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Why stock was adjusted by hand.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, sqlx::Type, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "stock_adjustment_reason", rename_all = "snake_case")]
+pub enum StockAdjustmentReason {
+    Damage,
+    Theft,
+    ExpiryWriteOff,
+    FoundStock,
+    Correction,
+}
+
+impl fmt::Display for StockAdjustmentReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            StockAdjustmentReason::Damage => "damage",
+            StockAdjustmentReason::Theft => "theft",
+            StockAdjustmentReason::ExpiryWriteOff => "expiry_write_off",
+            StockAdjustmentReason::FoundStock => "found_stock",
+            StockAdjustmentReason::Correction => "correction",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl StockAdjustmentReason {
+    /// Losses only remove stock and found stock only adds it; a correction goes either way.
+    pub fn allows(&self, delta: i32) -> bool {
+        match self {
+            StockAdjustmentReason::Damage
+            | StockAdjustmentReason::Theft
+            | StockAdjustmentReason::ExpiryWriteOff => delta < 0,
+            StockAdjustmentReason::FoundStock => delta > 0,
+            StockAdjustmentReason::Correction => delta != 0,
+        }
+    }
+}
+
+/// Which cost outgoing stock is booked at: the oldest layers' cost (FIFO) or
+/// the running weighted average.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, sqlx::Type, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "costing_method", rename_all = "snake_case")]
+pub enum CostingMethod {
+    Fifo,
+    WeightedAverage,
+}
+
+impl fmt::Display for CostingMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            CostingMethod::Fifo => "fifo",
+            CostingMethod::WeightedAverage => "weighted_average",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// A stock change to apply; `quantity` is signed and in base units.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewStockMovement {
+    pub product_id: uuid::Uuid,
+    /// Location whose stock changes.
+    pub location_id: uuid::Uuid,
+    /// Lot the units move in or out of; `None` for unlotted stock.
+    pub lot_id: Option<uuid::Uuid>,
+    pub reason: StockMovementReason,
+    pub quantity: i32,
+    pub created_by: Option<uuid::Uuid>,
+    pub reference_id: Option<uuid::Uuid>,
+    pub adjustment_reason: Option<StockAdjustmentReason>,
+    pub note: Option<String>,
+    /// Cost per base unit of incoming stock. Left empty, incoming stock comes in
+    /// at the product's average cost; outgoing stock is always costed by the
+    /// costing method.
+    pub unit_cost: Option<Money>,
+}
+
+/// Reason, user and reference shared by the movements of one allocation or release.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StockMovementSource {
+    pub reason: StockMovementReason,
+    pub created_by: Option<uuid::Uuid>,
+    pub reference_id: Option<uuid::Uuid>,
+}
+
+/// A product lot as seen by FEFO allocation.
+#[derive(Clone, Debug, FromRow, PartialEq)]
+pub struct LotBalance {
+    pub id: uuid::Uuid,
+    pub quantity: i32,
+    pub expired: bool,
+}
+
+/// Base units a cart line holds from one lot, or from unlotted stock, at the
+/// cart's location.
+#[derive(Clone, Debug, FromRow, PartialEq)]
+pub struct CartItemAllocation {
+    pub id: i64,
+    pub product_id: uuid::Uuid,
+    pub location_id: uuid::Uuid,
+    pub lot_id: Option<uuid::Uuid>,
+    pub quantity: i32,
+    /// What the held units cost when they were taken.
+    pub cost_value: Money,
+    /// Base units of the product per base unit of the cart line; more than 1
+    /// for a component of a bundle.
+    pub units_per_item: i32,
+}
+
+/// A product's stock and cost position, read under the product lock.
+#[derive(Clone, Debug, FromRow, PartialEq)]
+pub struct CostBasis {
+    pub quantity: i32,
+    pub average_cost: Money,
+    pub costing_method: CostingMethod,
+}
+
+/// Base units still on hand from one incoming movement.
+#[derive(Clone, Debug, FromRow, PartialEq)]
+pub struct CostLayer {
+    pub id: i64,
+    pub unit_cost: Money,
+    pub remaining: i32,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, FromRow, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct StockMovementModel {
+    pub id: i64,
+    #[serde(rename = "productId")]
+    pub product_id: uuid::Uuid,
+    #[serde(rename = "locationId")]
+    pub location_id: uuid::Uuid,
+    #[serde(rename = "lotId")]
+    pub lot_id: Option<uuid::Uuid>,
+    pub reason: StockMovementReason,
+    pub quantity: i32,
+    /// Cost per base unit the movement was booked at.
+    #[serde(rename = "unitCost")]
+    #[schema(value_type = String)]
+    pub unit_cost: Money,
+    /// Stock on hand across all locations right after this movement, rebuilt from the ledger.
+    #[serde(rename = "balanceAfter")]
+    pub balance_after: i64,
+    #[serde(rename = "createdBy")]
+    pub created_by: Option<uuid::Uuid>,
+    #[serde(rename = "referenceId")]
+    pub reference_id: Option<uuid::Uuid>,
+    #[serde(rename = "adjustmentReason")]
+    pub adjustment_reason: Option<StockAdjustmentReason>,
+    pub note: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct StockHistoryModel {
+    #[serde(rename = "productId")]
+    pub product_id: uuid::Uuid,
+    #[serde(rename = "productName")]
+    pub product_name: String,
+    /// Current `products.quantity`.
+    pub quantity: i32,
+    /// Sum of every movement; equals `quantity` while the ledger is complete.
+    #[serde(rename = "ledgerQuantity")]
+    pub ledger_quantity: i64,
+    pub movements: Vec<StockMovementModel>,
+}
+
+/// A lot with stock left that has expired or expires within the report window.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, FromRow, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct ExpiringLotModel {
+    #[serde(rename = "lotId")]
+    pub lot_id: uuid::Uuid,
+    #[serde(rename = "productId")]
+    pub product_id: uuid::Uuid,
+    #[serde(rename = "productName")]
+    pub product_name: String,
+    #[serde(rename = "locationId")]
+    pub location_id: uuid::Uuid,
+    #[serde(rename = "locationName")]
+    pub location_name: String,
+    #[serde(rename = "lotNumber")]
+    pub lot_number: String,
+    #[serde(rename = "expiryDate")]
+    pub expiry_date: NaiveDate,
+    pub quantity: i32,
+    #[serde(rename = "unitCost")]
+    #[schema(value_type = Option<String>)]
+    pub unit_cost: Option<Money>,
+    /// `quantity * unit_cost`; empty when the lot has no recorded cost.
+    #[serde(rename = "valueAtCost")]
+    #[schema(value_type = Option<String>)]
+    pub value_at_cost: Option<Money>,
+    pub expired: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct ExpiryReportProductModel {
+    #[serde(rename = "productId")]
+    pub product_id: uuid::Uuid,
+    #[serde(rename = "productName")]
+    pub product_name: String,
+    /// Units in the listed lots, expired ones included.
+    pub quantity: i32,
+    #[serde(rename = "expiredQuantity")]
+    pub expired_quantity: i32,
+    /// Value of the lots that have a recorded cost.
+    #[serde(rename = "valueAtCost")]
+    #[schema(value_type = String)]
+    pub value_at_cost: Money,
+    pub lots: Vec<ExpiringLotModel>,
+}
+
+impl ExpiryReportProductModel {
+    /// Groups lots, already ordered by product, into one entry per product.
+    pub fn group(lots: Vec<ExpiringLotModel>) -> Vec<Self> {
+        let mut products: Vec<Self> = Vec::new();
+        for lot in lots {
+            let entry = match products.last_mut() {
+                Some(p) if p.product_id == lot.product_id => p,
+                _ => {
+                    products.push(Self {
+                        product_id: lot.product_id,
+                        product_name: lot.product_name.clone(),
+                        quantity: 0,
+                        expired_quantity: 0,
+                        value_at_cost: Money::ZERO,
+                        lots: Vec::new(),
+                    });
+                    products.last_mut().unwrap()
+                }
+            };
+            entry.quantity += lot.quantity;
+            if lot.expired {
+                entry.expired_quantity += lot.quantity;
+            }
+            entry.value_at_cost += lot.value_at_cost.unwrap_or_default();
+            entry.lots.push(lot);
+        }
+        products
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct ExpiryReportModel {
+    #[serde(rename = "asOf")]
+    pub as_of: NaiveDate,
+    /// Lots expiring on or before this date are listed.
+    pub through: NaiveDate,
+    pub quantity: i32,
+    #[serde(rename = "valueAtCost")]
+    #[schema(value_type = String)]
+    pub value_at_cost: Money,
+    pub products: Vec<ExpiryReportProductModel>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct ExpiredWriteOffModel {
+    /// Shared `referenceId` of the ledger rows written by this write-off.
+    #[serde(rename = "referenceId")]
+    pub reference_id: uuid::Uuid,
+    pub quantity: i32,
+    #[serde(rename = "valueAtCost")]
+    #[schema(value_type = String)]
+    pub value_at_cost: Money,
+    pub lots: Vec<ExpiringLotModel>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, FromRow, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct CostingSettingsModel {
+    #[serde(rename = "costingMethod")]
+    pub costing_method: CostingMethod,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Stock of one product as of the valuation date, valued from the ledger.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, FromRow, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct ProductValuationModel {
+    #[serde(rename = "productId")]
+    pub product_id: uuid::Uuid,
+    #[serde(rename = "productName")]
+    pub product_name: String,
+    pub quantity: i64,
+    #[schema(value_type = String)]
+    pub value: Money,
+    /// `value / quantity`; empty when nothing is on hand.
+    #[serde(rename = "unitCost")]
+    #[schema(value_type = Option<String>)]
+    pub unit_cost: Option<Money>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct StockValuationModel {
+    /// Stock is valued at the end of this day.
+    #[serde(rename = "asOf")]
+    pub as_of: NaiveDate,
+    #[serde(rename = "locationId")]
+    pub location_id: Option<uuid::Uuid>,
+    /// The method in force now; earlier movements keep the cost they were booked at.
+    #[serde(rename = "costingMethod")]
+    pub costing_method: CostingMethod,
+    pub quantity: i64,
+    #[serde(rename = "totalValue")]
+    #[schema(value_type = String)]
+    pub total_value: Money,
+    pub products: Vec<ProductValuationModel>,
+}
+
+/// A product at or below its reorder level.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, FromRow, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct LowStockProductModel {
+    #[serde(rename = "productId")]
+    pub product_id: uuid::Uuid,
+    #[serde(rename = "productName")]
+    pub product_name: String,
+    /// Base units on hand.
+    pub quantity: i32,
+    #[serde(rename = "reorderLevel")]
+    pub reorder_level: i32,
+    #[serde(rename = "reorderQuantity")]
+    pub reorder_quantity: i32,
+    /// Base units still to arrive on open purchase orders, drafts included.
+    #[serde(rename = "onOrder")]
+    pub on_order: i32,
+    /// The reorder quantity, or enough to reach the reorder level if that is more.
+    /// 0 once what is on order lifts the stock above the level.
+    #[serde(rename = "suggestedQuantity")]
+    pub suggested_quantity: i32,
+    /// The product's preferred supplier, if it has an active one.
+    #[serde(rename = "supplierId")]
+    pub supplier_id: Option<uuid::Uuid>,
+    #[serde(rename = "supplierName")]
+    pub supplier_name: Option<String>,
+    /// The preferred supplier's last cost, else the product's average cost.
+    #[serde(rename = "unitCost")]
+    #[schema(value_type = String)]
+    pub unit_cost: Money,
+}
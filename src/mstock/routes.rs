@@ -0,0 +1,94 @@
+use crate::{
+    AppState,
+    mauth::{
+        layers::{MyAuthLayer, MyAuthPermsLayer},
+        middlewares::JWTAuthMiddleware,
+    },
+    mstock::{
+        self,
+        schemas::{
+            ExpiryReportQuery, LowStockQuery, SetCostingMethodSchema, StockValuationQuery,
+            WriteOffExpiredSchema,
+        },
+    },
+};
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, Query, State},
+    routing::{get, post, put},
+};
+
+pub fn create_stock_router(app: AppState) -> Router {
+    return Router::new()
+        .route(
+            "/products/{id}/movements",
+            get(
+                |pool: State<AppState>, Path(id): Path<uuid::Uuid>| async move {
+                    return mstock::handlers::get_stock_history_handler(id, pool.0.clone()).await;
+                },
+            ),
+        )
+        .route(
+            "/expiry-report",
+            get(
+                |pool: State<AppState>, Query(params): Query<ExpiryReportQuery>| async move {
+                    return mstock::handlers::get_expiry_report_handler(params, pool.0.clone())
+                        .await;
+                },
+            ),
+        )
+        .route(
+            "/low-stock",
+            get(
+                |pool: State<AppState>, Query(params): Query<LowStockQuery>| async move {
+                    return mstock::handlers::get_low_stock_handler(params, pool.0.clone()).await;
+                },
+            ),
+        )
+        .route(
+            "/write-off-expired",
+            post(
+                |pool: State<AppState>,
+                 Extension(auth): Extension<JWTAuthMiddleware>,
+                 Json(payload): Json<WriteOffExpiredSchema>| async move {
+                    return mstock::handlers::write_off_expired_handler(
+                        auth,
+                        payload,
+                        pool.0.clone(),
+                    )
+                    .await;
+                },
+            )
+            .layer(MyAuthPermsLayer {}),
+        )
+        .route(
+            "/valuation",
+            get(
+                |pool: State<AppState>, Query(params): Query<StockValuationQuery>| async move {
+                    return mstock::handlers::get_stock_valuation_handler(params, pool.0.clone())
+                        .await;
+                },
+            )
+            .layer(MyAuthPermsLayer {}),
+        )
+        .route(
+            "/costing-method",
+            get(|pool: State<AppState>| async move {
+                return mstock::handlers::get_costing_method_handler(pool.0.clone()).await;
+            })
+            .merge(
+                put(
+                    |pool: State<AppState>, Json(payload): Json<SetCostingMethodSchema>| async move {
+                        return mstock::handlers::set_costing_method_handler(
+                            payload,
+                            pool.0.clone(),
+                        )
+                        .await;
+                    },
+                )
+                .layer(MyAuthPermsLayer {}),
+            ),
+        )
+        .layer(MyAuthLayer { state: app.clone() })
+        .with_state(app);
+}
@@ -0,0 +1,40 @@
+use chrono::NaiveDate;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::mstock::models::CostingMethod;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, IntoParams, PartialEq)]
+pub struct ExpiryReportQuery {
+    /// Include lots expiring within this many days; expired lots are always included. Defaults to 30.
+    pub days: Option<i32>,
+    /// Only lots at this location; every location when empty.
+    pub location_id: Option<uuid::Uuid>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct WriteOffExpiredSchema {
+    /// Only write off these products; all products when empty.
+    pub product_ids: Option<Vec<uuid::Uuid>>,
+    /// Only lots at this location; every location when empty.
+    pub location_id: Option<uuid::Uuid>,
+    pub note: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, IntoParams, PartialEq)]
+pub struct StockValuationQuery {
+    /// Value stock as it stood at the end of this day. Defaults to today.
+    pub as_of: Option<NaiveDate>,
+    /// Only stock at this location; every location when empty.
+    pub location_id: Option<uuid::Uuid>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct SetCostingMethodSchema {
+    pub costing_method: CostingMethod,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, IntoParams, PartialEq)]
+pub struct LowStockQuery {
+    /// Compare stock at this location with the reorder level; total stock when empty.
+    pub location_id: Option<uuid::Uuid>,
+}
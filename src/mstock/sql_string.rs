@@ -0,0 +1,282 @@
+pub struct StockSQLString;
+impl StockSQLString {
+    /// Gives a product a stock row at a location so stock can be received there.
+    pub const ENSURE_PRODUCT_STOCK: &'static str = r#"
+        INSERT INTO product_stock (product_id, location_id)
+        SELECT id, $2 FROM products WHERE id = $1
+        ON CONFLICT (product_id, location_id) DO NOTHING;
+    "#;
+
+    /// Applies a signed change at location `$9` and records it in one statement.
+    /// With a lot the lot moves too; without one a decrease may only use the
+    /// location's unlotted stock. `products.quantity` follows as the total. No
+    /// row comes back when the change would take any of them below zero. `$10`
+    /// is the cost per base unit the movement is booked at.
+    pub const APPLY_STOCK_MOVEMENT: &'static str = r#"
+        WITH lot AS (
+            UPDATE product_lots
+            SET quantity = quantity + $2
+            WHERE id = $8 AND product_id = $1 AND location_id = $9 AND quantity + $2 >= 0
+            RETURNING id
+        ),
+        stocked AS (
+            UPDATE product_stock s
+            SET quantity = s.quantity + $2, updated_at = now()
+            WHERE s.product_id = $1
+              AND s.location_id = $9
+              AND s.quantity + $2 >= 0
+              AND CASE
+                    WHEN $8::uuid IS NOT NULL THEN EXISTS (SELECT 1 FROM lot)
+                    ELSE $2 >= 0 OR s.quantity + $2 >= (
+                        SELECT COALESCE(SUM(l.quantity), 0)
+                        FROM product_lots l
+                        WHERE l.product_id = $1 AND l.location_id = $9
+                    )
+                  END
+            RETURNING s.product_id
+        ),
+        moved AS (
+            UPDATE products p
+            SET quantity = p.quantity + $2, updated_at = now()
+            WHERE p.id IN (SELECT product_id FROM stocked)
+            RETURNING p.id
+        )
+        INSERT INTO stock_movements (
+            product_id, location_id, lot_id, reason, quantity, created_by, reference_id,
+            adjustment_reason, note, unit_cost
+        )
+        SELECT id, $9, $8, $3, $2, $4, $5, $6, $7, $10 FROM moved
+        RETURNING id;
+    "#;
+
+    /// Locks the product and reads what its next movement is costed from.
+    pub const LOCK_PRODUCT_COST: &'static str = r#"
+        SELECT p.quantity, p.average_cost, s.costing_method
+        FROM products p
+        CROSS JOIN inventory_settings s
+        WHERE p.id = $1
+        FOR UPDATE OF p;
+    "#;
+
+    /// Layers with stock left at location `$2`, oldest first.
+    pub const LOCK_OPEN_COST_LAYERS: &'static str = r#"
+        SELECT id, unit_cost, remaining
+        FROM cost_layers
+        WHERE product_id = $1 AND location_id = $2 AND remaining > 0
+        ORDER BY created_at, id
+        FOR UPDATE;
+    "#;
+
+    pub const CONSUME_COST_LAYER: &'static str = r#"
+        UPDATE cost_layers SET remaining = remaining - $2 WHERE id = $1;
+    "#;
+
+    pub const INSERT_COST_LAYER: &'static str = r#"
+        INSERT INTO cost_layers (product_id, location_id, movement_id, unit_cost, quantity, remaining)
+        VALUES ($1, $2, $3, $4, $5, $5);
+    "#;
+
+    /// Folds `$3` units at `$4` into the average; `$2` is the stock on hand before them.
+    pub const UPDATE_AVERAGE_COST: &'static str = r#"
+        UPDATE products
+        SET average_cost = CASE
+              WHEN $2 <= 0 THEN $4
+              ELSE round(($2 * average_cost + $3 * $4) / ($2 + $3), 4)
+            END
+        WHERE id = $1;
+    "#;
+
+    pub const GET_COSTING_SETTINGS: &'static str = r#"
+        SELECT costing_method, updated_at FROM inventory_settings;
+    "#;
+
+    pub const SET_COSTING_METHOD: &'static str = r#"
+        UPDATE inventory_settings SET costing_method = $1
+        RETURNING costing_method, updated_at;
+    "#;
+
+    /// Stock by product at the end of day `$1`, valued at the cost every movement
+    /// was booked at, optionally at location `$2` only.
+    pub const GET_STOCK_VALUATION: &'static str = r#"
+        SELECT
+          m.product_id,
+          p.name AS product_name,
+          SUM(m.quantity) AS quantity,
+          round(SUM(m.quantity * m.unit_cost), 2) AS value,
+          round(SUM(m.quantity * m.unit_cost) / NULLIF(SUM(m.quantity), 0), 4) AS unit_cost
+        FROM stock_movements m
+        JOIN products p ON p.id = m.product_id
+        WHERE m.created_at < ($1::DATE + 1)
+          AND ($2::UUID IS NULL OR m.location_id = $2)
+        GROUP BY m.product_id, p.name
+        HAVING SUM(m.quantity) <> 0 OR SUM(m.quantity * m.unit_cost) <> 0
+        ORDER BY p.name, m.product_id;
+    "#;
+
+    pub const GET_PRODUCT_MOVEMENTS: &'static str = r#"
+        SELECT
+          id,
+          product_id,
+          location_id,
+          lot_id,
+          reason,
+          quantity,
+          unit_cost,
+          SUM(quantity) OVER (ORDER BY id) AS balance_after,
+          created_by,
+          reference_id,
+          adjustment_reason,
+          note,
+          created_at
+        FROM stock_movements
+        WHERE product_id = $1
+        ORDER BY id;
+    "#;
+
+    /// Stock at location `$2`. The product row is locked so every movement of
+    /// the product queues behind this one.
+    pub const LOCK_LOCATION_QUANTITY: &'static str = r#"
+        SELECT COALESCE(s.quantity, 0)
+        FROM products p
+        LEFT JOIN product_stock s ON s.product_id = p.id AND s.location_id = $2
+        WHERE p.id = $1
+        FOR UPDATE OF p;
+    "#;
+
+    /// Lots at location `$2` in FEFO order: earliest expiry first.
+    pub const LOCK_PRODUCT_LOTS: &'static str = r#"
+        SELECT id, quantity, expiry_date <= CURRENT_DATE AS expired
+        FROM product_lots
+        WHERE product_id = $1 AND location_id = $2
+        ORDER BY expiry_date, lot_number
+        FOR UPDATE;
+    "#;
+
+    pub const ADD_CART_ITEM_ALLOCATION: &'static str = r#"
+        INSERT INTO cart_item_allocations (cart_item_id, product_id, lot_id, quantity, cost_value, units_per_item)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (cart_item_id, product_id, lot_id)
+        DO UPDATE SET quantity = cart_item_allocations.quantity + EXCLUDED.quantity,
+                      cost_value = cart_item_allocations.cost_value + EXCLUDED.cost_value;
+    "#;
+
+    /// Allocations in release order: unlotted first, then the latest-expiring lot.
+    pub const LOCK_CART_ITEM_ALLOCATIONS: &'static str = r#"
+        SELECT a.id, a.product_id, c.location_id, a.lot_id, a.quantity, a.cost_value, a.units_per_item
+        FROM cart_item_allocations a
+        JOIN cart_items ci ON ci.id = a.cart_item_id
+        JOIN carts c ON c.id = ci.cart_id
+        LEFT JOIN product_lots l ON l.id = a.lot_id
+        WHERE a.cart_item_id = $1
+        ORDER BY l.expiry_date DESC NULLS FIRST, a.id DESC
+        FOR UPDATE OF a;
+    "#;
+
+    pub const REDUCE_CART_ITEM_ALLOCATION: &'static str = r#"
+        UPDATE cart_item_allocations
+        SET quantity = quantity - $2, cost_value = cost_value - $3
+        WHERE id = $1;
+    "#;
+
+    pub const DELETE_CART_ITEM_ALLOCATION: &'static str = r#"
+        DELETE FROM cart_item_allocations WHERE id = $1;
+    "#;
+
+    /// Lots with stock that expire on or before `CURRENT_DATE + $1` days, by
+    /// product, optionally at location `$2` only.
+    pub const GET_EXPIRING_LOTS: &'static str = r#"
+        SELECT
+          l.id AS lot_id,
+          l.product_id,
+          p.name AS product_name,
+          l.location_id,
+          loc.name AS location_name,
+          l.lot_number,
+          l.expiry_date,
+          l.quantity,
+          l.unit_cost,
+          round(l.quantity * l.unit_cost, 2) AS value_at_cost,
+          l.expiry_date <= CURRENT_DATE AS expired
+        FROM product_lots l
+        JOIN products p ON p.id = l.product_id
+        JOIN locations loc ON loc.id = l.location_id
+        WHERE l.quantity > 0
+          AND l.expiry_date <= CURRENT_DATE + $1::INTEGER
+          AND ($2::UUID IS NULL OR l.location_id = $2)
+        ORDER BY p.name, l.product_id, l.expiry_date, l.lot_number, loc.name;
+    "#;
+
+    /// Expired lots with stock, optionally limited to some products (`$1`) or
+    /// one location (`$2`).
+    pub const LOCK_EXPIRED_LOTS: &'static str = r#"
+        SELECT
+          l.id AS lot_id,
+          l.product_id,
+          p.name AS product_name,
+          l.location_id,
+          loc.name AS location_name,
+          l.lot_number,
+          l.expiry_date,
+          l.quantity,
+          l.unit_cost,
+          round(l.quantity * l.unit_cost, 2) AS value_at_cost,
+          TRUE AS expired
+        FROM product_lots l
+        JOIN products p ON p.id = l.product_id
+        JOIN locations loc ON loc.id = l.location_id
+        WHERE l.quantity > 0
+          AND l.expiry_date <= CURRENT_DATE
+          AND ($1::UUID[] IS NULL OR l.product_id = ANY($1))
+          AND ($2::UUID IS NULL OR l.location_id = $2)
+        ORDER BY p.name, l.product_id, l.expiry_date, l.lot_number, loc.name
+        FOR UPDATE OF l;
+    "#;
+
+    /// Products at or below their reorder level, with what is already on order
+    /// and the preferred supplier. Stock and orders are counted at one location
+    /// (`$1`), or across all of them when it is empty.
+    pub const GET_LOW_STOCK_PRODUCTS: &'static str = r#"
+        WITH stock AS (
+          SELECT
+            p.id,
+            p.name,
+            p.reorder_level,
+            p.reorder_quantity,
+            p.average_cost,
+            CASE
+              WHEN $1::UUID IS NULL THEN p.quantity
+              ELSE COALESCE(
+                (SELECT s.quantity FROM product_stock s
+                 WHERE s.product_id = p.id AND s.location_id = $1), 0)
+            END AS quantity,
+            COALESCE(
+              (SELECT SUM(pl.quantity - pl.received_quantity)
+               FROM purchase_order_lines pl
+               JOIN purchase_orders po ON po.id = pl.purchase_order_id
+               WHERE pl.product_id = p.id
+                 AND po.status IN ('draft', 'sent', 'partially_received')
+                 AND ($1::UUID IS NULL OR po.location_id = $1)), 0)::INTEGER AS on_order
+          FROM products p
+          WHERE p.reorder_level > 0
+        )
+        SELECT
+          s.id AS product_id,
+          s.name AS product_name,
+          s.quantity,
+          s.reorder_level,
+          s.reorder_quantity,
+          s.on_order,
+          CASE
+            WHEN s.quantity + s.on_order > s.reorder_level THEN 0
+            ELSE GREATEST(s.reorder_quantity, s.reorder_level - s.quantity - s.on_order)
+          END AS suggested_quantity,
+          sup.id AS supplier_id,
+          sup.name AS supplier_name,
+          COALESCE(ps.last_cost, round(s.average_cost, 2)) AS unit_cost
+        FROM stock s
+        LEFT JOIN (product_suppliers ps JOIN suppliers sup ON sup.id = ps.supplier_id AND sup.is_active)
+          ON ps.product_id = s.id AND ps.is_preferred
+        WHERE s.quantity <= s.reorder_level
+        ORDER BY sup.name NULLS LAST, sup.id, s.name;
+    "#;
+}
@@ -0,0 +1,381 @@
+use crate::AppState;
+use crate::msupplier::models::{ProductSupplierModel, SupplierModel};
+use crate::msupplier::schema::{
+    AddSupplierSchema, DeleteSupplierSchema, GetProductSuppliersSchema, LinkProductSupplierSchema,
+    UnlinkProductSupplierSchema, UpdateSupplierSchema,
+};
+use crate::shared_var::{FilterOptions, MyBaseResponse};
+use crate::util::money::round_money;
+use axum::Json;
+use axum::extract::{Query, State};
+
+use sqlx::query_as;
+
+const PRODUCT_SUPPLIER_COLUMNS: &str = r#"
+    ps.product_id, p.name AS product_name, ps.supplier_id, s.name AS supplier_name,
+    ps.supplier_sku, ps.last_cost, ps.lead_time_days, ps.is_preferred,
+    ps.created_at, ps.updated_at
+"#;
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/suppliers/get",
+    tag = "Suppliers",
+    params(
+        FilterOptions
+    ),
+    responses(
+        (status = 200, description = "Suppliers fetched successfully", body = MyBaseResponse<Vec<SupplierModel>>),
+        (status = 409, description = "Database error"),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn get_suppliers_handler(
+    State(app): State<AppState>,
+    Query(opts): Query<FilterOptions>,
+) -> MyBaseResponse<Vec<SupplierModel>> {
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+    let pattern = opts.search.map(|s| format!("%{}%", s));
+
+    let select_sql = r#"
+        SELECT *
+        FROM suppliers
+        WHERE $1::TEXT IS NULL OR name ILIKE $1 OR contact_name ILIKE $1
+        ORDER BY name
+        LIMIT $2 OFFSET $3
+    "#;
+
+    let res = query_as::<_, SupplierModel>(select_sql)
+        .bind(&pattern)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&app.db)
+        .await;
+
+    match res {
+        Ok(suppliers) => MyBaseResponse::ok(Some(suppliers), Some("Suppliers fetched".into())),
+        Err(e) => {
+            eprintln!("database query error: {}", e);
+            MyBaseResponse::db_err(e)
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/suppliers/add",
+    tag = "Suppliers",
+    request_body = AddSupplierSchema,
+    responses(
+        (status = 200, description = "Supplier created successfully", body = MyBaseResponse<SupplierModel>),
+        (status = 400, description = "Invalid supplier"),
+        (status = 409, description = "Supplier already exists"),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn add_supplier_handler(
+    State(app): State<AppState>,
+    Json(payload): Json<AddSupplierSchema>,
+) -> MyBaseResponse<SupplierModel> {
+    if payload.name.trim().is_empty() {
+        return MyBaseResponse::error(400, "Supplier name is required");
+    }
+
+    let insert_sql = r#"
+        INSERT INTO suppliers (
+            name, contact_name, email, phone, address, payment_terms_days, notes
+        ) VALUES ($1, $2, $3, $4, $5, COALESCE($6, 0), $7)
+        RETURNING *
+    "#;
+
+    let res = query_as::<_, SupplierModel>(insert_sql)
+        .bind(payload.name.trim())
+        .bind(&payload.contact_name)
+        .bind(&payload.email)
+        .bind(&payload.phone)
+        .bind(&payload.address)
+        .bind(payload.payment_terms_days)
+        .bind(&payload.notes)
+        .fetch_one(&app.db)
+        .await;
+
+    match res {
+        Ok(supplier) => MyBaseResponse::ok(Some(supplier), Some("Supplier created".into())),
+        Err(e) => {
+            eprintln!("database insert error: {}", e);
+            MyBaseResponse::db_err(e)
+        }
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/suppliers/update",
+    tag = "Suppliers",
+    request_body = UpdateSupplierSchema,
+    responses(
+        (status = 200, description = "Supplier updated successfully", body = MyBaseResponse<SupplierModel>),
+        (status = 404, description = "Supplier not found"),
+        (status = 409, description = "Supplier already exists"),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn update_supplier_handler(
+    State(app): State<AppState>,
+    Json(payload): Json<UpdateSupplierSchema>,
+) -> MyBaseResponse<SupplierModel> {
+    if payload.name.as_ref().is_some_and(|n| n.trim().is_empty()) {
+        return MyBaseResponse::error(400, "Supplier name is required");
+    }
+
+    let update_sql = r#"
+        UPDATE suppliers SET
+            name = COALESCE($1, name),
+            contact_name = COALESCE($2, contact_name),
+            email = COALESCE($3, email),
+            phone = COALESCE($4, phone),
+            address = COALESCE($5, address),
+            payment_terms_days = COALESCE($6, payment_terms_days),
+            notes = COALESCE($7, notes),
+            is_active = COALESCE($8, is_active)
+        WHERE id = $9
+        RETURNING *
+    "#;
+
+    let res = query_as::<_, SupplierModel>(update_sql)
+        .bind(payload.name.as_deref().map(str::trim))
+        .bind(&payload.contact_name)
+        .bind(&payload.email)
+        .bind(&payload.phone)
+        .bind(&payload.address)
+        .bind(payload.payment_terms_days)
+        .bind(&payload.notes)
+        .bind(payload.is_active)
+        .bind(payload.id)
+        .fetch_one(&app.db)
+        .await;
+
+    match res {
+        Ok(supplier) => MyBaseResponse::ok(Some(supplier), Some("Supplier updated".into())),
+        Err(e) => {
+            eprintln!("database update error: {}", e);
+            MyBaseResponse::db_err(e)
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/suppliers/delete",
+    tag = "Suppliers",
+    request_body = DeleteSupplierSchema,
+    responses(
+        (status = 200, description = "Supplier deleted successfully", body = MyBaseResponse<SupplierModel>),
+        (status = 404, description = "Supplier not found"),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn delete_supplier_handler(
+    State(app): State<AppState>,
+    Json(payload): Json<DeleteSupplierSchema>,
+) -> MyBaseResponse<SupplierModel> {
+    let delete_sql = r#"
+        DELETE FROM suppliers
+        WHERE id = $1
+        RETURNING *
+    "#;
+
+    let res = query_as::<_, SupplierModel>(delete_sql)
+        .bind(payload.id)
+        .fetch_one(&app.db)
+        .await;
+
+    match res {
+        Ok(supplier) => MyBaseResponse::ok(Some(supplier), Some("Supplier deleted".into())),
+        Err(e) => {
+            eprintln!("database delete error: {}", e);
+            MyBaseResponse::db_err(e)
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/suppliers/products",
+    tag = "Suppliers",
+    params(
+        GetProductSuppliersSchema
+    ),
+    responses(
+        (status = 200, description = "Product suppliers fetched successfully", body = MyBaseResponse<Vec<ProductSupplierModel>>),
+        (status = 409, description = "Database error"),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn get_product_suppliers_handler(
+    State(app): State<AppState>,
+    Query(params): Query<GetProductSuppliersSchema>,
+) -> MyBaseResponse<Vec<ProductSupplierModel>> {
+    let select_sql = format!(
+        r#"
+        SELECT {PRODUCT_SUPPLIER_COLUMNS}
+        FROM product_suppliers ps
+        JOIN products p ON p.id = ps.product_id
+        JOIN suppliers s ON s.id = ps.supplier_id
+        WHERE ($1::UUID IS NULL OR ps.product_id = $1)
+          AND ($2::UUID IS NULL OR ps.supplier_id = $2)
+        ORDER BY p.name, ps.is_preferred DESC, s.name
+    "#
+    );
+
+    let res = query_as::<_, ProductSupplierModel>(&select_sql)
+        .bind(params.product_id)
+        .bind(params.supplier_id)
+        .fetch_all(&app.db)
+        .await;
+
+    match res {
+        Ok(links) => MyBaseResponse::ok(Some(links), Some("Product suppliers fetched".into())),
+        Err(e) => {
+            eprintln!("database query error: {}", e);
+            MyBaseResponse::db_err(e)
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/suppliers/products/link",
+    tag = "Suppliers",
+    request_body = LinkProductSupplierSchema,
+    responses(
+        (status = 200, description = "Product linked to supplier", body = MyBaseResponse<ProductSupplierModel>),
+        (status = 400, description = "Unknown product or supplier, or invalid terms"),
+        (status = 409, description = "Supplier SKU already used"),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn link_product_supplier_handler(
+    State(app): State<AppState>,
+    Json(payload): Json<LinkProductSupplierSchema>,
+) -> MyBaseResponse<ProductSupplierModel> {
+    if payload.last_cost.is_some_and(|c| c.is_sign_negative()) {
+        return MyBaseResponse::error(400, "Cost cannot be negative");
+    }
+    if payload.lead_time_days.is_some_and(|d| d < 0) {
+        return MyBaseResponse::error(400, "Lead time cannot be negative");
+    }
+
+    let mut tx = match app.db.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    // Only one preferred supplier per product; demote the current one first so
+    // the partial unique index never sees two.
+    if payload.is_preferred {
+        let res = sqlx::query(
+            r#"
+            UPDATE product_suppliers SET is_preferred = FALSE
+            WHERE product_id = $1 AND supplier_id <> $2 AND is_preferred
+            "#,
+        )
+        .bind(payload.product_id)
+        .bind(payload.supplier_id)
+        .execute(&mut *tx)
+        .await;
+        if let Err(e) = res {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    }
+
+    let upsert_sql = format!(
+        r#"
+        WITH ps AS (
+            INSERT INTO product_suppliers (
+                product_id, supplier_id, supplier_sku, last_cost, lead_time_days, is_preferred
+            ) VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (product_id, supplier_id) DO UPDATE SET
+                supplier_sku = COALESCE(EXCLUDED.supplier_sku, product_suppliers.supplier_sku),
+                last_cost = COALESCE(EXCLUDED.last_cost, product_suppliers.last_cost),
+                lead_time_days = COALESCE(EXCLUDED.lead_time_days, product_suppliers.lead_time_days),
+                is_preferred = EXCLUDED.is_preferred
+            RETURNING *
+        )
+        SELECT {PRODUCT_SUPPLIER_COLUMNS}
+        FROM ps
+        JOIN products p ON p.id = ps.product_id
+        JOIN suppliers s ON s.id = ps.supplier_id
+    "#
+    );
+
+    let res = query_as::<_, ProductSupplierModel>(&upsert_sql)
+        .bind(payload.product_id)
+        .bind(payload.supplier_id)
+        .bind(&payload.supplier_sku)
+        .bind(payload.last_cost.map(round_money))
+        .bind(payload.lead_time_days)
+        .bind(payload.is_preferred)
+        .fetch_one(&mut *tx)
+        .await;
+
+    let link = match res {
+        Ok(link) => link,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            eprintln!("database insert error: {}", e);
+            return MyBaseResponse::db_err(e);
+        }
+    };
+
+    match tx.commit().await {
+        Ok(_) => MyBaseResponse::ok(Some(link), Some("Product linked to supplier".into())),
+        Err(e) => MyBaseResponse::db_err(e),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/suppliers/products/unlink",
+    tag = "Suppliers",
+    request_body = UnlinkProductSupplierSchema,
+    responses(
+        (status = 200, description = "Product unlinked from supplier", body = MyBaseResponse<ProductSupplierModel>),
+        (status = 404, description = "Link not found"),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn unlink_product_supplier_handler(
+    State(app): State<AppState>,
+    Json(payload): Json<UnlinkProductSupplierSchema>,
+) -> MyBaseResponse<ProductSupplierModel> {
+    let delete_sql = format!(
+        r#"
+        WITH ps AS (
+            DELETE FROM product_suppliers
+            WHERE product_id = $1 AND supplier_id = $2
+            RETURNING *
+        )
+        SELECT {PRODUCT_SUPPLIER_COLUMNS}
+        FROM ps
+        JOIN products p ON p.id = ps.product_id
+        JOIN suppliers s ON s.id = ps.supplier_id
+    "#
+    );
+
+    let res = query_as::<_, ProductSupplierModel>(&delete_sql)
+        .bind(payload.product_id)
+        .bind(payload.supplier_id)
+        .fetch_one(&app.db)
+        .await;
+
+    match res {
+        Ok(link) => MyBaseResponse::ok(Some(link), Some("Product unlinked from supplier".into())),
+        Err(e) => {
+            eprintln!("database delete error: {}", e);
+            MyBaseResponse::db_err(e)
+        }
+    }
+}
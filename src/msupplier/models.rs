@@ -0,0 +1,55 @@
+use chrono::{DateTime, Utc};
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+
+use crate::util::money::Money;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, FromRow, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct SupplierModel {
+    pub id: uuid::Uuid,
+    pub name: String,
+    #[serde(rename = "contactName")]
+    pub contact_name: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub address: Option<String>,
+    /// Days after invoice that payment is due; 0 is payment on delivery.
+    #[serde(rename = "paymentTermsDays")]
+    pub payment_terms_days: i32,
+    pub notes: Option<String>,
+    #[serde(rename = "isActive")]
+    pub is_active: bool,
+    #[serde(rename = "createdAt")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// A supplier a product can be bought from, with that supplier's terms for it.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, FromRow, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct ProductSupplierModel {
+    #[serde(rename = "productId")]
+    pub product_id: uuid::Uuid,
+    #[serde(rename = "productName")]
+    pub product_name: String,
+    #[serde(rename = "supplierId")]
+    pub supplier_id: uuid::Uuid,
+    #[serde(rename = "supplierName")]
+    pub supplier_name: String,
+    #[serde(rename = "supplierSku")]
+    pub supplier_sku: Option<String>,
+    /// Cost per base unit on the latest purchase.
+    #[serde(rename = "lastCost")]
+    #[schema(value_type = Option<String>)]
+    pub last_cost: Option<Money>,
+    #[serde(rename = "leadTimeDays")]
+    pub lead_time_days: Option<i32>,
+    #[serde(rename = "isPreferred")]
+    pub is_preferred: bool,
+    #[serde(rename = "createdAt")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,113 @@
+use crate::AppState;
+use crate::mauth::layers::{MyAuthLayer, MyAuthPermsLayer};
+use crate::msupplier::handlers::{
+    add_supplier_handler, delete_supplier_handler, get_product_suppliers_handler,
+    get_suppliers_handler, link_product_supplier_handler, unlink_product_supplier_handler,
+    update_supplier_handler,
+};
+use crate::msupplier::schema::{
+    AddSupplierSchema, DeleteSupplierSchema, GetProductSuppliersSchema, LinkProductSupplierSchema,
+    UnlinkProductSupplierSchema, UpdateSupplierSchema,
+};
+use crate::shared_var::FilterOptions;
+use axum::Json;
+use axum::extract::Query;
+use axum::routing::{delete, post, put};
+use axum::{Router, extract::State, routing::get};
+
+pub fn create_supplier_router(app: AppState) -> Router {
+    return Router::new()
+        .route(
+            "/get",
+            get(
+                |State(pool): State<AppState>, filter: Query<FilterOptions>| async move {
+                    let app = AppState {
+                        db: pool.db.clone(),
+                        env: pool.env.clone(),
+                    };
+                    return get_suppliers_handler(State(app), filter).await;
+                },
+            ),
+        )
+        .route(
+            "/add",
+            post(
+                |State(pool): State<AppState>, Json(payload): Json<AddSupplierSchema>| async move {
+                    let app = AppState {
+                        db: pool.db.clone(),
+                        env: pool.env.clone(),
+                    };
+                    return add_supplier_handler(State(app), Json(payload)).await;
+                },
+            )
+            .layer(MyAuthPermsLayer {}),
+        )
+        .route(
+            "/update",
+            put(
+                |State(pool): State<AppState>, Json(payload): Json<UpdateSupplierSchema>| async move {
+                    let app = AppState {
+                        db: pool.db.clone(),
+                        env: pool.env.clone(),
+                    };
+                    return update_supplier_handler(State(app), Json(payload)).await;
+                },
+            )
+            .layer(MyAuthPermsLayer {}),
+        )
+        .route(
+            "/delete",
+            delete(
+                |State(pool): State<AppState>, Json(payload): Json<DeleteSupplierSchema>| async move {
+                    let app = AppState {
+                        db: pool.db.clone(),
+                        env: pool.env.clone(),
+                    };
+                    return delete_supplier_handler(State(app), Json(payload)).await;
+                },
+            )
+            .layer(MyAuthPermsLayer {}),
+        )
+        .route(
+            "/products",
+            get(
+                |State(pool): State<AppState>, params: Query<GetProductSuppliersSchema>| async move {
+                    let app = AppState {
+                        db: pool.db.clone(),
+                        env: pool.env.clone(),
+                    };
+                    return get_product_suppliers_handler(State(app), params).await;
+                },
+            ),
+        )
+        .route(
+            "/products/link",
+            post(
+                |State(pool): State<AppState>,
+                 Json(payload): Json<LinkProductSupplierSchema>| async move {
+                    let app = AppState {
+                        db: pool.db.clone(),
+                        env: pool.env.clone(),
+                    };
+                    return link_product_supplier_handler(State(app), Json(payload)).await;
+                },
+            )
+            .layer(MyAuthPermsLayer {}),
+        )
+        .route(
+            "/products/unlink",
+            delete(
+                |State(pool): State<AppState>,
+                 Json(payload): Json<UnlinkProductSupplierSchema>| async move {
+                    let app = AppState {
+                        db: pool.db.clone(),
+                        env: pool.env.clone(),
+                    };
+                    return unlink_product_supplier_handler(State(app), Json(payload)).await;
+                },
+            )
+            .layer(MyAuthPermsLayer {}),
+        )
+        .layer(MyAuthLayer { state: app.clone() })
+        .with_state(app);
+}
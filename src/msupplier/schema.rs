@@ -0,0 +1,73 @@
+use utoipa::{IntoParams, ToSchema};
+
+use crate::util::money::Money;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct AddSupplierSchema {
+    pub name: String,
+    pub contact_name: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub address: Option<String>,
+    /// Defaults to 0, payment on delivery.
+    pub payment_terms_days: Option<i32>,
+    pub notes: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct UpdateSupplierSchema {
+    #[serde()]
+    pub id: uuid::Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_terms_days: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// Inactive suppliers keep their history but are left out of purchasing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_active: Option<bool>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct DeleteSupplierSchema {
+    #[serde()]
+    pub id: uuid::Uuid,
+}
+
+/// Links a product to a supplier, or updates an existing link.
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct LinkProductSupplierSchema {
+    pub product_id: uuid::Uuid,
+    pub supplier_id: uuid::Uuid,
+    pub supplier_sku: Option<String>,
+    /// Cost per base unit.
+    #[schema(value_type = Option<String>)]
+    pub last_cost: Option<Money>,
+    pub lead_time_days: Option<i32>,
+    /// Makes this the product's preferred supplier in place of the current one.
+    #[serde(default)]
+    pub is_preferred: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct UnlinkProductSupplierSchema {
+    pub product_id: uuid::Uuid,
+    pub supplier_id: uuid::Uuid,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, ToSchema, IntoParams, PartialEq)]
+pub struct GetProductSuppliersSchema {
+    /// Suppliers of this product.
+    pub product_id: Option<uuid::Uuid>,
+    /// Products bought from this supplier.
+    pub supplier_id: Option<uuid::Uuid>,
+}
@@ -0,0 +1,695 @@
+use std::collections::{HashMap, HashSet};
+
+use axum::Json;
+use axum::extract::{Query, State};
+use sqlx::{PgExecutor, query_as};
+
+use crate::AppState;
+use crate::mauth::middlewares::JWTAuthMiddleware;
+use crate::mlocation::handlers::resolve_location;
+use crate::mstock::handlers::{apply_costed_stock_movement, apply_stock_movement, plan_fefo_takes};
+use crate::mstock::models::{NewStockMovement, StockMovementReason};
+use crate::mtransfer::models::{
+    TransferAllocation, TransferLineModel, TransferModel, TransferStatus, TransferWithLinesModel,
+};
+use crate::mtransfer::schema::{
+    AddTransferSchema, GetTransfersSchema, ReceiveTransferSchema, TransferIdSchema,
+};
+use crate::shared_var::MyBaseResponse;
+use crate::util::money::Money;
+
+async fn load_transfer<'e, E: PgExecutor<'e>>(
+    executor: E,
+    id: uuid::Uuid,
+) -> Result<Option<TransferModel>, sqlx::Error> {
+    query_as!(
+        TransferModel,
+        r#"
+        SELECT t.id, t.transfer_number, t.from_location_id, f.name AS from_location_name,
+               t.to_location_id, d.name AS to_location_name, t.status AS "status: TransferStatus",
+               t.note, t.created_by, t.dispatched_by, t.dispatched_at, t.received_by,
+               t.received_at, t.cancelled_by, t.cancelled_at, t.created_at, t.updated_at
+        FROM stock_transfers t
+        JOIN locations f ON f.id = t.from_location_id
+        JOIN locations d ON d.id = t.to_location_id
+        WHERE t.id = $1
+        FOR UPDATE OF t
+        "#,
+        id,
+    )
+    .fetch_optional(executor)
+    .await
+}
+
+async fn load_lines<'e, E: PgExecutor<'e>>(
+    executor: E,
+    transfer_ids: &[uuid::Uuid],
+) -> Result<Vec<TransferLineModel>, sqlx::Error> {
+    query_as!(
+        TransferLineModel,
+        r#"
+        SELECT tl.id, tl.transfer_id, tl.product_id, p.name AS product_name, tl.quantity,
+               tl.received_quantity, tl.quantity - tl.received_quantity AS discrepancy,
+               tl.discrepancy_note
+        FROM stock_transfer_lines tl
+        JOIN products p ON p.id = tl.product_id
+        WHERE tl.transfer_id = ANY($1)
+        ORDER BY tl.id
+        "#,
+        transfer_ids,
+    )
+    .fetch_all(executor)
+    .await
+}
+
+fn with_lines(
+    transfers: Vec<TransferModel>,
+    lines: Vec<TransferLineModel>,
+) -> Vec<TransferWithLinesModel> {
+    let mut by_transfer: HashMap<uuid::Uuid, Vec<TransferLineModel>> = HashMap::new();
+    for line in lines {
+        by_transfer.entry(line.transfer_id).or_default().push(line);
+    }
+    transfers
+        .into_iter()
+        .map(|transfer| TransferWithLinesModel {
+            lines: by_transfer.remove(&transfer.id).unwrap_or_default(),
+            transfer,
+        })
+        .collect()
+}
+
+/// Rejects a step the transfer's current status does not allow.
+fn check_transition(
+    transfer: &TransferModel,
+    next: TransferStatus,
+) -> Result<(), MyBaseResponse<()>> {
+    if transfer.status.can_transition_to(&next) {
+        return Ok(());
+    }
+    let allowed: Vec<String> = transfer
+        .status
+        .allowed_next()
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    Err(MyBaseResponse::error(
+        409,
+        format!(
+            "Transfer is {}; cannot move to {} (allowed: {})",
+            transfer.status,
+            next,
+            if allowed.is_empty() { "none".to_string() } else { allowed.join(", ") }
+        ),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/transfers/get",
+    tag = "Transfers",
+    params(
+        GetTransfersSchema
+    ),
+    responses(
+        (status = 200, description = "Transfers retrieved successfully", body = MyBaseResponse<Vec<TransferWithLinesModel>>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn get_transfers_handler(
+    Query(params): Query<GetTransfersSchema>,
+    State(app_state): State<AppState>,
+) -> MyBaseResponse<Vec<TransferWithLinesModel>> {
+    let transfers = query_as!(
+        TransferModel,
+        r#"
+        SELECT t.id, t.transfer_number, t.from_location_id, f.name AS from_location_name,
+               t.to_location_id, d.name AS to_location_name, t.status AS "status: TransferStatus",
+               t.note, t.created_by, t.dispatched_by, t.dispatched_at, t.received_by,
+               t.received_at, t.cancelled_by, t.cancelled_at, t.created_at, t.updated_at
+        FROM stock_transfers t
+        JOIN locations f ON f.id = t.from_location_id
+        JOIN locations d ON d.id = t.to_location_id
+        WHERE ($1::TEXT IS NULL OR t.status::TEXT = $1)
+          AND ($2::UUID IS NULL OR $2 IN (t.from_location_id, t.to_location_id))
+        ORDER BY t.created_at DESC
+        "#,
+        params.status.map(|s| s.to_string()),
+        params.location_id,
+    )
+    .fetch_all(&app_state.db)
+    .await;
+    let transfers = match transfers {
+        Ok(t) => t,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let ids: Vec<uuid::Uuid> = transfers.iter().map(|t| t.id).collect();
+    match load_lines(&app_state.db, &ids).await {
+        Ok(lines) => MyBaseResponse::ok(
+            Some(with_lines(transfers, lines)),
+            Some("Transfers retrieved successfully".into()),
+        ),
+        Err(e) => MyBaseResponse::db_err(e),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/transfers/add",
+    tag = "Transfers",
+    request_body = AddTransferSchema,
+    responses(
+        (status = 200, description = "Draft transfer created", body = MyBaseResponse<TransferWithLinesModel>),
+        (status = 400, description = "Invalid transfer", body = MyBaseResponse<TransferWithLinesModel>),
+        (status = 404, description = "Location or product not found", body = MyBaseResponse<TransferWithLinesModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn add_transfer_handler(
+    auth: JWTAuthMiddleware,
+    Json(payload): Json<AddTransferSchema>,
+    State(app_state): State<AppState>,
+) -> MyBaseResponse<TransferWithLinesModel> {
+    if payload.from_location_id == payload.to_location_id {
+        return MyBaseResponse::error(400, "Source and destination must be different locations");
+    }
+    if payload.lines.is_empty() {
+        return MyBaseResponse::error(400, "A transfer needs at least one line");
+    }
+    let mut seen = HashSet::new();
+    for line in &payload.lines {
+        if line.quantity <= 0 {
+            return MyBaseResponse::error(400, "Quantity must be > 0");
+        }
+        if !seen.insert(line.product_id) {
+            return MyBaseResponse::error(
+                400,
+                format!("Product {} is listed more than once", line.product_id),
+            );
+        }
+    }
+
+    let mut tx = match app_state.db.begin().await {
+        Ok(t) => t,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    for location_id in [payload.from_location_id, payload.to_location_id] {
+        if let Err(e) = resolve_location(&mut *tx, Some(location_id)).await {
+            let _ = tx.rollback().await;
+            return e.cast();
+        }
+    }
+
+    let product_ids: Vec<uuid::Uuid> = payload.lines.iter().map(|l| l.product_id).collect();
+    let quantities: Vec<i32> = payload.lines.iter().map(|l| l.quantity).collect();
+    let found = sqlx::query_scalar!(
+        r#"SELECT id FROM products WHERE id = ANY($1)"#,
+        &product_ids,
+    )
+    .fetch_all(&mut *tx)
+    .await;
+    let found: HashSet<uuid::Uuid> = match found {
+        Ok(ids) => ids.into_iter().collect(),
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+    if let Some(missing) = product_ids.iter().find(|id| !found.contains(id)) {
+        let _ = tx.rollback().await;
+        return MyBaseResponse::error(404, format!("Product {} not found", missing));
+    }
+
+    let transfer_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO stock_transfers (from_location_id, to_location_id, note, created_by)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id
+        "#,
+        payload.from_location_id,
+        payload.to_location_id,
+        payload.note,
+        auth.user.id,
+    )
+    .fetch_one(&mut *tx)
+    .await;
+    let transfer_id = match transfer_id {
+        Ok(id) => id,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+
+    let inserted = sqlx::query!(
+        r#"
+        INSERT INTO stock_transfer_lines (transfer_id, product_id, quantity)
+        SELECT $1, l.product_id, l.quantity
+        FROM UNNEST($2::UUID[], $3::INTEGER[]) AS l(product_id, quantity)
+        "#,
+        transfer_id,
+        &product_ids,
+        &quantities,
+    )
+    .execute(&mut *tx)
+    .await;
+    if let Err(e) = inserted {
+        let _ = tx.rollback().await;
+        return MyBaseResponse::db_err(e);
+    }
+
+    finish(tx, transfer_id, "Draft transfer created").await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/transfers/dispatch",
+    tag = "Transfers",
+    request_body = TransferIdSchema,
+    responses(
+        (status = 200, description = "Transfer dispatched; stock is in transit", body = MyBaseResponse<TransferWithLinesModel>),
+        (status = 400, description = "Not enough sellable stock at the source", body = MyBaseResponse<TransferWithLinesModel>),
+        (status = 404, description = "Transfer not found", body = MyBaseResponse<TransferWithLinesModel>),
+        (status = 409, description = "Transfer is not a draft", body = MyBaseResponse<TransferWithLinesModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn dispatch_transfer_handler(
+    auth: JWTAuthMiddleware,
+    Json(payload): Json<TransferIdSchema>,
+    State(app_state): State<AppState>,
+) -> MyBaseResponse<TransferWithLinesModel> {
+    let mut tx = match app_state.db.begin().await {
+        Ok(t) => t,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let transfer = match load_transfer(&mut *tx, payload.id).await {
+        Ok(Some(t)) => t,
+        Ok(None) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::error(404, "Transfer not found");
+        }
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+    if let Err(e) = check_transition(&transfer, TransferStatus::InTransit) {
+        let _ = tx.rollback().await;
+        return e.cast();
+    }
+
+    let lines = match load_lines(&mut *tx, &[transfer.id]).await {
+        Ok(l) => l,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+
+    // Stock leaves the source FEFO, like a sale; the lots taken are kept so
+    // the destination receives the same lots.
+    let mut short = Vec::new();
+    for line in &lines {
+        let takes = match plan_fefo_takes(
+            &mut tx,
+            line.product_id,
+            transfer.from_location_id,
+            line.quantity,
+        )
+        .await
+        {
+            Ok(Some(t)) => t,
+            Ok(None) => {
+                short.push(line.product_name.clone());
+                continue;
+            }
+            Err(e) => {
+                let _ = tx.rollback().await;
+                return MyBaseResponse::db_err(e);
+            }
+        };
+
+        for (lot_id, take) in takes {
+            let movement = NewStockMovement {
+                product_id: line.product_id,
+                location_id: transfer.from_location_id,
+                lot_id,
+                reason: StockMovementReason::TransferOut,
+                quantity: -take,
+                created_by: Some(auth.user.id),
+                reference_id: Some(transfer.id),
+                adjustment_reason: None,
+                note: None,
+                unit_cost: None,
+            };
+            let unit_cost = match apply_costed_stock_movement(&mut tx, &movement).await {
+                Ok(Some(c)) => c,
+                Ok(None) => {
+                    short.push(line.product_name.clone());
+                    break;
+                }
+                Err(e) => {
+                    let _ = tx.rollback().await;
+                    return MyBaseResponse::db_err(e);
+                }
+            };
+            let allocated = sqlx::query!(
+                r#"
+                INSERT INTO stock_transfer_allocations (line_id, lot_id, quantity, cost_value)
+                VALUES ($1, $2, $3, $4)
+                "#,
+                line.id,
+                lot_id,
+                take,
+                unit_cost * Money::from(take),
+            )
+            .execute(&mut *tx)
+            .await;
+            if let Err(e) = allocated {
+                let _ = tx.rollback().await;
+                return MyBaseResponse::db_err(e);
+            }
+        }
+    }
+    if !short.is_empty() {
+        let _ = tx.rollback().await;
+        return MyBaseResponse::error(
+            400,
+            format!(
+                "Not enough sellable stock at {}: {}",
+                transfer.from_location_name,
+                short.join(", ")
+            ),
+        );
+    }
+
+    let updated = sqlx::query!(
+        r#"
+        UPDATE stock_transfers
+        SET status = 'in_transit', dispatched_by = $2, dispatched_at = now()
+        WHERE id = $1
+        "#,
+        transfer.id,
+        auth.user.id,
+    )
+    .execute(&mut *tx)
+    .await;
+    if let Err(e) = updated {
+        let _ = tx.rollback().await;
+        return MyBaseResponse::db_err(e);
+    }
+
+    finish(tx, transfer.id, "Transfer dispatched").await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/transfers/receive",
+    tag = "Transfers",
+    request_body = ReceiveTransferSchema,
+    responses(
+        (status = 200, description = "Transfer received; short lines are recorded as discrepancies", body = MyBaseResponse<TransferWithLinesModel>),
+        (status = 400, description = "Invalid received quantities", body = MyBaseResponse<TransferWithLinesModel>),
+        (status = 404, description = "Transfer not found", body = MyBaseResponse<TransferWithLinesModel>),
+        (status = 409, description = "Transfer is not in transit", body = MyBaseResponse<TransferWithLinesModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn receive_transfer_handler(
+    auth: JWTAuthMiddleware,
+    Json(payload): Json<ReceiveTransferSchema>,
+    State(app_state): State<AppState>,
+) -> MyBaseResponse<TransferWithLinesModel> {
+    let mut tx = match app_state.db.begin().await {
+        Ok(t) => t,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let transfer = match load_transfer(&mut *tx, payload.id).await {
+        Ok(Some(t)) => t,
+        Ok(None) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::error(404, "Transfer not found");
+        }
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+    if let Err(e) = check_transition(&transfer, TransferStatus::Received) {
+        let _ = tx.rollback().await;
+        return e.cast();
+    }
+
+    let lines = match load_lines(&mut *tx, &[transfer.id]).await {
+        Ok(l) => l,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+
+    let mut received: HashMap<i64, (i32, Option<String>)> = HashMap::new();
+    for entry in payload.lines.iter().flatten() {
+        let Some(line) = lines.iter().find(|l| l.id == entry.line_id) else {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::error(
+                400,
+                format!("Line {} is not on this transfer", entry.line_id),
+            );
+        };
+        if !(0..=line.quantity).contains(&entry.received_quantity) {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::error(
+                400,
+                format!(
+                    "{}: received quantity must be between 0 and the {} sent; record extra stock as found stock",
+                    line.product_name, line.quantity
+                ),
+            );
+        }
+        if received
+            .insert(entry.line_id, (entry.received_quantity, entry.note.clone()))
+            .is_some()
+        {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::error(
+                400,
+                format!("Line {} is listed more than once", entry.line_id),
+            );
+        }
+    }
+
+    for line in &lines {
+        let (quantity, note) = received
+            .remove(&line.id)
+            .unwrap_or((line.quantity, None));
+
+        let allocations = query_as!(
+            TransferAllocation,
+            r#"
+            SELECT a.id, a.lot_id, l.lot_number AS "lot_number?", l.expiry_date AS "expiry_date?",
+                   l.unit_cost, a.quantity, a.cost_value
+            FROM stock_transfer_allocations a
+            LEFT JOIN product_lots l ON l.id = a.lot_id
+            WHERE a.line_id = $1
+            ORDER BY l.expiry_date NULLS LAST, a.id
+            "#,
+            line.id,
+        )
+        .fetch_all(&mut *tx)
+        .await;
+        let allocations = match allocations {
+            Ok(a) => a,
+            Err(e) => {
+                let _ = tx.rollback().await;
+                return MyBaseResponse::db_err(e);
+            }
+        };
+
+        // What arrived fills the earliest-expiring lots first; any shortfall
+        // comes off the latest ones.
+        let mut remaining = quantity;
+        for allocation in allocations {
+            if remaining == 0 {
+                break;
+            }
+            let take = remaining.min(allocation.quantity);
+            let lot_id = match (&allocation.lot_number, allocation.expiry_date) {
+                (Some(lot_number), Some(expiry_date)) => {
+                    let lot = sqlx::query_scalar!(
+                        r#"
+                        INSERT INTO product_lots (product_id, location_id, lot_number, expiry_date, unit_cost)
+                        VALUES ($1, $2, $3, $4, $5)
+                        ON CONFLICT (product_id, location_id, lot_number)
+                        DO UPDATE SET unit_cost = COALESCE(product_lots.unit_cost, EXCLUDED.unit_cost)
+                        RETURNING id
+                        "#,
+                        line.product_id,
+                        transfer.to_location_id,
+                        lot_number,
+                        expiry_date,
+                        allocation.unit_cost,
+                    )
+                    .fetch_one(&mut *tx)
+                    .await;
+                    match lot {
+                        Ok(id) => Some(id),
+                        Err(e) => {
+                            let _ = tx.rollback().await;
+                            return MyBaseResponse::db_err(e);
+                        }
+                    }
+                }
+                _ => None,
+            };
+
+            let movement = NewStockMovement {
+                product_id: line.product_id,
+                location_id: transfer.to_location_id,
+                lot_id,
+                reason: StockMovementReason::TransferIn,
+                quantity: take,
+                created_by: Some(auth.user.id),
+                reference_id: Some(transfer.id),
+                adjustment_reason: None,
+                note: None,
+                // Arrives at the cost it left the source at.
+                unit_cost: Some(allocation.cost_value / Money::from(allocation.quantity)),
+            };
+            if let Err(e) = apply_stock_movement(&mut tx, &movement).await {
+                let _ = tx.rollback().await;
+                return MyBaseResponse::db_err(e);
+            }
+            remaining -= take;
+        }
+
+        let updated = sqlx::query!(
+            r#"
+            UPDATE stock_transfer_lines
+            SET received_quantity = $2, discrepancy_note = $3
+            WHERE id = $1
+            "#,
+            line.id,
+            quantity,
+            note,
+        )
+        .execute(&mut *tx)
+        .await;
+        if let Err(e) = updated {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    }
+
+    let updated = sqlx::query!(
+        r#"
+        UPDATE stock_transfers
+        SET status = 'received', received_by = $2, received_at = now()
+        WHERE id = $1
+        "#,
+        transfer.id,
+        auth.user.id,
+    )
+    .execute(&mut *tx)
+    .await;
+    if let Err(e) = updated {
+        let _ = tx.rollback().await;
+        return MyBaseResponse::db_err(e);
+    }
+
+    finish(tx, transfer.id, "Transfer received").await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/transfers/cancel",
+    tag = "Transfers",
+    request_body = TransferIdSchema,
+    responses(
+        (status = 200, description = "Draft transfer cancelled", body = MyBaseResponse<TransferWithLinesModel>),
+        (status = 404, description = "Transfer not found", body = MyBaseResponse<TransferWithLinesModel>),
+        (status = 409, description = "Transfer is not a draft", body = MyBaseResponse<TransferWithLinesModel>),
+    ),
+     security(("bearerAuth" = [])),
+)]
+pub async fn cancel_transfer_handler(
+    auth: JWTAuthMiddleware,
+    Json(payload): Json<TransferIdSchema>,
+    State(app_state): State<AppState>,
+) -> MyBaseResponse<TransferWithLinesModel> {
+    let mut tx = match app_state.db.begin().await {
+        Ok(t) => t,
+        Err(e) => return MyBaseResponse::db_err(e),
+    };
+
+    let transfer = match load_transfer(&mut *tx, payload.id).await {
+        Ok(Some(t)) => t,
+        Ok(None) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::error(404, "Transfer not found");
+        }
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+    if let Err(e) = check_transition(&transfer, TransferStatus::Cancelled) {
+        let _ = tx.rollback().await;
+        return e.cast();
+    }
+
+    let updated = sqlx::query!(
+        r#"
+        UPDATE stock_transfers
+        SET status = 'cancelled', cancelled_by = $2, cancelled_at = now()
+        WHERE id = $1
+        "#,
+        transfer.id,
+        auth.user.id,
+    )
+    .execute(&mut *tx)
+    .await;
+    if let Err(e) = updated {
+        let _ = tx.rollback().await;
+        return MyBaseResponse::db_err(e);
+    }
+
+    finish(tx, transfer.id, "Transfer cancelled").await
+}
+
+/// Reads the transfer back with its lines and commits.
+async fn finish(
+    mut tx: sqlx::Transaction<'_, sqlx::Postgres>,
+    transfer_id: uuid::Uuid,
+    message: &str,
+) -> MyBaseResponse<TransferWithLinesModel> {
+    let transfer = match load_transfer(&mut *tx, transfer_id).await {
+        Ok(Some(t)) => t,
+        Ok(None) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::error(404, "Transfer not found");
+        }
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+    let lines = match load_lines(&mut *tx, &[transfer_id]).await {
+        Ok(l) => l,
+        Err(e) => {
+            let _ = tx.rollback().await;
+            return MyBaseResponse::db_err(e);
+        }
+    };
+
+    if let Err(e) = tx.commit().await {
+        return MyBaseResponse::db_err(e);
+    }
+    let transfer = with_lines(vec![transfer], lines).remove(0);
+    MyBaseResponse::ok(Some(transfer), Some(message.into()))
+}
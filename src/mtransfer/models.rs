@@ -0,0 +1,123 @@
+use std::fmt;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+
+use crate::util::money::Money;
+
+/// Where a transfer is in its life: created as a draft, dispatched (stock has
+/// left the source and is in transit), then received at the destination.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, sqlx::Type, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "transfer_status", rename_all = "snake_case")]
+pub enum TransferStatus {
+    Draft,
+    InTransit,
+    Received,
+    Cancelled,
+}
+
+impl fmt::Display for TransferStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            TransferStatus::Draft => "draft",
+            TransferStatus::InTransit => "in_transit",
+            TransferStatus::Received => "received",
+            TransferStatus::Cancelled => "cancelled",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl TransferStatus {
+    /// States a transfer may move to from this one. Received and cancelled are terminal.
+    pub fn allowed_next(&self) -> Vec<TransferStatus> {
+        match self {
+            TransferStatus::Draft => vec![TransferStatus::InTransit, TransferStatus::Cancelled],
+            TransferStatus::InTransit => vec![TransferStatus::Received],
+            TransferStatus::Received | TransferStatus::Cancelled => vec![],
+        }
+    }
+
+    pub fn can_transition_to(&self, next: &TransferStatus) -> bool {
+        self.allowed_next().contains(next)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, FromRow, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct TransferModel {
+    pub id: uuid::Uuid,
+    #[serde(rename = "transferNumber")]
+    pub transfer_number: i64,
+    #[serde(rename = "fromLocationId")]
+    pub from_location_id: uuid::Uuid,
+    #[serde(rename = "fromLocationName")]
+    pub from_location_name: String,
+    #[serde(rename = "toLocationId")]
+    pub to_location_id: uuid::Uuid,
+    #[serde(rename = "toLocationName")]
+    pub to_location_name: String,
+    pub status: TransferStatus,
+    pub note: Option<String>,
+    #[serde(rename = "createdBy")]
+    pub created_by: Option<uuid::Uuid>,
+    #[serde(rename = "dispatchedBy")]
+    pub dispatched_by: Option<uuid::Uuid>,
+    #[serde(rename = "dispatchedAt")]
+    pub dispatched_at: Option<DateTime<Utc>>,
+    #[serde(rename = "receivedBy")]
+    pub received_by: Option<uuid::Uuid>,
+    #[serde(rename = "receivedAt")]
+    pub received_at: Option<DateTime<Utc>>,
+    #[serde(rename = "cancelledBy")]
+    pub cancelled_by: Option<uuid::Uuid>,
+    #[serde(rename = "cancelledAt")]
+    pub cancelled_at: Option<DateTime<Utc>>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, FromRow, ToSchema, PartialEq)]
+#[allow(non_snake_case)]
+pub struct TransferLineModel {
+    pub id: i64,
+    #[serde(rename = "transferId")]
+    pub transfer_id: uuid::Uuid,
+    #[serde(rename = "productId")]
+    pub product_id: uuid::Uuid,
+    #[serde(rename = "productName")]
+    pub product_name: String,
+    /// Base units sent.
+    pub quantity: i32,
+    /// Base units that arrived; empty until the transfer is received.
+    #[serde(rename = "receivedQuantity")]
+    pub received_quantity: Option<i32>,
+    /// `quantity - received_quantity`: units lost or short on arrival.
+    pub discrepancy: Option<i32>,
+    #[serde(rename = "discrepancyNote")]
+    pub discrepancy_note: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, ToSchema, PartialEq)]
+pub struct TransferWithLinesModel {
+    #[serde(flatten)]
+    pub transfer: TransferModel,
+    pub lines: Vec<TransferLineModel>,
+}
+
+/// Base units a dispatched line took from one source lot, or from unlotted stock.
+#[derive(Clone, Debug, FromRow, PartialEq)]
+pub struct TransferAllocation {
+    pub id: i64,
+    pub lot_id: Option<uuid::Uuid>,
+    pub lot_number: Option<String>,
+    pub expiry_date: Option<NaiveDate>,
+    pub unit_cost: Option<Money>,
+    pub quantity: i32,
+    /// What the units cost when they left the source.
+    pub cost_value: Money,
+}
@@ -0,0 +1,107 @@
+use axum::{
+    Extension, Router,
+    extract::State,
+    routing::{get, post},
+};
+
+use crate::{
+    AppState,
+    mauth::{
+        layers::{MyAuthLayer, MyAuthPermsLayer},
+        middlewares::JWTAuthMiddleware,
+    },
+    mtransfer::{
+        self,
+        schema::{AddTransferSchema, GetTransfersSchema, ReceiveTransferSchema, TransferIdSchema},
+    },
+};
+
+pub fn create_transfer_router(app: AppState) -> Router {
+    return Router::new()
+        .route(
+            "/get",
+            get(
+                |pool: axum::extract::State<AppState>,
+                 params: axum::extract::Query<GetTransfersSchema>| async move {
+                    let state = AppState {
+                        db: pool.0.db,
+                        env: pool.0.env,
+                    };
+                    return mtransfer::handlers::get_transfers_handler(params, State(state)).await;
+                },
+            ),
+        )
+        .route(
+            "/add",
+            post(
+                |pool: axum::extract::State<AppState>,
+                 Extension(auth): Extension<JWTAuthMiddleware>,
+                 payload: axum::extract::Json<AddTransferSchema>| async move {
+                    let state = AppState {
+                        db: pool.0.db,
+                        env: pool.0.env,
+                    };
+                    return mtransfer::handlers::add_transfer_handler(auth, payload, State(state))
+                        .await;
+                },
+            ),
+        )
+        .route(
+            "/dispatch",
+            post(
+                |pool: axum::extract::State<AppState>,
+                 Extension(auth): Extension<JWTAuthMiddleware>,
+                 payload: axum::extract::Json<TransferIdSchema>| async move {
+                    let state = AppState {
+                        db: pool.0.db,
+                        env: pool.0.env,
+                    };
+                    return mtransfer::handlers::dispatch_transfer_handler(
+                        auth,
+                        payload,
+                        State(state),
+                    )
+                    .await;
+                },
+            )
+            .layer(MyAuthPermsLayer {}),
+        )
+        .route(
+            "/receive",
+            post(
+                |pool: axum::extract::State<AppState>,
+                 Extension(auth): Extension<JWTAuthMiddleware>,
+                 payload: axum::extract::Json<ReceiveTransferSchema>| async move {
+                    let state = AppState {
+                        db: pool.0.db,
+                        env: pool.0.env,
+                    };
+                    return mtransfer::handlers::receive_transfer_handler(
+                        auth,
+                        payload,
+                        State(state),
+                    )
+                    .await;
+                },
+            )
+            .layer(MyAuthPermsLayer {}),
+        )
+        .route(
+            "/cancel",
+            post(
+                |pool: axum::extract::State<AppState>,
+                 Extension(auth): Extension<JWTAuthMiddleware>,
+                 payload: axum::extract::Json<TransferIdSchema>| async move {
+                    let state = AppState {
+                        db: pool.0.db,
+                        env: pool.0.env,
+                    };
+                    return mtransfer::handlers::cancel_transfer_handler(auth, payload, State(state))
+                        .await;
+                },
+            )
+            .layer(MyAuthPermsLayer {}),
+        )
+        .layer(MyAuthLayer { state: app.clone() })
+        .with_state(app);
+}
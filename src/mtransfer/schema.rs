@@ -0,0 +1,46 @@
+use utoipa::{IntoParams, ToSchema};
+
+use crate::mtransfer::models::TransferStatus;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct TransferLineSchema {
+    pub product_id: uuid::Uuid,
+    /// Base units to send.
+    pub quantity: i32,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct AddTransferSchema {
+    pub from_location_id: uuid::Uuid,
+    pub to_location_id: uuid::Uuid,
+    pub note: Option<String>,
+    pub lines: Vec<TransferLineSchema>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct TransferIdSchema {
+    pub id: uuid::Uuid,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct ReceiveTransferLineSchema {
+    pub line_id: i64,
+    /// Base units that arrived, at most the quantity sent.
+    pub received_quantity: i32,
+    /// Why the line arrived short.
+    pub note: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, ToSchema, PartialEq)]
+pub struct ReceiveTransferSchema {
+    pub id: uuid::Uuid,
+    /// Lines that arrived short. Lines left out are received in full.
+    pub lines: Option<Vec<ReceiveTransferLineSchema>>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, ToSchema, IntoParams, PartialEq)]
+pub struct GetTransfersSchema {
+    pub status: Option<TransferStatus>,
+    /// Transfers leaving from or arriving at this location.
+    pub location_id: Option<uuid::Uuid>,
+}
@@ -1,7 +1,10 @@
 use crate::AppState;
 use crate::musers::models::MUserModel;
-use crate::musers::schema::{AddUserSchema, DeleteUsersSchema, UpdateUsersSchema};
-use crate::shared_var::{FilterOptions, MyBaseResponse};
+use crate::musers::schema::{
+    AddUserSchema, DeleteUsersSchema, UpdateUsersSchema, UserFilterOptions,
+};
+use crate::shared_var::MyBaseResponse;
+use crate::util::pagination::{Keyed, PageRequest, Sort, SortField, check_range};
 use crate::util::passsword::hash_password;
 use axum::Json;
 use axum::extract::{Query, State};
@@ -9,15 +12,41 @@ use axum::extract::{Query, State};
 use chrono::Utc;
 use sqlx::query_as;
 
+/// Fields users may be sorted by. Relevance comes first so lists that are
+/// not a search can leave it out.
+static USER_SORTS: [SortField; 4] = [
+    SortField {
+        name: "relevance",
+        sql: "ts_rank(u.search_tsv, plainto_tsquery('english', $1))",
+        sql_type: "REAL",
+    },
+    SortField { name: "username", sql: "lower(u.username)", sql_type: "TEXT" },
+    SortField { name: "email", sql: "lower(u.email)", sql_type: "TEXT" },
+    SortField {
+        name: "createdAt",
+        sql: "COALESCE(u.created_at, '-infinity'::timestamptz)",
+        sql_type: "TIMESTAMPTZ",
+    },
+];
+
+/// Users matching the search (`$1`) and creation time bounds (`$2`, `$3`).
+const USER_LIST_FROM: &str = r#"
+    FROM users u
+    WHERE ($1::TEXT IS NULL OR u.search_tsv @@ plainto_tsquery('english', $1))
+      AND ($2::TIMESTAMPTZ IS NULL OR u.created_at >= $2)
+      AND ($3::TIMESTAMPTZ IS NULL OR u.created_at <= $3)
+"#;
+
 #[utoipa::path(
     get,
     path = "/api/v1/users/get", 
     tag = "Users",
     params(
-        FilterOptions
+        UserFilterOptions
     ),
     responses(
-        (status = 200, description = "Users fetched successfully", body = MyBaseResponse<Vec<MUserModel>>),
+        (status = 200, description = "One page of users, with its place in the list in `meta`", body = MyBaseResponse<Vec<MUserModel>>),
+        (status = 400, description = "Unknown sort field, invalid cursor or an empty range"),
         (status = 409, description = "Database error", body = MyBaseResponse<Vec<MUserModel>>),
     ),
      security(("bearerAuth" = [])), 
@@ -25,77 +54,70 @@ use sqlx::query_as;
 
 pub async fn get_users_handler(
     State(app): State<AppState>,
-    Query(opts): Query<FilterOptions>,
+    Query(opts): Query<UserFilterOptions>,
 ) -> MyBaseResponse<Vec<MUserModel>> {
-    // Implementation for getting users with filtering, pagination, etc. based on FilterOptions
-    if opts == FilterOptions::default() {
-        // If no filter options provided, return all users
-        let insert_sql = r#"
-            SELECT *
-            FROM users
-            ORDER BY created_at DESC
-            "#;
-        let res = query_as::<_, MUserModel>(insert_sql)
-            .fetch_all(&app.db)
-            .await;
-        return match res {
-            Ok(user) => MyBaseResponse::ok(Some(user), Some("User created".into())),
-            Err(e) => {
-                eprintln!("database insert error: {}", e);
-                MyBaseResponse::db_err(e)
-            }
-        };
-    }
-    if let Some(search_term) = opts.search {
-        let pattern = format!("%{}%", search_term);
-        let limit = opts.limit.unwrap_or(10);
-        let offset = (opts.page.unwrap_or(1) - 1) * limit;
-
-        let search_sql = r#"
-                SELECT *,
-                ts_rank(search_tsv, plainto_tsquery('english', $1)) AS rank
-                FROM users
-                WHERE search_tsv @@ plainto_tsquery('english', $1)
-                ORDER BY rank DESC
-                LIMIT $2 OFFSET $3;
-                    "#;
-
-        let res = query_as::<_, MUserModel>(search_sql)
-            .bind(&pattern)
-            .bind(limit)
-            .bind(offset)
-            .fetch_all(&app.db)
-            .await;
-
-        return match res {
-            Ok(users) => {
-                println!("Fetched users: {:?}", users);
-                MyBaseResponse::ok(Some(users), Some("Users fetched".into()))
-            }
-            Err(e) => {
-                eprintln!("database query error: {}", e);
-                MyBaseResponse::db_err(e)
-            }
-        };
+    let search = opts
+        .search
+        .as_ref()
+        .map(|term| term.trim().to_string())
+        .filter(|term| !term.is_empty());
+    // Relevance only orders a search.
+    let (sorts, default_sort) = match search {
+        Some(_) => (&USER_SORTS[..], "-relevance"),
+        None => (&USER_SORTS[1..], "-createdAt"),
+    };
+    let page = match Sort::parse(opts.sort.as_deref(), sorts, default_sort)
+        .and_then(|sort| PageRequest::new(sort, opts.page, opts.limit, opts.cursor.as_deref()))
+    {
+        Ok(p) => p,
+        Err(e) => return e.cast(),
+    };
+    if let Err(e) = check_range("created", opts.created_from.as_ref(), opts.created_to.as_ref()) {
+        return e.cast();
     }
-    let insert_sql = r#"
-            SELECT *
-            FROM users
-            ORDER BY created_at DESC
-            LIMIT $1 OFFSET $2
-            "#;
-    let res = query_as::<_, MUserModel>(insert_sql)
-        .bind(opts.limit.unwrap_or(10))
-        .bind((opts.page.unwrap_or(1) - 1) * opts.limit.unwrap_or(10))
+
+    let count_sql = format!("SELECT COUNT(*) {USER_LIST_FROM}");
+    let total = sqlx::query_scalar::<_, i64>(&count_sql)
+        .bind(&search)
+        .bind(opts.created_from)
+        .bind(opts.created_to)
+        .fetch_one(&app.db)
+        .await;
+    let total = match total {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("database query error: {}", e);
+            return MyBaseResponse::db_err(e);
+        }
+    };
+
+    let select_sql = format!(
+        "SELECT u.*, {} {USER_LIST_FROM} AND {} ORDER BY {} LIMIT $4 OFFSET $5",
+        page.sort.key_columns("u.id"),
+        page.sort.after("u.id", 6),
+        page.sort.order_by("u.id"),
+    );
+    let res = query_as::<_, Keyed<MUserModel>>(&select_sql)
+        .bind(&search)
+        .bind(opts.created_from)
+        .bind(opts.created_to)
+        .bind(page.fetch_limit())
+        .bind(page.offset())
+        .bind(page.cursor_key())
+        .bind(page.cursor_id())
         .fetch_all(&app.db)
         .await;
-    return match res {
-        Ok(user) => MyBaseResponse::ok(Some(user), Some("User created".into())),
+
+    match res {
+        Ok(rows) => {
+            let (users, meta) = page.finish(rows, total);
+            MyBaseResponse::page(users, meta, Some("Users fetched".into()))
+        }
         Err(e) => {
-            eprintln!("database insert error: {}", e);
+            eprintln!("database query error: {}", e);
             MyBaseResponse::db_err(e)
         }
-    };
+    }
 }
 
 #[utoipa::path(
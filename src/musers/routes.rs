@@ -5,11 +5,9 @@ use crate::mproduct::models::ProductModel;
 use crate::musers::handlers::{
     create_new_user_handler, delete_users_handler, get_users_handler, update_users_handler,
 };
-use crate::musers::schema::{DeleteUsersSchema, UpdateUsersSchema};
-use crate::shared_var::FilterOptions;
+use crate::musers::schema::{DeleteUsersSchema, UpdateUsersSchema, UserFilterOptions};
 use crate::{AppState, shared_var::MyBaseResponse};
 use axum::Json;
-use axum::extract::Query;
 use axum::routing::{delete, post, put};
 use axum::{Router, extract::State, routing::get};
 
@@ -53,18 +51,12 @@ pub fn create_user_router(app: AppState) -> Router {
             "/get",
             get(
                 |pool: axum::extract::State<AppState>,
-                 filter: axum::extract::Query<FilterOptions>| async move {
+                 filter: axum::extract::Query<UserFilterOptions>| async move {
                     let app = AppState {
                         db: pool.db.clone(),
                         env: pool.env.clone(),
                     };
-                    let op = Query(FilterOptions {
-                        limit: filter.limit.as_ref().and_then(|f| Some(f.clone())),
-                        page: filter.page.as_ref().and_then(|f| Some(f.clone())),
-                        // search: Some("Amoxil".to_string()),
-                        search: filter.search.as_ref().and_then(|f| Some(f.clone())),
-                    });
-                    return get_users_handler(State(app), op).await;
+                    return get_users_handler(State(app), filter).await;
                 },
             )
             .layer(MyAuthPermsLayer {}),
@@ -1,5 +1,5 @@
 use chrono::{DateTime, Utc};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 use validator::Validate;
 
 use crate::musers::models::UserRole;
@@ -56,3 +56,21 @@ pub struct DeleteUsersSchema {
     #[serde()]
     pub id: uuid::Uuid,
 }
+
+#[derive(Debug, Default, Clone, serde::Deserialize, ToSchema, IntoParams, PartialEq)]
+pub struct UserFilterOptions {
+    pub page: Option<i64>,
+    /// 10 by default, at most 100.
+    pub limit: Option<i64>,
+    /// Words from the username, name or email.
+    pub search: Option<String>,
+    /// `relevance` (searches only), `username`, `email` or `createdAt`;
+    /// prefix with `-` for descending. Searches default to `-relevance`,
+    /// other lists to `-createdAt`.
+    pub sort: Option<String>,
+    /// `nextCursor` of the previous page, to resume after it instead of
+    /// counting pages.
+    pub cursor: Option<String>,
+    pub created_from: Option<DateTime<Utc>>,
+    pub created_to: Option<DateTime<Utc>>,
+}
@@ -11,8 +11,9 @@ use utoipa::Modify;
 
     
 
-use crate::{AppState, mauth, mcart, mproduct, musers};
+use crate::{AppState, mauth, mcart, mcategory, mlocation, mproduct, mpurchase, mstock, msupplier, mtransfer, musers};
 use crate::util::helpers::map_pg_database_error;
+use crate::util::pagination::PageMeta;
 
 
 // 
@@ -22,6 +23,9 @@ pub struct MyBaseResponse<T> {
     pub code: u32,
     pub message: String,
     pub data: Option<T>,
+    /// Paging details, sent with list responses only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Box<PageMeta>>,
 }
 
 impl<T> MyBaseResponse<T> {
@@ -31,6 +35,17 @@ impl<T> MyBaseResponse<T> {
             code: 200,
             message: message.unwrap_or("OK".into()),
             data,
+            meta: None,
+        }
+    }
+
+    /// Successful response carrying one page of a list.
+    pub fn page(data: T, meta: PageMeta, message: Option<String>) -> Self {
+        Self {
+            code: 200,
+            message: message.unwrap_or("OK".into()),
+            data: Some(data),
+            meta: Some(Box::new(meta)),
         }
     }
 
@@ -40,6 +55,17 @@ impl<T> MyBaseResponse<T> {
             code,
             message: message.into(),
             data: None,
+            meta: None,
+        }
+    }
+
+    /// Re-types an error response so it can be returned from a handler with a different payload.
+    pub fn cast<U>(self) -> MyBaseResponse<U> {
+        MyBaseResponse {
+            code: self.code,
+            message: self.message,
+            data: None,
+            meta: None,
         }
     }
         pub fn db_err(e: sqlx::Error) -> Self {
@@ -49,6 +75,7 @@ impl<T> MyBaseResponse<T> {
                 code: 404,
                 message: "Record not found".into(),
                 data: None,
+                meta: None,
             },
             sqlx::Error::Database(db) => {
                 let error = map_pg_database_error(db.as_ref());
@@ -57,12 +84,14 @@ impl<T> MyBaseResponse<T> {
                     code: error.code.parse().unwrap_or(500),
                     message: error.message,
                     data: None,
+                    meta: None,
                 }
             }
             _ => Self {
                 code: 500,
                 message: format!("Database error: {}", e),
                 data: None,
+                meta: None,
             },
         }
     }
@@ -99,11 +128,61 @@ pub struct FilterOptions {
         mproduct::handlers::add_product_handler,
         mproduct::handlers::update_product_handler,
         mproduct::handlers::del_product_handler,
+        mproduct::handlers::adjust_stock_handler,
+        mproduct::handlers::add_product_lot_handler,
+        mproduct::handlers::get_product_lots_handler,
+        mproduct::handlers::lookup_product_handler,
+        mproduct::handlers::get_product_barcodes_handler,
+        mproduct::handlers::add_product_barcode_handler,
+        mproduct::handlers::delete_product_barcode_handler,
+        mproduct::handlers::autocomplete_products_handler,
+        mproduct::handlers::get_bundle_handler,
+        mproduct::handlers::update_bundle_handler,
+        mproduct::handlers::print_labels_handler,
         mcart::handlers::create_cart_handler,
         mcart::handlers::get_cart_by_user_handler,
         mcart::handlers::get_open_cart_by_user_handler,
         mcart::handlers::add_item_to_cart_handler,
         mcart::handlers::update_item_in_cart_handler,
+        mcart::handlers::checkout_cart_handler,
+        mcart::handlers::update_cart_status_handler,
+        mcart::handlers::refund_cart_handler,
+        mcart::handlers::delete_cart_item_handler,
+        mcart::handlers::clear_cart_handler,
+        mstock::handlers::get_stock_history_handler,
+        mstock::handlers::get_expiry_report_handler,
+        mstock::handlers::write_off_expired_handler,
+        mstock::handlers::get_stock_valuation_handler,
+        mstock::handlers::get_costing_method_handler,
+        mstock::handlers::set_costing_method_handler,
+        mstock::handlers::get_low_stock_handler,
+        mlocation::handlers::get_locations_handler,
+        mlocation::handlers::add_location_handler,
+        mlocation::handlers::update_location_handler,
+        mtransfer::handlers::get_transfers_handler,
+        mtransfer::handlers::add_transfer_handler,
+        mtransfer::handlers::dispatch_transfer_handler,
+        mtransfer::handlers::receive_transfer_handler,
+        mtransfer::handlers::cancel_transfer_handler,
+        mcategory::handlers::get_categories_handler,
+        mcategory::handlers::add_category_handler,
+        mcategory::handlers::update_category_handler,
+        mcategory::handlers::delete_category_handler,
+        msupplier::handlers::get_suppliers_handler,
+        msupplier::handlers::add_supplier_handler,
+        msupplier::handlers::update_supplier_handler,
+        msupplier::handlers::delete_supplier_handler,
+        msupplier::handlers::get_product_suppliers_handler,
+        msupplier::handlers::link_product_supplier_handler,
+        msupplier::handlers::unlink_product_supplier_handler,
+        mpurchase::handlers::get_purchase_orders_handler,
+        mpurchase::handlers::add_purchase_order_handler,
+        mpurchase::handlers::get_purchase_suggestions_handler,
+        mpurchase::handlers::draft_suggested_orders_handler,
+        mpurchase::handlers::send_purchase_order_handler,
+        mpurchase::handlers::receive_purchase_order_handler,
+        mpurchase::handlers::close_purchase_order_handler,
+        mpurchase::handlers::cancel_purchase_order_handler,
 
 
     ),
@@ -112,6 +191,12 @@ pub struct FilterOptions {
             mproduct::schema::AddProductSchema,
             mproduct::schema::UpdateProductSchema,
             mproduct::schema::DeleteProductSchema,
+            mproduct::schema::AdjustStockSchema,
+            mproduct::schema::AddProductLotSchema,
+            mproduct::schema::GetProductLotsSchema,
+            mproduct::models::ProductLotModel,
+            MyBaseResponse::<mproduct::models::ProductLotModel>,
+            MyBaseResponse::<mproduct::models::ProductLotsModel>,
             mproduct::models::ProductModel,
             mauth::schemas::LoginUserSchema,
             musers::models::MUserModel,
@@ -119,7 +204,9 @@ pub struct FilterOptions {
             musers::schema::AddUserSchema,
             musers::schema::UpdateUsersSchema,
             musers::schema::DeleteUsersSchema,
+            musers::schema::UserFilterOptions,
             FilterOptions,
+            PageMeta,
             MyBaseResponse::<mproduct::models::ProductModel>,
             MyBaseResponse::<musers::models::MUserModel>,
             MyBaseResponse<Vec<mproduct::models::ProductModel>>,
@@ -132,10 +219,124 @@ pub struct FilterOptions {
             mcart::schemas::ClearCartSchema,
             mcart::schemas::CheckoutCartSchema,
             mcart::schemas::GetCartByUserSchema,
+            mcart::schemas::AdminOverrideQuery,
             MyBaseResponse::<mcart::models::CartModel>,
             MyBaseResponse::<mcart::models::CartItemModel>,
             MyBaseResponse::<mcart::models::CartWithItemsModel>,
             MyBaseResponse::<Vec<mcart::models::CartItemWithProductModel>>,
+            mcart::models::OrderModel,
+            mcart::models::OrderItemModel,
+            MyBaseResponse::<mcart::models::OrderWithItemsModel>,
+            mcart::schemas::RefundCartSchema,
+            mcart::schemas::RefundLineSchema,
+            mcart::models::RefundModel,
+            mcart::models::RefundItemModel,
+            MyBaseResponse::<mcart::models::RefundWithItemsModel>,
+            mstock::models::StockMovementReason,
+            mstock::models::StockAdjustmentReason,
+            mstock::models::StockMovementModel,
+            MyBaseResponse::<mstock::models::StockHistoryModel>,
+            mstock::schemas::ExpiryReportQuery,
+            mstock::schemas::WriteOffExpiredSchema,
+            mstock::models::ExpiringLotModel,
+            mstock::models::ExpiryReportProductModel,
+            MyBaseResponse::<mstock::models::ExpiryReportModel>,
+            MyBaseResponse::<mstock::models::ExpiredWriteOffModel>,
+            mstock::models::CostingMethod,
+            mstock::models::CostingSettingsModel,
+            mstock::models::ProductValuationModel,
+            mstock::models::StockValuationModel,
+            mstock::schemas::StockValuationQuery,
+            mstock::schemas::SetCostingMethodSchema,
+            MyBaseResponse::<mstock::models::CostingSettingsModel>,
+            MyBaseResponse::<mstock::models::StockValuationModel>,
+            mstock::models::LowStockProductModel,
+            mstock::schemas::LowStockQuery,
+            MyBaseResponse::<Vec<mstock::models::LowStockProductModel>>,
+            mlocation::schemas::AddLocationSchema,
+            mlocation::schemas::UpdateLocationSchema,
+            mlocation::models::LocationModel,
+            mlocation::models::LocationStockModel,
+            MyBaseResponse::<mlocation::models::LocationModel>,
+            MyBaseResponse::<Vec<mlocation::models::LocationModel>>,
+            mproduct::schema::ProductFilterOptions,
+            mproduct::models::ProductWithStockModel,
+            MyBaseResponse::<Vec<mproduct::models::ProductWithStockModel>>,
+            mproduct::models::BarcodeType,
+            mproduct::models::ProductBarcodeModel,
+            mproduct::models::ProductLookupModel,
+            mproduct::schema::ProductLookupQuery,
+            mproduct::schema::GetProductBarcodesSchema,
+            mproduct::schema::AddProductBarcodeSchema,
+            mproduct::schema::DeleteProductBarcodeSchema,
+            mproduct::models::LabelTemplate,
+            mproduct::models::LabelFormat,
+            mproduct::schema::PrintLabelsSchema,
+            MyBaseResponse::<mproduct::models::ProductLookupModel>,
+            MyBaseResponse::<mproduct::models::ProductBarcodeModel>,
+            MyBaseResponse::<Vec<mproduct::models::ProductBarcodeModel>>,
+            mproduct::models::ProductSuggestionModel,
+            mproduct::schema::AutocompleteQuery,
+            MyBaseResponse::<Vec<mproduct::models::ProductSuggestionModel>>,
+            mproduct::models::BundlePricing,
+            mproduct::models::BundleComponentModel,
+            mproduct::models::BundleModel,
+            mproduct::schema::BundleComponentSchema,
+            mproduct::schema::GetBundleSchema,
+            mproduct::schema::UpdateBundleSchema,
+            MyBaseResponse::<mproduct::models::BundleModel>,
+            mcart::schemas::CreateCartQuery,
+            mcart::schemas::CartFilterOptions,
+            mtransfer::models::TransferStatus,
+            mtransfer::models::TransferModel,
+            mtransfer::models::TransferLineModel,
+            mtransfer::models::TransferWithLinesModel,
+            mtransfer::schema::AddTransferSchema,
+            mtransfer::schema::TransferLineSchema,
+            mtransfer::schema::TransferIdSchema,
+            mtransfer::schema::ReceiveTransferSchema,
+            mtransfer::schema::ReceiveTransferLineSchema,
+            mtransfer::schema::GetTransfersSchema,
+            MyBaseResponse::<mtransfer::models::TransferWithLinesModel>,
+            MyBaseResponse::<Vec<mtransfer::models::TransferWithLinesModel>>,
+            mcategory::models::CategoryModel,
+            mcategory::models::CategoryTreeModel,
+            mcategory::models::CategoryCatalogModel,
+            mcategory::schema::GetCategoriesSchema,
+            mcategory::schema::AddCategorySchema,
+            mcategory::schema::UpdateCategorySchema,
+            mcategory::schema::DeleteCategorySchema,
+            MyBaseResponse::<mcategory::models::CategoryModel>,
+            MyBaseResponse::<mcategory::models::CategoryCatalogModel>,
+            msupplier::models::SupplierModel,
+            msupplier::models::ProductSupplierModel,
+            msupplier::schema::AddSupplierSchema,
+            msupplier::schema::UpdateSupplierSchema,
+            msupplier::schema::DeleteSupplierSchema,
+            msupplier::schema::LinkProductSupplierSchema,
+            msupplier::schema::UnlinkProductSupplierSchema,
+            msupplier::schema::GetProductSuppliersSchema,
+            MyBaseResponse::<msupplier::models::SupplierModel>,
+            MyBaseResponse::<Vec<msupplier::models::SupplierModel>>,
+            MyBaseResponse::<msupplier::models::ProductSupplierModel>,
+            MyBaseResponse::<Vec<msupplier::models::ProductSupplierModel>>,
+            mpurchase::models::PurchaseOrderStatus,
+            mpurchase::models::PurchaseOrderModel,
+            mpurchase::models::PurchaseOrderLineModel,
+            mpurchase::models::PurchaseReceiptLineModel,
+            mpurchase::models::PurchaseOrderWithLinesModel,
+            mpurchase::schema::AddPurchaseOrderSchema,
+            mpurchase::schema::PurchaseOrderLineSchema,
+            mpurchase::schema::PurchaseOrderIdSchema,
+            mpurchase::schema::ReceivePurchaseOrderSchema,
+            mpurchase::schema::ReceivePurchaseOrderLineSchema,
+            mpurchase::schema::GetPurchaseOrdersSchema,
+            mpurchase::models::PurchaseSuggestionModel,
+            mpurchase::schema::PurchaseSuggestionsQuery,
+            mpurchase::schema::DraftSuggestedOrdersSchema,
+            MyBaseResponse::<Vec<mpurchase::models::PurchaseSuggestionModel>>,
+            MyBaseResponse::<mpurchase::models::PurchaseOrderWithLinesModel>,
+            MyBaseResponse::<Vec<mpurchase::models::PurchaseOrderWithLinesModel>>,
             
         )
     ),
@@ -143,7 +344,13 @@ pub struct FilterOptions {
         (name = "Products", description = "APIs for managing products"),
         (name = "Authentication", description = "APIs for user authentication"),
         (name = "Users", description = "APIs for managing users"),
-        (name = "Carts", description = "APIs for managing shopping carts")
+        (name = "Carts", description = "APIs for managing shopping carts"),
+        (name = "Stock", description = "APIs for stock movements and history"),
+        (name = "Locations", description = "APIs for managing stock locations"),
+        (name = "Transfers", description = "APIs for moving stock between locations"),
+        (name = "Suppliers", description = "APIs for managing suppliers and what they supply"),
+        (name = "Purchasing", description = "APIs for purchase orders and goods receiving"),
+        (name = "Categories", description = "APIs for managing nested product categories")
     ),
     modifiers(&SecurityAddon),
 
@@ -183,6 +390,27 @@ pub fn create_router(app_state: AppState) -> Router {
                     mproduct::routes::create_prod_router(app_state.clone()),
                 )
                 .nest("/cart", mcart::routes::create_cart_router(app_state.clone()),)
+                .nest("/stock", mstock::routes::create_stock_router(app_state.clone()))
+                .nest(
+                    "/locations",
+                    mlocation::routes::create_location_router(app_state.clone()),
+                )
+                .nest(
+                    "/transfers",
+                    mtransfer::routes::create_transfer_router(app_state.clone()),
+                )
+                .nest(
+                    "/suppliers",
+                    msupplier::routes::create_supplier_router(app_state.clone()),
+                )
+                .nest(
+                    "/purchase-orders",
+                    mpurchase::routes::create_purchase_router(app_state.clone()),
+                )
+                .nest(
+                    "/categories",
+                    mcategory::routes::create_category_router(app_state.clone()),
+                )
                 .merge(
                     SwaggerUi::new("/swagger")
                         .url("/api-docs/openapi.json", ApiDoc::openapi().clone()),
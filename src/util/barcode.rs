@@ -0,0 +1,77 @@
+/// Longest SKU or internal code accepted.
+pub const MAX_CODE_LEN: usize = 64;
+
+/// Whether `code` is all digits and ends in a valid GS1 check digit, the
+/// scheme EAN-13 and UPC-A share.
+pub fn has_valid_check_digit(code: &str) -> bool {
+    if code.len() < 2 || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return false;
+    }
+    let digits: Vec<u32> = code.bytes().map(|b| (b - b'0') as u32).collect();
+    let (body, check) = digits.split_at(digits.len() - 1);
+    // Weights alternate 3, 1, ... starting from the digit next to the check digit.
+    let sum: u32 = body
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, d)| if i % 2 == 0 { d * 3 } else { *d })
+        .sum();
+    (10 - sum % 10) % 10 == check[0]
+}
+
+/// Whether `code` can be used as a SKU or internal code: printable ASCII
+/// without spaces, at most [`MAX_CODE_LEN`] long.
+pub fn is_valid_code(code: &str) -> bool {
+    !code.is_empty() && code.len() <= MAX_CODE_LEN && code.bytes().all(|b| b.is_ascii_graphic())
+}
+
+/// The forms a scanned code may be stored under. A UPC-A code is an EAN-13
+/// code with a leading zero, and scanners report it either way.
+pub fn scan_variants(code: &str) -> Vec<String> {
+    let mut variants = vec![code.to_string()];
+    if code.bytes().all(|b| b.is_ascii_digit()) {
+        match code.len() {
+            12 => variants.push(format!("0{}", code)),
+            13 if code.starts_with('0') => variants.push(code[1..].to_string()),
+            _ => {}
+        }
+    }
+    variants
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_valid_check_digits() {
+        assert!(has_valid_check_digit("4006381333931"));
+        assert!(has_valid_check_digit("036000291452"));
+        assert!(has_valid_check_digit("96385074"));
+    }
+
+    #[test]
+    fn rejects_invalid_check_digits() {
+        assert!(!has_valid_check_digit("4006381333932"));
+        assert!(!has_valid_check_digit("036000291453"));
+        assert!(!has_valid_check_digit("40063813339a1"));
+        assert!(!has_valid_check_digit("7"));
+        assert!(!has_valid_check_digit(""));
+    }
+
+    #[test]
+    fn upc_a_scans_as_ean_13() {
+        assert_eq!(scan_variants("036000291452"), vec!["036000291452", "0036000291452"]);
+    }
+
+    #[test]
+    fn ean_13_with_leading_zero_scans_as_upc_a() {
+        assert_eq!(scan_variants("0036000291452"), vec!["0036000291452", "036000291452"]);
+    }
+
+    #[test]
+    fn other_codes_scan_as_themselves() {
+        assert_eq!(scan_variants("4006381333931"), vec!["4006381333931"]);
+        assert_eq!(scan_variants("SKU-000000012"), vec!["SKU-000000012"]);
+    }
+}
@@ -43,6 +43,7 @@ pub fn map_pg_database_error(db_err: &(dyn DatabaseError + 'static)) -> FieldErr
         "23502" => FieldError::new(field, "Required field missing", "400"),
         "23503" => FieldError::new(field, "Foreign key violation", "400"),
         "23514" => FieldError::new(field, "Check constraint failed", "400"),
+        "55000" => FieldError::new(field, db_err.message(), "409"),
         _ => FieldError::new(field, db_err.message(), "500"),
     }
 }
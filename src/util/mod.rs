@@ -1,4 +1,7 @@
+pub mod barcode;
 pub mod errors;
 pub mod helpers;
+pub mod money;
+pub mod pagination;
 pub mod passsword;
 pub mod token;
@@ -0,0 +1,58 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+
+/// Exact monetary amount, stored as `NUMERIC(12,2)` and serialised as a string
+/// (e.g. `"12.50"`) so clients never see binary floating point.
+pub type Money = Decimal;
+
+/// Decimal places every stored amount is rounded to.
+pub const MONEY_SCALE: u32 = 2;
+
+/// Rounds to cents, half away from zero, matching Postgres `round(numeric, 2)`.
+pub fn round_money(amount: Money) -> Money {
+    amount.round_dp_with_strategy(MONEY_SCALE, RoundingStrategy::MidpointAwayFromZero)
+}
+
+/// Line total under the same rule the database applies to `cart_items.line_total`.
+pub fn line_total(unit_amount: Money, quantity: i32) -> Money {
+    round_money(unit_amount * Money::from(quantity))
+}
+
+/// Decimal places a cost per base unit is kept to, so pack costs split into
+/// units without losing cents.
+pub const COST_SCALE: u32 = 4;
+
+/// Rounds a cost per base unit, half away from zero, matching `NUMERIC(14,4)`.
+pub fn round_cost(amount: Money) -> Money {
+    amount.round_dp_with_strategy(COST_SCALE, RoundingStrategy::MidpointAwayFromZero)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn m(s: &str) -> Money {
+        Money::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn rounds_to_cents_half_away_from_zero() {
+        assert_eq!(round_money(m("2.345")), m("2.35"));
+        assert_eq!(round_money(m("-2.345")), m("-2.35"));
+        assert_eq!(round_money(m("2.344")), m("2.34"));
+        assert_eq!(round_money(m("0.005")), m("0.01"));
+        assert_eq!(round_money(m("1.23456")).scale(), MONEY_SCALE);
+    }
+
+    #[test]
+    fn line_total_rounds_after_multiplying() {
+        assert_eq!(line_total(m("0.125"), 3), m("0.38"));
+        assert_eq!(line_total(m("19.99"), 0), m("0.00"));
+    }
+
+    #[test]
+    fn cost_keeps_four_places() {
+        assert_eq!(round_cost(m("1.23455")), m("1.2346"));
+        assert_eq!(round_cost(m("10") / m("3")), m("3.3333"));
+    }
+}
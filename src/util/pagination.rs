@@ -0,0 +1,348 @@
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use sqlx::postgres::PgRow;
+use sqlx::{FromRow, Row};
+use utoipa::ToSchema;
+
+use crate::shared_var::MyBaseResponse;
+
+/// Rows a list returns when `limit` is not given.
+pub const DEFAULT_PAGE_SIZE: i64 = 10;
+
+/// Most rows a list returns at once.
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+/// A field a list may be sorted by.
+#[derive(Debug)]
+pub struct SortField {
+    /// Name clients pass in `sort`.
+    pub name: &'static str,
+    /// SQL the rows are ordered by. It must never be NULL, or rows after a
+    /// cursor could not be found.
+    pub sql: &'static str,
+    /// Type the text form of `sql` is cast back to when resuming from a cursor.
+    pub sql_type: &'static str,
+}
+
+/// Order of a list: one allowed field, ascending or descending, with the row
+/// id breaking ties so every row has a fixed place.
+#[derive(Debug, Clone, Copy)]
+pub struct Sort {
+    pub field: &'static SortField,
+    pub descending: bool,
+}
+
+impl Sort {
+    /// Reads a `sort` parameter, `field` or `-field` for descending, against
+    /// the fields a list allows.
+    pub fn parse(
+        param: Option<&str>,
+        allowed: &'static [SortField],
+        default: &str,
+    ) -> Result<Sort, MyBaseResponse<()>> {
+        let param = param.map(str::trim).filter(|p| !p.is_empty()).unwrap_or(default);
+        let (name, descending) = match param.strip_prefix('-') {
+            Some(name) => (name, true),
+            None => (param, false),
+        };
+        match allowed.iter().find(|f| f.name == name) {
+            Some(field) => Ok(Sort { field, descending }),
+            None => {
+                let names: Vec<&str> = allowed.iter().map(|f| f.name).collect();
+                Err(MyBaseResponse::error(
+                    400,
+                    format!("Cannot sort by \"{}\"; sort by one of: {}", name, names.join(", ")),
+                ))
+            }
+        }
+    }
+
+    /// The `sort` parameter this order is asked for with.
+    pub fn param(&self) -> String {
+        if self.descending {
+            format!("-{}", self.field.name)
+        } else {
+            self.field.name.to_string()
+        }
+    }
+
+    /// `ORDER BY` terms, with `id` as the tie-breaker.
+    pub fn order_by(&self, id: &str) -> String {
+        let dir = if self.descending { "DESC" } else { "ASC" };
+        format!("{} {dir}, {id} {dir}", self.field.sql)
+    }
+
+    /// Columns selected alongside each row so a cursor can be made from it.
+    pub fn key_columns(&self, id: &str) -> String {
+        format!("({})::TEXT AS sort_key, {id} AS sort_id", self.field.sql)
+    }
+
+    /// Condition keeping only rows after a cursor, whose key and id are bound
+    /// as parameters `key` and `key + 1`. Holds for every row without one.
+    pub fn after(&self, id: &str, key: usize) -> String {
+        let op = if self.descending { "<" } else { ">" };
+        format!(
+            "(${key}::TEXT IS NULL OR ({}, {id}) {op} (${key}::TEXT::{}, ${}::UUID))",
+            self.field.sql,
+            self.field.sql_type,
+            key + 1
+        )
+    }
+}
+
+/// Where a page ended: the sort key and id of its last row.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct Cursor {
+    sort: String,
+    pub key: String,
+    pub id: uuid::Uuid,
+}
+
+impl Cursor {
+    /// Opaque token handed to clients as `nextCursor`.
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).unwrap_or_default();
+        URL_SAFE_NO_PAD.encode(json)
+    }
+
+    /// Reads a token back. It only resumes the order it was made in.
+    pub fn decode(token: &str, sort: &Sort) -> Result<Cursor, MyBaseResponse<()>> {
+        let cursor = URL_SAFE_NO_PAD
+            .decode(token.trim())
+            .ok()
+            .and_then(|json| serde_json::from_slice::<Cursor>(&json).ok())
+            .ok_or_else(|| MyBaseResponse::error(400, "Invalid cursor"))?;
+        if cursor.sort != sort.param() {
+            return Err(MyBaseResponse::error(
+                400,
+                format!(
+                    "This cursor continues a list sorted by \"{}\"; pass that sort or start again without a cursor",
+                    cursor.sort
+                ),
+            ));
+        }
+        Ok(cursor)
+    }
+}
+
+/// A row with its place in the sort order, selected with [`Sort::key_columns`].
+pub struct Keyed<T> {
+    pub row: T,
+    pub sort_key: String,
+    pub sort_id: uuid::Uuid,
+}
+
+impl<'r, T: FromRow<'r, PgRow>> FromRow<'r, PgRow> for Keyed<T> {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Keyed {
+            row: T::from_row(row)?,
+            sort_key: row.try_get("sort_key")?,
+            sort_id: row.try_get("sort_id")?,
+        })
+    }
+}
+
+/// Which rows of a list to return: a page by number, or the rows after a
+/// cursor, which stays fast however far into a large table it is.
+#[derive(Debug, Clone)]
+pub struct PageRequest {
+    pub sort: Sort,
+    pub page: i64,
+    pub limit: i64,
+    pub cursor: Option<Cursor>,
+}
+
+impl PageRequest {
+    /// A cursor takes precedence over `page`.
+    pub fn new(
+        sort: Sort,
+        page: Option<i64>,
+        limit: Option<i64>,
+        cursor: Option<&str>,
+    ) -> Result<PageRequest, MyBaseResponse<()>> {
+        let cursor = match cursor.filter(|c| !c.trim().is_empty()) {
+            Some(token) => Some(Cursor::decode(token, &sort)?),
+            None => None,
+        };
+        let page = if cursor.is_some() { 1 } else { page.unwrap_or(1).max(1) };
+        let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+        if (page - 1).checked_mul(limit).is_none() {
+            return Err(MyBaseResponse::error(400, format!("Page {page} is out of range")));
+        }
+        Ok(PageRequest { sort, page, limit, cursor })
+    }
+
+    /// Rows to fetch: one more than a page, to tell whether another follows.
+    pub fn fetch_limit(&self) -> i64 {
+        self.limit + 1
+    }
+
+    pub fn offset(&self) -> i64 {
+        (self.page - 1) * self.limit
+    }
+
+    pub fn cursor_key(&self) -> Option<&str> {
+        self.cursor.as_ref().map(|c| c.key.as_str())
+    }
+
+    pub fn cursor_id(&self) -> Option<uuid::Uuid> {
+        self.cursor.as_ref().map(|c| c.id)
+    }
+
+    /// Trims the extra row fetched by [`PageRequest::fetch_limit`] and
+    /// describes the page.
+    pub fn finish<T>(&self, mut rows: Vec<Keyed<T>>, total: i64) -> (Vec<T>, PageMeta) {
+        let has_more = rows.len() as i64 > self.limit;
+        rows.truncate(self.limit as usize);
+        let next_cursor = match rows.last() {
+            Some(last) if has_more => Some(
+                Cursor {
+                    sort: self.sort.param(),
+                    key: last.sort_key.clone(),
+                    id: last.sort_id,
+                }
+                .encode(),
+            ),
+            _ => None,
+        };
+        let meta = PageMeta {
+            total,
+            page: if self.cursor.is_some() { None } else { Some(self.page) },
+            limit: self.limit,
+            total_pages: (total + self.limit - 1) / self.limit,
+            has_more,
+            next_cursor,
+            sort: self.sort.param(),
+        };
+        (rows.into_iter().map(|r| r.row).collect(), meta)
+    }
+}
+
+/// Where a page sits in its list.
+#[derive(serde::Serialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct PageMeta {
+    /// Rows matching the filters, across every page.
+    pub total: i64,
+    /// Page number, or null when the page followed a cursor.
+    pub page: Option<i64>,
+    pub limit: i64,
+    #[serde(rename = "totalPages")]
+    pub total_pages: i64,
+    #[serde(rename = "hasMore")]
+    pub has_more: bool,
+    /// Pass as `cursor` to get the rows after this page.
+    #[serde(rename = "nextCursor")]
+    pub next_cursor: Option<String>,
+    /// Order the rows are in, as a `sort` parameter.
+    pub sort: String,
+}
+
+/// Rejects a range filter whose lower bound is above its upper bound.
+pub fn check_range<T: PartialOrd>(
+    name: &str,
+    min: Option<&T>,
+    max: Option<&T>,
+) -> Result<(), MyBaseResponse<()>> {
+    match (min, max) {
+        (Some(min), Some(max)) if min > max => Err(MyBaseResponse::error(
+            400,
+            format!("The lower bound of {name} is above its upper bound"),
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static FIELDS: [SortField; 2] = [
+        SortField { name: "name", sql: "p.name", sql_type: "TEXT" },
+        SortField { name: "createdAt", sql: "p.created_at", sql_type: "TIMESTAMPTZ" },
+    ];
+
+    fn sort(param: &str) -> Sort {
+        Sort::parse(Some(param), &FIELDS, "name").unwrap()
+    }
+
+    fn cursor(sort: &Sort) -> Cursor {
+        Cursor {
+            sort: sort.param(),
+            key: "Widget".to_string(),
+            id: uuid::Uuid::new_v4(),
+        }
+    }
+
+    #[test]
+    fn parses_allowed_sorts() {
+        let asc = sort("name");
+        assert_eq!(asc.field.name, "name");
+        assert!(!asc.descending);
+        let desc = sort("-createdAt");
+        assert_eq!(desc.field.name, "createdAt");
+        assert!(desc.descending);
+        assert_eq!(desc.param(), "-createdAt");
+        assert_eq!(desc.order_by("p.id"), "p.created_at DESC, p.id DESC");
+    }
+
+    #[test]
+    fn falls_back_to_default_sort() {
+        assert_eq!(Sort::parse(None, &FIELDS, "-name").unwrap().param(), "-name");
+        assert_eq!(Sort::parse(Some("  "), &FIELDS, "name").unwrap().param(), "name");
+    }
+
+    #[test]
+    fn rejects_unknown_sort() {
+        let err = Sort::parse(Some("-price"), &FIELDS, "name").unwrap_err();
+        assert_eq!(err.code, 400);
+        assert_eq!(err.message, "Cannot sort by \"price\"; sort by one of: name, createdAt");
+    }
+
+    #[test]
+    fn cursor_round_trips() {
+        let sort = sort("-name");
+        let original = cursor(&sort);
+        let decoded = Cursor::decode(&original.encode(), &sort).unwrap();
+        assert_eq!(decoded.sort, "-name");
+        assert_eq!(decoded.key, original.key);
+        assert_eq!(decoded.id, original.id);
+    }
+
+    #[test]
+    fn rejects_tampered_cursor() {
+        let sort = sort("name");
+        let mut token = cursor(&sort).encode();
+        token.insert(3, '!');
+        let err = Cursor::decode(&token, &sort).unwrap_err();
+        assert_eq!(err.code, 400);
+        assert_eq!(err.message, "Invalid cursor");
+        assert_eq!(Cursor::decode("not-a-cursor", &sort).unwrap_err().code, 400);
+    }
+
+    #[test]
+    fn rejects_cursor_from_another_sort() {
+        let token = cursor(&sort("name")).encode();
+        let err = Cursor::decode(&token, &sort("-name")).unwrap_err();
+        assert_eq!(err.code, 400);
+        assert!(err.message.starts_with("This cursor continues a list sorted by \"name\""));
+    }
+
+    #[test]
+    fn cursor_resets_page() {
+        let sort = sort("name");
+        let token = cursor(&sort).encode();
+        let req = PageRequest::new(sort, Some(5), Some(500), Some(&token)).unwrap();
+        assert_eq!(req.page, 1);
+        assert_eq!(req.limit, MAX_PAGE_SIZE);
+        assert_eq!(req.offset(), 0);
+    }
+
+    #[test]
+    fn rejects_page_whose_offset_overflows() {
+        let err = PageRequest::new(sort("name"), Some(i64::MAX), Some(50), None).unwrap_err();
+        assert_eq!(err.code, 400);
+        let last = i64::MAX / 50 + 1;
+        let req = PageRequest::new(sort("name"), Some(last), Some(50), None).unwrap();
+        assert_eq!(req.offset(), (last - 1) * 50);
+    }
+}